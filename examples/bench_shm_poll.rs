@@ -0,0 +1,60 @@
+//! Full version scan (`try_poll`) vs block-counter scan (`fast_poll`) on a
+//! quiet matrix: one symbol written every `POLLS_PER_UPDATE` polls.
+//!
+//! cargo run --release --example bench_shm_poll
+
+use aleph_tx::shm_reader::{NUM_EXCHANGES, NUM_SYMBOLS, ShmReader};
+use std::os::unix::fs::FileExt;
+use std::time::{Duration, Instant};
+
+const POLLS: usize = 2_000_000;
+const POLLS_PER_UPDATE: usize = 1_000;
+
+/// Version table + BBO matrix + global tick + 32 block counters
+const LEN: usize = NUM_SYMBOLS * 8 + NUM_SYMBOLS * NUM_EXCHANGES * 64 + 8 + NUM_SYMBOLS / 64 * 8;
+const BLOCKS_OFFSET: usize = LEN - NUM_SYMBOLS / 64 * 8;
+
+fn run(name: &str, poll: fn(&mut ShmReader) -> Option<u16>) -> Duration {
+    let path = std::env::temp_dir().join(format!("aleph-bench-{}-{}", name, std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    file.set_len(LEN as u64).unwrap();
+    let mut reader = ShmReader::open(path.to_str().unwrap(), NUM_SYMBOLS).unwrap();
+
+    let mut versions = vec![0u64; NUM_SYMBOLS];
+    let mut blocks = vec![0u64; NUM_SYMBOLS / 64];
+    let mut hits = 0usize;
+    let mut elapsed = Duration::ZERO;
+    for i in 0..POLLS {
+        if i % POLLS_PER_UPDATE == 0 {
+            // Feeder write, outside the timed section
+            let sym = (i / POLLS_PER_UPDATE * 397) % NUM_SYMBOLS;
+            versions[sym] += 1;
+            blocks[sym / 64] += 1;
+            file.write_at(&versions[sym].to_le_bytes(), (sym * 8) as u64)
+                .unwrap();
+            let offset = BLOCKS_OFFSET + sym / 64 * 8;
+            file.write_at(&blocks[sym / 64].to_le_bytes(), offset as u64)
+                .unwrap();
+        }
+        let start = Instant::now();
+        hits += poll(&mut reader).is_some() as usize;
+        elapsed += start.elapsed();
+    }
+    std::fs::remove_file(&path).ok();
+    println!(
+        "{:<10} {:>8.1} ns/poll ({} updates seen)",
+        name,
+        elapsed.as_nanos() as f64 / POLLS as f64,
+        hits
+    );
+    elapsed
+}
+
+fn main() {
+    let full = run("try_poll", ShmReader::try_poll);
+    let fast = run("fast_poll", ShmReader::fast_poll);
+    println!(
+        "speedup    {:>8.1}x",
+        full.as_secs_f64() / fast.as_secs_f64()
+    );
+}
@@ -7,12 +7,17 @@ import (
 	"os/signal"
 	"sync"
 	"syscall"
+	"time"
 
 	"github.com/AlephTX/aleph-tx/feeder/config"
 	"github.com/AlephTX/aleph-tx/feeder/exchanges"
 	"github.com/AlephTX/aleph-tx/feeder/shm"
 )
 
+// tickInterval is the period of the shared global sequence: one NextTick per
+// feeder loop iteration, so a sequence gap of N is N milliseconds.
+const tickInterval = time.Millisecond
+
 func main() {
 	log.Println("🐙 AlephTX Feeder starting (Configuration Driven)...")
 
@@ -76,6 +81,22 @@ func main() {
 
 	var wg sync.WaitGroup
 
+	// Feeder loop: advance the global tick at a fixed rate
+	wg.Add(1)
+	go func() {
+		defer wg.Done()
+		ticker := time.NewTicker(tickInterval)
+		defer ticker.Stop()
+		for {
+			select {
+			case <-ctx.Done():
+				return
+			case <-ticker.C:
+				matrix.NextTick()
+			}
+		}
+	}()
+
 	// Convert unified config to exchange map for backward compatibility
 	exchangeConfigs := cfg.ToExchangeMap()
 
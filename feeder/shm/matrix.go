@@ -8,6 +8,10 @@
 // Memory layout (single mmap, cache-line friendly):
 //   - SymbolVersions[2048]: AtomicU64 per symbol (16 KB, fits in L1d)
 //   - BboMatrix[2048][5]: ShmBboMessage payload (64B × 5 × 2048 = 640 KB)
+//   - GlobalSequence: AtomicU64 feeder tick counter, +1 per feeder loop tick
+//     (appended at the tail so existing offsets are unchanged)
+//   - BlockVersions[32]: AtomicU64 per 64-symbol block, +1 on every write to
+//     any symbol of the block, so readers skip unchanged blocks (also trailing)
 //
 // Total: ~656 KB
 package shm
@@ -24,6 +28,7 @@ const (
 	NumSymbols   = 2048
 	NumExchanges = 7
 	SlotSize     = 64 // sizeof(ShmBboMessage)
+	NumBlocks    = NumSymbols / 64
 )
 
 // ShmBboMessage is the 64-byte cache-line-aligned BBO message.
@@ -52,6 +57,17 @@ type ShmMarketState struct {
 	// BBO matrix: [symbol_id][exchange_id] → ShmBboMessage
 	// Total: 640 KB (2048 × 5 × 64 bytes)
 	BboMatrix [NumSymbols][NumExchanges]ShmBboMessage
+
+	// Global feeder tick counter, incremented once per WriteBBO across all
+	// symbols/exchanges. Lets the reader tell whether two BBOs were written
+	// in the same tick or how many ticks apart.
+	GlobalSequence uint64
+
+	// Write counter per 64-symbol block (symbol / 64), bumped after the
+	// symbol version. Readers compare these 32 words first and only scan the
+	// SymbolVersions of blocks that moved. A counter rather than a clearable
+	// bitset, since every reader maps the matrix read-only.
+	BlockVersions [NumBlocks]uint64
 }
 
 func init() {
@@ -127,6 +143,10 @@ func (m *Matrix) WriteBBO(exchangeID uint8, symbolID uint16, tsNs uint64,
 
 	// Phase 4: increment symbol version to notify Rust reader
 	atomic.AddUint64(&m.shm.SymbolVersions[symbolID], 1)
+
+	// Phase 5: flag the symbol's block (after the version, so a reader that
+	// sees the block move also sees the new symbol version)
+	atomic.AddUint64(&m.shm.BlockVersions[symbolID/64], 1)
 }
 
 // GetVersion returns the current version for a symbol (for diagnostics).
@@ -137,6 +157,17 @@ func (m *Matrix) GetVersion(symbolID uint16) uint64 {
 	return atomic.LoadUint64(&m.shm.SymbolVersions[symbolID])
 }
 
+// NextTick advances the global feeder tick. The feeder loop calls it once per
+// iteration, so sequence distances measure time rather than update volume.
+func (m *Matrix) NextTick() {
+	atomic.AddUint64(&m.shm.GlobalSequence, 1)
+}
+
+// GetGlobalSequence returns the current global feeder tick (for diagnostics).
+func (m *Matrix) GetGlobalSequence() uint64 {
+	return atomic.LoadUint64(&m.shm.GlobalSequence)
+}
+
 // Close unmaps the shared memory.
 func (m *Matrix) Close() error {
 	return syscall.Munmap(m.data)
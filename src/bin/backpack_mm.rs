@@ -3,9 +3,11 @@
 //! Demonstrates using BackpackGateway with the unified Exchange trait.
 
 use aleph_tx::config::AppConfig;
-use aleph_tx::exchanges::backpack::client::BackpackClient;
+use aleph_tx::exchanges::backpack::client::BackpackClientPool;
 use aleph_tx::exchanges::backpack::gateway::BackpackGateway;
+use aleph_tx::secret::Secret;
 use aleph_tx::shm_reader::ShmReader;
+use anyhow::Context;
 use std::sync::Arc;
 use tokio::sync::watch;
 
@@ -35,35 +37,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let env_content = std::fs::read_to_string(&env_path)?;
 
     let mut api_key = String::new();
-    let mut api_secret = String::new();
+    let mut api_secret = Secret::default();
     for line in env_content.lines() {
         if let Some(rest) = line.strip_prefix("BACKPACK_PUBLIC_KEY=") {
             api_key = rest.trim().to_string();
         }
         if let Some(rest) = line.strip_prefix("BACKPACK_SECRET_KEY=") {
-            api_secret = rest.trim().to_string();
+            api_secret = Secret::new(rest.trim().to_string());
         }
     }
 
-    if api_key.is_empty() || api_secret.is_empty() {
+    if api_key.is_empty() || api_secret.expose().is_empty() {
         return Err("Missing BACKPACK_PUBLIC_KEY or BACKPACK_SECRET_KEY in .env.backpack".into());
     }
 
     // Step 3: Initialize Backpack client
     tracing::info!("🎯 Initializing Backpack client...");
-    let client = BackpackClient::new(&api_key, &api_secret, "https://api.backpack.exchange")?;
-    let client = Arc::new(client);
+    let client = BackpackClientPool::get_or_create(
+        &api_key,
+        api_secret.expose(),
+        "https://api.backpack.exchange",
+    )?;
 
     // Step 4: Create BackpackGateway (Exchange trait implementation)
     tracing::info!("🌉 Creating Backpack gateway...");
-    let gateway = Arc::new(BackpackGateway::new(
-        client.clone(),
-        "ETH_USDC_PERP".to_string(),
-    ));
+    let gateway = Arc::new(
+        BackpackGateway::new(client.clone(), backpack_config.symbol_name.clone())
+            .with_precision(backpack_config.tick_size, backpack_config.step_size),
+    );
 
     // Step 5: Connect to BBO Matrix
     tracing::info!("📡 Connecting to BBO matrix...");
-    let mut shm_reader = ShmReader::open("/dev/shm/aleph-matrix", 2048)?;
+    let mut shm_reader =
+        ShmReader::open("/dev/shm/aleph-matrix", 2048).context("Failed to open shared memory")?;
 
     // Step 6: Setup graceful shutdown
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
@@ -76,7 +82,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Step 7: Simple market making loop (demo only)
     tracing::info!("🚀 Starting market making loop...\n");
 
-    let symbol_id = 1002; // ETH
+    let symbol_id = backpack_config.symbol_id;
     let exchange_id = 5; // Backpack
     let mut last_quote_time = std::time::Instant::now();
     let requote_interval = std::time::Duration::from_millis(backpack_config.requote_interval_ms);
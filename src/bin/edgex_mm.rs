@@ -6,7 +6,9 @@ use aleph_tx::config::AppConfig;
 use aleph_tx::exchange::Exchange;
 use aleph_tx::exchanges::edgex::client::EdgeXClient;
 use aleph_tx::exchanges::edgex::gateway::{EdgeXConfig, EdgeXGateway};
+use aleph_tx::secret::Secret;
 use aleph_tx::shm_reader::ShmReader;
+use anyhow::Context;
 use std::sync::Arc;
 use tokio::sync::watch;
 
@@ -36,12 +38,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables
     dotenv::from_filename(&env_path).ok();
 
-    let stark_private_key = std::env::var("EDGEX_STARK_PRIVATE_KEY")
-        .map_err(|_| "Missing EDGEX_STARK_PRIVATE_KEY in .env.edgex")?;
+    let stark_private_key: Secret<String> = std::env::var("EDGEX_STARK_PRIVATE_KEY")
+        .map_err(|_| "Missing EDGEX_STARK_PRIVATE_KEY in .env.edgex")?
+        .into();
 
     // Step 3: Initialize EdgeX client
     tracing::info!("🎯 Initializing EdgeX client...");
-    let client = EdgeXClient::new(&stark_private_key, None)?;
+    let client = EdgeXClient::new(stark_private_key.expose(), None)?;
     let client = Arc::new(client);
 
     // Step 4: Load EdgeX gateway configuration
@@ -60,7 +63,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Step 6: Connect to BBO Matrix
     tracing::info!("📡 Connecting to BBO matrix...");
-    let mut shm_reader = ShmReader::open("/dev/shm/aleph-matrix", 2048)?;
+    let mut shm_reader =
+        ShmReader::open("/dev/shm/aleph-matrix", 2048).context("Failed to open shared memory")?;
 
     // Step 7: Setup graceful shutdown
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
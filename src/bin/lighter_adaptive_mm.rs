@@ -12,6 +12,7 @@ use aleph_tx::shadow_ledger::ShadowLedgerManager;
 use aleph_tx::shm_event_reader::ShmEventReader;
 use aleph_tx::shm_reader::ShmReader;
 use aleph_tx::strategy::lighter_adaptive_mm::AdaptiveMarketMaker;
+use anyhow::Context;
 use std::sync::Arc;
 
 #[tokio::main]
@@ -31,7 +32,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _consumer_handle = ledger_manager.spawn_consumer(event_reader);
 
     // Connect to shared memory
-    let shm_reader = ShmReader::open("/dev/shm/aleph-matrix", 2048)?;
+    let shm_reader =
+        ShmReader::open("/dev/shm/aleph-matrix", 2048).context("Failed to open shared memory")?;
     let account_stats_reader = AccountStatsReader::open("/dev/shm/aleph-account-stats")?;
 
     // Initialize LighterTrading (market_id=0 = ETH perps)
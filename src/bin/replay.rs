@@ -0,0 +1,79 @@
+//! Replay a BBO capture (`aleph-tx --capture <file>`) through the strategies.
+//!
+//! Usage: `replay <capture-file>`
+//!
+//! Market makers are forced into `dry_run`, so a replay never sends orders;
+//! their quoting decisions show up in the log exactly as they did live.
+
+use aleph_tx::config::{AppConfig, EXCH_BACKPACK, EXCH_EDGEX, SYM_ETH};
+use aleph_tx::credentials::Credentials;
+use aleph_tx::exchange_state::account_caches;
+use aleph_tx::replay::ReplayEngine;
+use aleph_tx::strategy::{
+    Strategy, arbitrage::ArbitrageEngine, backpack_mm::BackpackMMStrategy,
+    edgex_mm::MarketMakerStrategy,
+};
+use std::path::PathBuf;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter("info,aleph_tx=debug")
+        .init();
+
+    let path: PathBuf = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("usage: replay <capture-file>"))?;
+
+    let mut config = AppConfig::load_default();
+    config.edgex.dry_run = true;
+    config.backpack.dry_run = true;
+
+    let credentials = Credentials::load();
+    let backpack_client = credentials.backpack_client();
+    let edgex = credentials.edgex_client();
+    let (edgex_client, edgex_account) = match &edgex {
+        Some((client, account_id)) => (Some(client.clone()), *account_id),
+        None => (None, 0),
+    };
+    let (backpack_state, edgex_state) = account_caches(&config, &backpack_client, &edgex);
+
+    let mut strategies: Vec<Box<dyn Strategy>> = vec![
+        Box::new(ArbitrageEngine::new_with_whitelist(
+            25.0,
+            config.arbitrage.allowed_symbol_ids.clone(),
+        )),
+        Box::new(MarketMakerStrategy::new(
+            EXCH_EDGEX,
+            SYM_ETH,
+            25.0,
+            config.edgex.clone(),
+            edgex_client,
+            edgex_account,
+            edgex_state,
+        )),
+        Box::new(BackpackMMStrategy::new(
+            EXCH_BACKPACK,
+            config.backpack.symbol_id,
+            config.backpack.symbol_name.clone(),
+            25.0,
+            config.backpack.clone(),
+            backpack_client,
+            backpack_state,
+        )),
+    ];
+
+    tracing::info!("🎞️ Replaying {}", path.display());
+    let stats = ReplayEngine::play(&path, &mut strategies)?;
+    tracing::info!(
+        "🏁 Replay done: {} updates, {} idle ticks",
+        stats.updates,
+        stats.idles
+    );
+
+    for strategy in strategies.iter_mut() {
+        strategy.on_shutdown().await;
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Startup check of the local clock against exchange server time.
+//!
+//! Signed requests carry a local millisecond timestamp that both exchanges
+//! only accept within a few seconds of their own clock. `ClockSync::check`
+//! hits each exchange's public time endpoint (no auth) and reports drift
+//! beyond `max_drift_ms`, so a skewed host shows up as one clear warning at
+//! boot instead of a stream of opaque 401s.
+
+use serde_json::Value;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+const BACKPACK_TIME_URL: &str = "https://api.backpack.exchange/api/v1/time";
+const EDGEX_TIME_URL: &str = "https://pro.edgex.exchange/api/v1/public/meta/getServerTime";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ClockSyncError {
+    /// Positive offset: the exchange clock is ahead of ours.
+    #[error("{exchange} clock differs from local by {offset_ms}ms")]
+    Drift {
+        exchange: &'static str,
+        offset_ms: i64,
+    },
+    #[error("{exchange} time unavailable: {reason}")]
+    Unavailable {
+        exchange: &'static str,
+        reason: String,
+    },
+}
+
+pub struct ClockSync {
+    client: reqwest::Client,
+    max_drift_ms: i64,
+}
+
+impl Default for ClockSync {
+    /// 1s tolerance: well inside Backpack's 5s signature window.
+    fn default() -> Self {
+        Self::new(1_000)
+    }
+}
+
+impl ClockSync {
+    pub fn new(max_drift_ms: i64) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            max_drift_ms,
+        }
+    }
+
+    /// Check every exchange; returns the first drift (or fetch failure) found.
+    pub async fn check(&self) -> Result<(), ClockSyncError> {
+        let backpack = self.offset_ms("backpack", BACKPACK_TIME_URL, parse_backpack_time);
+        let edgex = self.offset_ms("edgex", EDGEX_TIME_URL, parse_edgex_time);
+        let (backpack, edgex) = futures::join!(backpack, edgex);
+        for (exchange, offset) in [("backpack", backpack?), ("edgex", edgex?)] {
+            check_drift(exchange, offset, self.max_drift_ms)?;
+        }
+        Ok(())
+    }
+
+    /// Server − local clock (ms), taking server time at the round-trip midpoint.
+    async fn offset_ms(
+        &self,
+        exchange: &'static str,
+        url: &str,
+        parse: fn(&str) -> Option<i64>,
+    ) -> Result<i64, ClockSyncError> {
+        let unavailable = |reason: String| ClockSyncError::Unavailable { exchange, reason };
+        let sent = local_ms();
+        let body = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| unavailable(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| unavailable(e.to_string()))?;
+        let received = local_ms();
+        let server = parse(&body).ok_or_else(|| unavailable(format!("bad body: {}", body)))?;
+        Ok(server - (sent + received) / 2)
+    }
+}
+
+fn check_drift(
+    exchange: &'static str,
+    offset_ms: i64,
+    max_drift_ms: i64,
+) -> Result<(), ClockSyncError> {
+    if offset_ms.abs() > max_drift_ms {
+        return Err(ClockSyncError::Drift {
+            exchange,
+            offset_ms,
+        });
+    }
+    Ok(())
+}
+
+/// Backpack answers with the bare millisecond timestamp.
+fn parse_backpack_time(body: &str) -> Option<i64> {
+    body.trim().trim_matches('"').parse().ok()
+}
+
+/// EdgeX: `{"code":"SUCCESS","data":{"timeMillis":"1700000000000"}}`.
+fn parse_edgex_time(body: &str) -> Option<i64> {
+    let json: Value = serde_json::from_str(body).ok()?;
+    match json.get("data")?.get("timeMillis")? {
+        Value::String(s) => s.parse().ok(),
+        v => v.as_i64(),
+    }
+}
+
+fn local_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_server_times_and_flags_drift() {
+        assert_eq!(
+            parse_backpack_time("1700000000000\n"),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(
+            parse_edgex_time(r#"{"code":"SUCCESS","data":{"timeMillis":"1700000000123"}}"#),
+            Some(1_700_000_000_123)
+        );
+        assert_eq!(parse_edgex_time(r#"{"code":"SUCCESS","data":{}}"#), None);
+
+        assert_eq!(check_drift("edgex", -900, 1_000), Ok(()));
+        assert_eq!(
+            check_drift("backpack", 2_500, 1_000),
+            Err(ClockSyncError::Drift {
+                exchange: "backpack",
+                offset_ms: 2_500
+            })
+        );
+    }
+}
@@ -11,6 +11,7 @@ pub const EXCH_BACKPACK: u8 = 5;
 pub const SYM_BTC: u16 = 1001;
 pub const SYM_ETH: u16 = 1002;
 
+use crate::types::ContractSpec;
 use serde::Deserialize;
 use std::path::Path;
 
@@ -47,9 +48,51 @@ pub fn symbol_name(symbol_id: u16) -> &'static str {
     }
 }
 
+/// Quote pricing model for the EdgeX/Backpack MMs (see `strategy::quoting`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotingModel {
+    /// vol × multiplier spread, momentum widening, linear inventory skew
+    #[default]
+    Basic,
+    /// Avellaneda-Stoikov reservation price + optimal spread
+    Avellaneda,
+}
+
+/// What to do when an order would cross one of our own resting quotes
+/// (see `strategy::quote_board`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelfTradePolicy {
+    /// Drop the order
+    Skip,
+    /// Reprice one tick short of our best conflicting quote
+    Adjust,
+    /// Cancel the conflicting quotes, then send the order unchanged
+    #[default]
+    Cancel,
+}
+
+/// Realized volatility estimator for the MMs (see `strategy::volatility`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolModel {
+    /// Std-dev of per-tick returns over the last `vol_window` mids
+    #[default]
+    Window,
+    /// Time-weighted EWMA with `vol_half_life_secs` half-life
+    Ewma,
+}
+
 /// Per-exchange strategy configuration.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ExchangeConfig {
+    /// SHM symbol id the MM quotes (`SYM_*`)
+    #[serde(default = "default_symbol_id")]
+    pub symbol_id: u16,
+    /// Venue symbol the MM quotes, e.g. "SOL_USDC_PERP" (Backpack)
+    #[serde(default = "default_symbol_name")]
+    pub symbol_name: String,
     /// Fraction of account balance to use as max position (e.g. 0.10 = 10%)
     pub risk_fraction: f64,
     /// Minimum half-spread floor in basis points
@@ -69,6 +112,12 @@ pub struct ExchangeConfig {
     /// Number of mid-price samples for volatility ring buffer
     #[serde(default = "default_vol_window")]
     pub vol_window: usize,
+    /// Volatility estimator: "window" | "ewma"
+    #[serde(default)]
+    pub vol_model: VolModel,
+    /// EWMA half-life in seconds (vol_model = "ewma")
+    #[serde(default = "default_vol_half_life")]
+    pub vol_half_life_secs: f64,
     /// How often to refresh balance (seconds)
     #[serde(default = "default_balance_refresh")]
     pub balance_refresh_secs: u64,
@@ -81,15 +130,93 @@ pub struct ExchangeConfig {
     /// Size step size (e.g. 0.01 for 0.01 unit increments)
     #[serde(default = "default_step_size")]
     pub step_size: f64,
+    /// Minimum order notional in quote currency (0 = none)
+    #[serde(default)]
+    pub min_notional: f64,
     /// Avellaneda-Stoikov risk aversion parameter
     #[serde(default = "default_gamma")]
     pub gamma: f64,
     /// Avellaneda-Stoikov time horizon in seconds
     #[serde(default = "default_time_horizon")]
     pub time_horizon_sec: f64,
-    /// Minimum price deviation (bps) to trigger requote (Phase 2 incremental quoting)
+    /// Avellaneda-Stoikov order arrival intensity k
+    #[serde(default = "default_kappa")]
+    pub kappa: f64,
+    /// Quote pricing model: "basic" | "avellaneda"
+    #[serde(default)]
+    pub quoting_model: QuotingModel,
+    /// Minimum price deviation (bps) to trigger requote (Phase 2 incremental quoting).
+    /// Backpack MM amends resting quotes in place when the mid drifted less than this.
     #[serde(default = "default_requote_threshold")]
     pub requote_threshold_bps: f64,
+    /// Book-imbalance weight: shade mid toward the heavy side and widen the
+    /// vulnerable side by up to this fraction (0 = disabled)
+    #[serde(default)]
+    pub imbalance_weight: f64,
+    /// Depth band around mid (bps) used to measure book imbalance
+    #[serde(default = "default_imbalance_depth_bps")]
+    pub imbalance_depth_bps: f64,
+    /// Orders per side (1 = single best bid/ask)
+    #[serde(default = "default_num_quote_levels")]
+    pub num_quote_levels: usize,
+    /// Level i spread = base spread × this^i
+    #[serde(default = "default_level_spacing_multiplier")]
+    pub level_spacing_multiplier: f64,
+    /// Level i size = base size × this^i
+    #[serde(default = "default_level_size_decay")]
+    pub level_size_decay: f64,
+    /// Funding skew: when inventory is on the paying side, shift the quote mid
+    /// against it by weight × rate (scaled up as settlement approaches; 0 = off)
+    #[serde(default)]
+    pub funding_skew_weight: f64,
+    /// Funding settlement period in seconds
+    #[serde(default = "default_funding_interval")]
+    pub funding_interval_secs: u64,
+    /// Extra half-spread (bps) on both sides in the last `funding_window_mins` (0 = off)
+    #[serde(default)]
+    pub funding_window_widen_bps: f64,
+    #[serde(default = "default_funding_window_mins")]
+    pub funding_window_mins: u64,
+    /// Adverse-selection defense: trigger when the 1s post-fill markout on a side
+    /// averages below −this many bps (0 = track only, no defense)
+    #[serde(default)]
+    pub markout_threshold_bps: f64,
+    /// Fills needed on a side before its markout is considered persistent
+    #[serde(default = "default_markout_min_fills")]
+    pub markout_min_fills: usize,
+    /// Spread multiplier applied to the toxic side
+    #[serde(default = "default_markout_widen_mult")]
+    pub markout_widen_mult: f64,
+    /// Pause quoting for this long instead of widening (0 = widen)
+    #[serde(default)]
+    pub markout_pause_secs: u64,
+    /// Leverage to set on startup (perps; None = leave account setting unchanged)
+    #[serde(default)]
+    pub target_leverage: Option<u32>,
+    /// Log orders/cancels instead of sending them; fills are simulated at mid
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Adverse slippage applied to simulated dry-run fills
+    #[serde(default = "default_dry_run_slippage_bps")]
+    pub dry_run_slippage_bps: f64,
+    /// Cancel and requote once any resting quote is older than this (0 = off)
+    #[serde(default)]
+    pub max_order_age_secs: u64,
+    /// Requote at least this often even if the mid has not moved
+    #[serde(default = "default_max_quote_age")]
+    pub max_quote_age_secs: u64,
+    /// Mid move since the last quote (bps) that triggers a requote
+    #[serde(default = "default_price_change_threshold")]
+    pub price_change_threshold_bps: f64,
+    /// Aggressive orders that would trade against our own quotes
+    #[serde(default)]
+    pub self_trade_policy: SelfTradePolicy,
+    /// Resting exchange-side stop this far beyond the average entry (Backpack; 0 = off)
+    #[serde(default)]
+    pub protective_stop_bps: f64,
+    /// Only keep the protective stop while |position| is at least this size
+    #[serde(default)]
+    pub protective_stop_min_position: f64,
 
     // EdgeX-specific L2 configuration
     #[serde(default)]
@@ -121,9 +248,18 @@ fn default_momentum_mult() -> f64 {
 fn default_vol_window() -> usize {
     120
 }
+fn default_vol_half_life() -> f64 {
+    30.0
+}
 fn default_balance_refresh() -> u64 {
     60
 }
+fn default_symbol_id() -> u16 {
+    SYM_ETH
+}
+fn default_symbol_name() -> String {
+    "ETH_USDC_PERP".to_string()
+}
 fn default_tick_size() -> f64 {
     0.01
 }
@@ -136,9 +272,45 @@ fn default_gamma() -> f64 {
 fn default_time_horizon() -> f64 {
     60.0
 }
+fn default_kappa() -> f64 {
+    1.5
+}
 fn default_requote_threshold() -> f64 {
     2.0 // 2 bps deviation threshold
 }
+fn default_max_quote_age() -> u64 {
+    5
+}
+fn default_price_change_threshold() -> f64 {
+    8.0
+}
+fn default_imbalance_depth_bps() -> f64 {
+    10.0
+}
+fn default_num_quote_levels() -> usize {
+    1
+}
+fn default_level_spacing_multiplier() -> f64 {
+    1.5
+}
+fn default_level_size_decay() -> f64 {
+    0.7
+}
+fn default_funding_interval() -> u64 {
+    8 * 3600
+}
+fn default_funding_window_mins() -> u64 {
+    5
+}
+fn default_dry_run_slippage_bps() -> f64 {
+    1.0
+}
+fn default_markout_min_fills() -> usize {
+    5
+}
+fn default_markout_widen_mult() -> f64 {
+    1.5
+}
 fn default_poll_interval_ms() -> u64 {
     100
 }
@@ -206,6 +378,96 @@ fn default_min_inventory_notional_usd() -> f64 {
     10.0
 }
 
+/// Threading for the main binary (`[runtime]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// CPU core for the SHM data plane thread
+    pub data_plane_core: Option<usize>,
+    /// CPU core per strategy thread, in boot order (missing entries = unpinned)
+    pub core_affinity: Vec<usize>,
+    /// Strategy idle timer (on_idle cadence when no BBO arrives)
+    pub idle_interval_ms: u64,
+    /// BBOs with a wider (or crossed / one-sided) quote never reach strategies
+    pub max_spread_bps: f64,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            data_plane_core: Some(2),
+            core_affinity: Vec::new(),
+            idle_interval_ms: 1,
+            max_spread_bps: 500.0,
+        }
+    }
+}
+
+/// Cross-exchange arbitrage scanner (`[arbitrage]`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ArbitrageConfig {
+    /// SHM symbol IDs to scan (empty = all)
+    pub allowed_symbol_ids: Vec<u16>,
+    /// Trade Backpack ↔ EdgeX crosses on `backpack.symbol_id` (default: log only)
+    pub execute: bool,
+    /// Max size per executed arb (base units)
+    pub max_size: f64,
+    /// Skip crosses whose quote on either leg trails the symbol's newest by more (0 = off)
+    pub max_quote_age_ms: u64,
+}
+
+/// Backpack ↔ EdgeX funding-rate arbitrage (`[funding_arb]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FundingArbConfig {
+    pub enabled: bool,
+    /// SHM symbol ID used for the BBO mids
+    pub symbol_id: u16,
+    pub backpack_symbol: String,
+    /// Open the pair when the annualized funding differential exceeds this (0.15 = 15%/yr)
+    pub entry_apr: f64,
+    /// Unwind once the differential (in the held direction) drops below this
+    pub exit_apr: f64,
+    /// Per-leg notional = min venue equity × this
+    pub risk_fraction: f64,
+    /// Hard cap on per-leg notional (USD)
+    pub max_notional_usd: f64,
+    /// Unwind after this long regardless of the differential
+    pub max_hold_secs: u64,
+    /// How often funding is re-checked
+    pub check_interval_secs: u64,
+    /// IOC limit price offset from mid (bps)
+    pub slippage_bps: f64,
+    pub size_step: f64,
+    pub min_size: f64,
+    /// Reduce-only unwind of an open pair on shutdown
+    pub unwind_on_shutdown: bool,
+    /// Append-only JSONL journal of entries/exits
+    pub journal_path: String,
+}
+
+impl Default for FundingArbConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            symbol_id: SYM_ETH,
+            backpack_symbol: "ETH_USDC_PERP".to_string(),
+            entry_apr: 0.15,
+            exit_apr: 0.03,
+            risk_fraction: 0.10,
+            max_notional_usd: 5_000.0,
+            max_hold_secs: 3 * 86_400,
+            check_interval_secs: 60,
+            slippage_bps: 10.0,
+            size_step: 0.01,
+            min_size: 0.01,
+            unwind_on_shutdown: true,
+            journal_path: "logs/funding_arb.jsonl".to_string(),
+        }
+    }
+}
+
 /// Inventory Neutral Market Maker 策略配置
 #[derive(Debug, Clone, Deserialize)]
 pub struct InventoryNeutralMMConfig {
@@ -350,6 +612,22 @@ pub struct AppConfig {
     pub edgex: ExchangeConfig,
     #[serde(default)]
     pub inventory_neutral_mm: Option<InventoryNeutralMMConfig>,
+    #[serde(default)]
+    pub arbitrage: ArbitrageConfig,
+    #[serde(default)]
+    pub funding_arb: FundingArbConfig,
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+}
+
+impl ExchangeConfig {
+    /// Take tick / lot size from the venue's contract spec, keeping any
+    /// stricter notional floor from the config.
+    pub fn apply_contract_spec(&mut self, spec: &ContractSpec) {
+        self.tick_size = spec.tick_size;
+        self.step_size = spec.lot_size;
+        self.min_notional = self.min_notional.max(spec.min_notional);
+    }
 }
 
 impl AppConfig {
@@ -384,6 +662,8 @@ impl Default for AppConfig {
     fn default() -> Self {
         Self {
             backpack: ExchangeConfig {
+                symbol_id: SYM_ETH,
+                symbol_name: default_symbol_name(),
                 risk_fraction: 0.10,
                 min_spread_bps: 12.0,
                 vol_multiplier: 3.0,
@@ -392,13 +672,40 @@ impl Default for AppConfig {
                 momentum_threshold_bps: 8.0,
                 momentum_spread_mult: 2.0,
                 vol_window: 120,
+                vol_model: VolModel::Window,
+                vol_half_life_secs: 30.0,
                 balance_refresh_secs: 60,
                 min_order_size: 0.0,
                 tick_size: 0.01,
                 step_size: 0.01,
+                min_notional: 0.0,
                 gamma: 0.1,
                 time_horizon_sec: 60.0,
+                kappa: 1.5,
+                quoting_model: QuotingModel::Basic,
                 requote_threshold_bps: 2.0,
+                imbalance_weight: 0.0,
+                imbalance_depth_bps: 10.0,
+                num_quote_levels: 1,
+                level_spacing_multiplier: 1.5,
+                level_size_decay: 0.7,
+                funding_skew_weight: 0.0,
+                funding_interval_secs: 8 * 3600,
+                funding_window_widen_bps: 0.0,
+                funding_window_mins: 5,
+                markout_threshold_bps: 0.0,
+                markout_min_fills: 5,
+                markout_widen_mult: 1.5,
+                markout_pause_secs: 0,
+                target_leverage: None,
+                dry_run: false,
+                dry_run_slippage_bps: 1.0,
+                max_order_age_secs: 0,
+                max_quote_age_secs: 5,
+                price_change_threshold_bps: 8.0,
+                self_trade_policy: SelfTradePolicy::Cancel,
+                protective_stop_bps: 0.0,
+                protective_stop_min_position: 0.0,
                 contract_id: None,
                 synthetic_asset_id: None,
                 collateral_asset_id: None,
@@ -410,6 +717,8 @@ impl Default for AppConfig {
                 fee_rate: None,
             },
             edgex: ExchangeConfig {
+                symbol_id: SYM_ETH,
+                symbol_name: String::new(),
                 risk_fraction: 0.08,
                 min_spread_bps: 20.0,
                 vol_multiplier: 3.5,
@@ -418,13 +727,40 @@ impl Default for AppConfig {
                 momentum_threshold_bps: 8.0,
                 momentum_spread_mult: 2.0,
                 vol_window: 120,
+                vol_model: VolModel::Window,
+                vol_half_life_secs: 30.0,
                 balance_refresh_secs: 60,
                 min_order_size: 0.1,
                 tick_size: 0.01,
                 step_size: 0.01,
+                min_notional: 0.0,
                 gamma: 0.1,
                 time_horizon_sec: 60.0,
+                kappa: 1.5,
+                quoting_model: QuotingModel::Basic,
                 requote_threshold_bps: 2.0,
+                imbalance_weight: 0.0,
+                imbalance_depth_bps: 10.0,
+                num_quote_levels: 1,
+                level_spacing_multiplier: 1.5,
+                level_size_decay: 0.7,
+                funding_skew_weight: 0.0,
+                funding_interval_secs: 8 * 3600,
+                funding_window_widen_bps: 0.0,
+                funding_window_mins: 5,
+                markout_threshold_bps: 0.0,
+                markout_min_fills: 5,
+                markout_widen_mult: 1.5,
+                markout_pause_secs: 0,
+                target_leverage: None,
+                dry_run: false,
+                dry_run_slippage_bps: 1.0,
+                max_order_age_secs: 0,
+                max_quote_age_secs: 5,
+                price_change_threshold_bps: 10.0,
+                self_trade_policy: SelfTradePolicy::Cancel,
+                protective_stop_bps: 0.0,
+                protective_stop_min_position: 0.0,
                 contract_id: Some(1),
                 synthetic_asset_id: Some("0x4554482d3130000000000000000000".to_string()),
                 collateral_asset_id: Some("0x555344432d36000000000000000000".to_string()),
@@ -436,6 +772,9 @@ impl Default for AppConfig {
                 fee_rate: Some(0.0005),
             },
             inventory_neutral_mm: Some(InventoryNeutralMMConfig::default()),
+            arbitrage: ArbitrageConfig::default(),
+            funding_arb: FundingArbConfig::default(),
+            runtime: RuntimeConfig::default(),
         }
     }
 }
@@ -480,5 +819,18 @@ mod tests {
         assert_eq!(cfg.backpack.time_horizon_sec, 60.0);
         assert_eq!(cfg.edgex.tick_size, 0.01);
         assert_eq!(cfg.edgex.gamma, 0.1);
+        assert_eq!(cfg.backpack.quoting_model, QuotingModel::Basic);
+    }
+
+    #[test]
+    fn test_quoting_model_from_toml() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            quoting_model: QuotingModel,
+        }
+        let w: Wrapper = toml::from_str(r#"quoting_model = "avellaneda""#).unwrap();
+        assert_eq!(w.quoting_model, QuotingModel::Avellaneda);
+        let w: Wrapper = toml::from_str(r#"quoting_model = "basic""#).unwrap();
+        assert_eq!(w.quoting_model, QuotingModel::Basic);
     }
 }
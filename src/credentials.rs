@@ -0,0 +1,117 @@
+//! Exchange credentials loader and shared client construction.
+//!
+//! Credentials live in dotenv files outside `config.toml`:
+//! - `.env.backpack` (`BACKPACK_ENV_PATH`): `BACKPACK_PUBLIC_KEY`, `BACKPACK_SECRET_KEY`
+//! - `.env.edgex` (`EDGEX_ENV_PATH`): `EDGEX_ACCOUNT_ID`, `EDGEX_STARK_PRIVATE_KEY`
+//!
+//! Each file is loaded into the process environment once, and the resulting
+//! clients are shared (`Arc`) by every strategy trading that account.
+
+use crate::exchanges::backpack::client::{BackpackClient, BackpackClientPool};
+use crate::exchanges::edgex::client::EdgeXClient;
+use crate::secret::Secret;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const BACKPACK_API_URL: &str = "https://api.backpack.exchange";
+/// Backpack server-time resync period (signatures expire after a 5s window)
+const BACKPACK_CLOCK_SYNC: Duration = Duration::from_secs(300);
+const DEFAULT_BACKPACK_ENV: &str = "/home/metaverse/.openclaw/workspace/aleph-tx/.env.backpack";
+const DEFAULT_EDGEX_ENV: &str = "/home/metaverse/.openclaw/workspace/aleph-tx/.env.edgex";
+
+#[derive(Debug, Clone)]
+pub struct BackpackCredentials {
+    pub api_key: String,
+    pub api_secret: Secret<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgeXCredentials {
+    pub account_id: u64,
+    pub stark_private_key: Secret<String>,
+}
+
+/// Credentials found on disk / in the environment (missing venue = `None`).
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    pub backpack: Option<BackpackCredentials>,
+    pub edgex: Option<EdgeXCredentials>,
+}
+
+impl Credentials {
+    /// Load both env files (paths overridable via `BACKPACK_ENV_PATH` / `EDGEX_ENV_PATH`).
+    pub fn load() -> Self {
+        let bp_path =
+            std::env::var("BACKPACK_ENV_PATH").unwrap_or_else(|_| DEFAULT_BACKPACK_ENV.to_string());
+        let ex_path =
+            std::env::var("EDGEX_ENV_PATH").unwrap_or_else(|_| DEFAULT_EDGEX_ENV.to_string());
+        dotenv::from_filename(&bp_path).ok();
+        dotenv::from_filename(&ex_path).ok();
+        Self::from_env()
+    }
+
+    /// Read credentials from already-set environment variables.
+    pub fn from_env() -> Self {
+        let var = |k: &str| std::env::var(k).ok().map(|v| v.trim().to_string());
+
+        let backpack = match (var("BACKPACK_PUBLIC_KEY"), var("BACKPACK_SECRET_KEY")) {
+            (Some(api_key), Some(api_secret)) if !api_key.is_empty() && !api_secret.is_empty() => {
+                Some(BackpackCredentials {
+                    api_key,
+                    api_secret: api_secret.into(),
+                })
+            }
+            _ => None,
+        };
+        let edgex = match (
+            var("EDGEX_ACCOUNT_ID").and_then(|v| v.parse::<u64>().ok()),
+            var("EDGEX_STARK_PRIVATE_KEY"),
+        ) {
+            (Some(account_id), Some(key)) if account_id > 0 && !key.is_empty() => {
+                Some(EdgeXCredentials {
+                    account_id,
+                    stark_private_key: key.into(),
+                })
+            }
+            _ => None,
+        };
+        Self { backpack, edgex }
+    }
+
+    /// One Backpack client for the whole process (`None` without credentials).
+    pub fn backpack_client(&self) -> Option<Arc<BackpackClient>> {
+        let creds = self.backpack.as_ref()?;
+        let pooled = BackpackClientPool::get_or_create(
+            &creds.api_key,
+            creds.api_secret.expose(),
+            BACKPACK_API_URL,
+        );
+        match pooled {
+            Ok(client) => {
+                info!("🎒 Loaded Backpack API Client (shared)");
+                client.spawn_clock_sync(BACKPACK_CLOCK_SYNC);
+                Some(client)
+            }
+            Err(e) => {
+                warn!("Failed to init Backpack Client: {}", e);
+                None
+            }
+        }
+    }
+
+    /// One EdgeX client for the whole process, with its account id.
+    pub fn edgex_client(&self) -> Option<(Arc<EdgeXClient>, u64)> {
+        let creds = self.edgex.as_ref()?;
+        match EdgeXClient::new(creds.stark_private_key.expose(), None) {
+            Ok(client) => {
+                info!("✅ Loaded EdgeX API Client (shared)");
+                Some((Arc::new(client), creds.account_id))
+            }
+            Err(e) => {
+                warn!("Failed to init EdgeX Client: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -2,10 +2,16 @@
 //!
 //! Solves the async starvation problem where SHM spin-loop monopolizes Tokio workers.
 //! Uses a dedicated OS thread with optional CPU pinning + flume channel for async bridge.
+//! [`BboFanout`] fans updates out to one conflating [`BboMailbox`] per strategy thread,
+//! so a slow consumer only ever loses stale ticks and never blocks the reader.
 
 use crate::shm_reader::{ShmBboMessage, ShmReader};
 use flume::{Receiver, Sender, bounded};
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use tracing::{error, info};
 
 /// BBO update message sent from data plane to strategy loop
@@ -14,6 +20,123 @@ pub struct BboUpdate {
     pub symbol_id: u16,
     pub exchange_id: u8,
     pub bbo: ShmBboMessage,
+    /// Global feeder sequence observed when this BBO was read
+    pub global_seq: u64,
+}
+
+/// Latest-per-(symbol, exchange) queue for one consumer.
+///
+/// A newer update for a key that is still pending overwrites it in place (drop
+/// oldest, keep latest); keys are delivered in first-arrival order. Capacity is
+/// bounded by the number of distinct keys, so `push` never blocks.
+pub struct BboMailbox {
+    inner: Mutex<MailboxInner>,
+    wake_tx: Sender<()>,
+    wake_rx: Receiver<()>,
+}
+
+#[derive(Default)]
+struct MailboxInner {
+    order: VecDeque<(u16, u8)>,
+    latest: HashMap<(u16, u8), BboUpdate>,
+    conflated: u64,
+}
+
+impl BboMailbox {
+    pub fn new() -> Arc<Self> {
+        let (wake_tx, wake_rx) = bounded(1);
+        Arc::new(Self {
+            inner: Mutex::new(MailboxInner::default()),
+            wake_tx,
+            wake_rx,
+        })
+    }
+
+    pub fn push(&self, update: BboUpdate) {
+        {
+            let mut inner = self.inner.lock();
+            let key = (update.symbol_id, update.exchange_id);
+            if inner.latest.insert(key, update).is_some() {
+                inner.conflated += 1;
+            } else {
+                inner.order.push_back(key);
+            }
+        }
+        // Full = a wake-up is already pending
+        let _ = self.wake_tx.try_send(());
+    }
+
+    /// Block until an update is pushed or `timeout` passes. Returns false on timeout.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        self.wake_rx.recv_timeout(timeout).is_ok()
+    }
+
+    /// Move all pending updates into `out`, oldest key first.
+    pub fn drain(&self, out: &mut Vec<BboUpdate>) {
+        let mut inner = self.inner.lock();
+        let MailboxInner { order, latest, .. } = &mut *inner;
+        out.extend(order.drain(..).filter_map(|key| latest.remove(&key)));
+    }
+
+    /// Updates overwritten before the consumer drained them.
+    pub fn conflated(&self) -> u64 {
+        self.inner.lock().conflated
+    }
+}
+
+/// Single-producer fan-out from the data plane to every strategy mailbox.
+#[derive(Clone)]
+pub struct BboFanout {
+    mailboxes: Vec<Arc<BboMailbox>>,
+    /// Lossless copy of every update (replay capture); unbounded, never blocks
+    tap: Option<Sender<BboUpdate>>,
+}
+
+impl BboFanout {
+    /// Fan-out with `n` subscribers; mailbox `i` belongs to strategy `i`.
+    pub fn new(n: usize) -> (Self, Vec<Arc<BboMailbox>>) {
+        let mailboxes: Vec<_> = (0..n).map(|_| BboMailbox::new()).collect();
+        (
+            Self {
+                mailboxes: mailboxes.clone(),
+                tap: None,
+            },
+            mailboxes,
+        )
+    }
+
+    /// Also forward every update, unconflated, to `tap`.
+    pub fn with_tap(mut self, tap: Sender<BboUpdate>) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    pub fn publish(&self, update: BboUpdate) {
+        if let Some(tap) = &self.tap {
+            let _ = tap.send(update.clone());
+        }
+        for mailbox in &self.mailboxes {
+            mailbox.push(update.clone());
+        }
+    }
+}
+
+/// Spawn the SHM polling thread feeding a [`BboFanout`] instead of a single channel.
+pub fn spawn_data_plane_fanout(
+    shm_path: &str,
+    max_symbols: usize,
+    cpu_core: Option<usize>,
+    fanout: BboFanout,
+) {
+    let shm_path = shm_path.to_string();
+    thread::Builder::new()
+        .name("data-plane".to_string())
+        .spawn(move || {
+            data_plane_loop(shm_path, max_symbols, cpu_core, |update| {
+                fanout.publish(update)
+            });
+        })
+        .expect("Failed to spawn data plane thread");
 }
 
 /// Spawn a dedicated data plane thread for SHM polling
@@ -36,7 +159,12 @@ pub fn spawn_data_plane_thread(
     thread::Builder::new()
         .name("data-plane".to_string())
         .spawn(move || {
-            data_plane_loop(shm_path, max_symbols, cpu_core, tx);
+            data_plane_loop(shm_path, max_symbols, cpu_core, |update| {
+                // Non-blocking send (drop if channel full to avoid backpressure)
+                // Channel full or disconnected - strategy loop is slow or dead
+                // In production, consider metrics here
+                let _ = tx.try_send(update);
+            });
         })
         .expect("Failed to spawn data plane thread");
 
@@ -48,7 +176,7 @@ fn data_plane_loop(
     shm_path: String,
     max_symbols: usize,
     cpu_core: Option<usize>,
-    tx: Sender<BboUpdate>,
+    mut publish: impl FnMut(BboUpdate),
 ) {
     // Pin to CPU core if specified
     if let Some(core) = cpu_core
@@ -79,6 +207,7 @@ fn data_plane_loop(
     loop {
         if let Some(symbol_id) = reader.try_poll() {
             // Read all exchanges for this symbol
+            let global_seq = reader.read_global_sequence();
             let exchanges = reader.read_all_exchanges(symbol_id);
             for (exch_idx, bbo) in exchanges.iter() {
                 if bbo.bid_price > 0.0 && bbo.ask_price > 0.0 {
@@ -86,13 +215,9 @@ fn data_plane_loop(
                         symbol_id,
                         exchange_id: *exch_idx,
                         bbo: *bbo,
+                        global_seq,
                     };
-
-                    // Non-blocking send (drop if channel full to avoid backpressure)
-                    if tx.try_send(update).is_err() {
-                        // Channel full or disconnected - strategy loop is slow or dead
-                        // In production, consider metrics here
-                    }
+                    publish(update);
                 }
             }
         } else {
@@ -125,11 +250,54 @@ mod tests {
             symbol_id: 1002,
             exchange_id: 2,
             bbo,
+            global_seq: 7,
         };
 
         let cloned = update.clone();
         assert_eq!(cloned.symbol_id, 1002);
         assert_eq!(cloned.exchange_id, 2);
         assert_eq!(cloned.bbo.bid_price, 3000.0);
+        assert_eq!(cloned.global_seq, 7);
+    }
+
+    fn update(symbol_id: u16, exchange_id: u8, bid: f64) -> BboUpdate {
+        BboUpdate {
+            symbol_id,
+            exchange_id,
+            bbo: ShmBboMessage {
+                bid_price: bid,
+                ask_price: bid + 1.0,
+                ..Default::default()
+            },
+            global_seq: 0,
+        }
+    }
+
+    #[test]
+    fn test_mailbox_keeps_latest_per_key_in_arrival_order() {
+        let (fanout, mailboxes) = BboFanout::new(2);
+        fanout.publish(update(1002, 3, 100.0));
+        fanout.publish(update(1002, 4, 200.0));
+        fanout.publish(update(1002, 3, 101.0));
+        fanout.publish(update(1002, 3, 102.0));
+
+        let mut out = Vec::new();
+        assert!(mailboxes[0].wait(Duration::from_millis(10)));
+        mailboxes[0].drain(&mut out);
+        let got: Vec<_> = out
+            .iter()
+            .map(|u| (u.exchange_id, u.bbo.bid_price))
+            .collect();
+        assert_eq!(got, vec![(3, 102.0), (4, 200.0)]);
+        assert_eq!(mailboxes[0].conflated(), 2);
+
+        // The other subscriber is independent; drained mailboxes stay empty
+        out.clear();
+        mailboxes[1].drain(&mut out);
+        assert_eq!(out.len(), 2);
+        out.clear();
+        mailboxes[0].drain(&mut out);
+        assert!(out.is_empty());
+        assert!(!mailboxes[0].wait(Duration::from_millis(1)));
     }
 }
@@ -51,6 +51,10 @@ pub enum TradingError {
 
     #[error("Insufficient margin to place order")]
     InsufficientMargin,
+
+    /// Accepted, but whether (and how much) it filled could not be confirmed
+    #[error("Order outcome unknown: {0}")]
+    UnknownOutcome(String),
 }
 
 impl From<anyhow::Error> for TradingError {
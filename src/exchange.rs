@@ -84,6 +84,14 @@ pub struct BatchResult {
     pub place_results: Vec<PlaceResult>,
 }
 
+/// 单笔撤单结果（批量撤单用）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CancelResult {
+    pub order_id: i64,
+    /// `None` = 撤单成功
+    pub error: Option<String>,
+}
+
 /// 订单信息（查询用）
 #[derive(Debug, Clone)]
 pub struct OrderInfo {
@@ -112,9 +120,43 @@ pub trait Exchange: Send + Sync {
     /// 撤销单个订单
     async fn cancel_order(&self, order_id: i64) -> Result<()>;
 
-    /// 撤销所有订单
+    /// 批量撤单，逐笔返回结果
+    ///
+    /// 默认实现：逐个调用 `cancel_order`。有原生批量撤单接口的交易所应覆盖此方法。
+    async fn cancel_orders(&self, order_ids: &[i64]) -> Result<Vec<CancelResult>> {
+        let mut results = Vec::with_capacity(order_ids.len());
+        for &order_id in order_ids {
+            let error = self
+                .cancel_order(order_id)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            results.push(CancelResult { order_id, error });
+        }
+        Ok(results)
+    }
+
+    /// 撤销所有订单，返回实际撤销的数量
     async fn cancel_all(&self) -> Result<u32>;
 
+    /// 改单（修改价格/数量）
+    ///
+    /// 默认实现：撤单 + 重新下单（产生新订单 ID）。支持原生改单的交易所应覆盖此方法，
+    /// 以保留订单 ID 并省去一条消息。
+    async fn amend_order(
+        &self,
+        order_id: i64,
+        side: Side,
+        new_price: f64,
+        new_size: f64,
+    ) -> Result<OrderResult> {
+        self.cancel_order(order_id).await?;
+        match side {
+            Side::Buy => self.buy(new_size, new_price).await,
+            Side::Sell => self.sell(new_size, new_price).await,
+        }
+    }
+
     /// 获取活跃订单列表
     async fn get_active_orders(&self) -> Result<Vec<OrderInfo>>;
 
@@ -125,8 +167,17 @@ pub trait Exchange: Send + Sync {
     async fn execute_batch(&self, actions: Vec<BatchAction>) -> Result<BatchResult>;
 
     /// Get account stats (balance, position, etc.)
-    async fn get_account_stats(&self) -> Result<crate::strategy::inventory_neutral_mm::AccountStats>;
+    async fn get_account_stats(
+        &self,
+    ) -> Result<crate::strategy::inventory_neutral_mm::AccountStats>;
 
     /// 获取限价单类型（PostOnly 或 Limit）
     fn limit_order_type(&self) -> OrderType;
+
+    /// 合约规格（tick / lot size、杠杆上限），来自交易所市场元数据
+    async fn get_contract_spec(&self) -> Result<crate::types::ContractSpec> {
+        Err(anyhow::anyhow!(
+            "contract spec not available on this exchange"
+        ))
+    }
 }
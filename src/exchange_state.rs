@@ -0,0 +1,302 @@
+//! Account state shared by every strategy trading the same exchange account.
+//!
+//! One [`ExchangeStateCache`] per account is created in `main` and handed to
+//! each strategy as `Arc<ExchangeStateCache>`. A single background task polls
+//! margin (plus funding for markets that asked for it) every
+//! `balance_refresh_secs`, so running several strategies on one exchange no
+//! longer multiplies the REST call rate. Readers never block on I/O.
+
+use crate::config::AppConfig;
+use crate::exchanges::backpack::client::BackpackClient;
+use crate::exchanges::edgex::client::EdgeXClient;
+use crate::types::{FundingRate, MarginSummary};
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// Latest account values for one market of the exchange.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BalanceSnapshot {
+    /// Account equity in USD (account-wide; 0 until the first successful fetch)
+    pub equity: f64,
+    /// Only refreshed for markets registered with funding
+    pub funding: Option<FundingRate>,
+}
+
+/// REST calls the refresher needs from an exchange account.
+#[async_trait]
+pub trait AccountSource: Send + Sync + 'static {
+    async fn margin(&self) -> anyhow::Result<MarginSummary>;
+    async fn funding(&self, market: &str) -> anyhow::Result<FundingRate>;
+}
+
+#[derive(Debug)]
+pub struct ExchangeStateCache {
+    tag: &'static str,
+    /// Keyed by the venue's market id (Backpack symbol, EdgeX contract id)
+    snapshots: Arc<RwLock<HashMap<String, BalanceSnapshot>>>,
+    funding_markets: RwLock<HashSet<String>>,
+    /// f64 bits of the last positive net equity (also copied into every snapshot)
+    equity_bits: AtomicU64,
+    margin: RwLock<MarginSummary>,
+    /// Bumped on every equity publish so readers can skip unchanged values
+    version: AtomicU64,
+}
+
+impl ExchangeStateCache {
+    pub fn new(tag: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            tag,
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            funding_markets: RwLock::new(HashSet::new()),
+            equity_bits: AtomicU64::new(0),
+            margin: RwLock::new(MarginSummary::default()),
+            version: AtomicU64::new(0),
+        })
+    }
+
+    /// New cache refreshed from `source` every `every` (never refreshed
+    /// without a source, e.g. missing credentials).
+    pub fn with_refresher(
+        tag: &'static str,
+        every: Duration,
+        source: Option<Arc<dyn AccountSource>>,
+    ) -> Arc<Self> {
+        let cache = Self::new(tag);
+        if let Some(source) = source {
+            cache.spawn_refresher(every, source);
+        }
+        cache
+    }
+
+    /// Register interest in `market`; funding is fetched only when requested.
+    pub fn watch(&self, market: &str, with_funding: bool) {
+        let equity = self.equity();
+        self.snapshots
+            .write()
+            .entry(market.to_string())
+            .or_insert(BalanceSnapshot {
+                equity,
+                funding: None,
+            });
+        if with_funding {
+            self.funding_markets.write().insert(market.to_string());
+        }
+    }
+
+    pub fn publish_margin(&self, margin: MarginSummary) {
+        let equity = margin.net_equity;
+        *self.margin.write() = margin;
+        self.equity_bits.store(equity.to_bits(), Ordering::Relaxed);
+        for snapshot in self.snapshots.write().values_mut() {
+            snapshot.equity = equity;
+        }
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    pub fn publish_funding(&self, market: &str, funding: FundingRate) {
+        self.snapshots
+            .write()
+            .entry(market.to_string())
+            .or_default()
+            .funding = Some(funding);
+    }
+
+    pub fn snapshot(&self, market: &str) -> Option<BalanceSnapshot> {
+        self.snapshots.read().get(market).copied()
+    }
+
+    /// Account net equity in USD; 0 until the first successful fetch.
+    pub fn equity(&self) -> f64 {
+        f64::from_bits(self.equity_bits.load(Ordering::Relaxed))
+    }
+
+    /// Last published margin totals (all zero until the first fetch).
+    pub fn margin(&self) -> MarginSummary {
+        *self.margin.read()
+    }
+
+    pub fn funding(&self, market: &str) -> Option<FundingRate> {
+        self.snapshot(market).and_then(|s| s.funding)
+    }
+
+    /// Number of margin publishes so far.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Start the shared refresher on the current runtime (no-op outside one).
+    ///
+    /// The task holds only a weak reference and exits once every strategy has
+    /// dropped the cache.
+    pub fn spawn_refresher(self: &Arc<Self>, every: Duration, source: Arc<dyn AccountSource>) {
+        let Ok(handle) = Handle::try_current() else {
+            return;
+        };
+        let cache: Weak<Self> = Arc::downgrade(self);
+        let tag = self.tag;
+        handle.spawn(async move {
+            let mut ticker = tokio::time::interval(every.max(Duration::from_millis(1)));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(markets) = cache
+                    .upgrade()
+                    .map(|c| c.funding_markets.read().iter().cloned().collect::<Vec<_>>())
+                else {
+                    break;
+                };
+                let mut funding = Vec::with_capacity(markets.len());
+                for market in markets {
+                    let rate = source.funding(&market).await;
+                    funding.push((market, rate));
+                }
+                let margin = source.margin().await;
+
+                let Some(cache) = cache.upgrade() else {
+                    break;
+                };
+                for (market, rate) in funding {
+                    match rate {
+                        Ok(rate) => {
+                            info!(
+                                "💸 [{}] Funding {} {:+.4}% | next settlement {}",
+                                tag,
+                                market,
+                                rate.rate * 100.0,
+                                rate.next_funding_ms
+                            );
+                            cache.publish_funding(&market, rate);
+                        }
+                        Err(e) => warn!("⚠️ [{}] Funding fetch err ({}): {}", tag, market, e),
+                    }
+                }
+                match margin {
+                    Ok(margin) if margin.net_equity > 0.0 => cache.publish_margin(margin),
+                    Ok(_) => warn!(
+                        "⚠️ [{}] No collateral in balance response, keeping last",
+                        tag
+                    ),
+                    Err(e) => warn!("⚠️ [{}] Balance fetch err: {}", tag, e),
+                }
+            }
+        });
+    }
+}
+
+/// Backpack and EdgeX caches for the binaries, each refreshed every
+/// `balance_refresh_secs` of its config section.
+pub fn account_caches(
+    config: &AppConfig,
+    backpack: &Option<Arc<BackpackClient>>,
+    edgex: &Option<(Arc<EdgeXClient>, u64)>,
+) -> (Arc<ExchangeStateCache>, Arc<ExchangeStateCache>) {
+    let backpack_state = ExchangeStateCache::with_refresher(
+        "BP",
+        Duration::from_secs(config.backpack.balance_refresh_secs),
+        backpack.clone().map(|c| c as Arc<dyn AccountSource>),
+    );
+    let edgex_state = ExchangeStateCache::with_refresher(
+        "EX",
+        Duration::from_secs(config.edgex.balance_refresh_secs),
+        edgex.clone().map(|(client, account_id)| {
+            Arc::new(EdgeXAccount { client, account_id }) as Arc<dyn AccountSource>
+        }),
+    );
+    (backpack_state, edgex_state)
+}
+
+#[async_trait]
+impl AccountSource for BackpackClient {
+    async fn margin(&self) -> anyhow::Result<MarginSummary> {
+        Ok(self.get_account_collateral().await?.summary())
+    }
+
+    async fn funding(&self, market: &str) -> anyhow::Result<FundingRate> {
+        self.get_funding_rate(market).await
+    }
+}
+
+/// EdgeX client bound to one account; markets are contract ids.
+pub struct EdgeXAccount {
+    pub client: Arc<EdgeXClient>,
+    pub account_id: u64,
+}
+
+#[async_trait]
+impl AccountSource for EdgeXAccount {
+    async fn margin(&self) -> anyhow::Result<MarginSummary> {
+        Ok(self.client.get_account_margin(self.account_id).await?)
+    }
+
+    async fn funding(&self, market: &str) -> anyhow::Result<FundingRate> {
+        let contract_id: u64 = market.parse()?;
+        Ok(self.client.get_funding_rate(contract_id).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingSource(Arc<AtomicUsize>, Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl AccountSource for CountingSource {
+        async fn margin(&self) -> anyhow::Result<MarginSummary> {
+            let equity = 1000.0 + self.0.fetch_add(1, Ordering::SeqCst) as f64;
+            Ok(MarginSummary {
+                net_equity: equity,
+                used_margin: 100.0,
+                available_margin: equity - 100.0,
+            })
+        }
+        async fn funding(&self, _market: &str) -> anyhow::Result<FundingRate> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            Ok(FundingRate {
+                rate: 0.0001,
+                next_funding_ms: 42,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_one_refresher_serves_every_watcher_and_stops_with_cache() {
+        let cache = ExchangeStateCache::new("T");
+        // Two strategies on the same account, only one of them quoting on funding
+        cache.watch("ETH_USDC_PERP", true);
+        cache.watch("BTC_USDC_PERP", false);
+        let equity_calls = Arc::new(AtomicUsize::new(0));
+        let funding_calls = Arc::new(AtomicUsize::new(0));
+        let source = CountingSource(equity_calls.clone(), funding_calls.clone());
+        cache.spawn_refresher(Duration::from_millis(200), Arc::new(source));
+
+        // First tick fires immediately
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(cache.version(), 1);
+        assert_eq!(cache.snapshot("BTC_USDC_PERP").unwrap().equity, 1000.0);
+        assert_eq!(
+            cache.funding("ETH_USDC_PERP").map(|f| f.next_funding_ms),
+            Some(42)
+        );
+        assert_eq!(cache.funding("BTC_USDC_PERP"), None);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(cache.version(), 2);
+        assert_eq!(cache.equity(), 1001.0);
+        assert_eq!(cache.margin().available_margin, 901.0);
+        assert_eq!(funding_calls.load(Ordering::SeqCst), 2);
+
+        drop(cache);
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        // The next tick observes the dropped cache and exits before fetching
+        assert_eq!(equity_calls.load(Ordering::SeqCst), 2);
+    }
+}
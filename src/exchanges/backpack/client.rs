@@ -1,17 +1,84 @@
 use super::model::*;
+use crate::secret::Secret;
+use crate::types::{ContractSpec, FundingRate, Market, Symbol, SymbolMapper};
 use anyhow::{Context, Result, anyhow};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use ed25519_dalek::{Signer, SigningKey};
-use reqwest::Client;
+use parking_lot::Mutex;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::{Client, Method};
 use serde_json::Value;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+/// Backpack errors callers may want to match on (via `anyhow::Error::downcast_ref`).
+#[derive(Error, Debug)]
+pub enum BackpackError {
+    /// Already filled, cancelled, or never existed.
+    #[error("Backpack order {order_id} not found")]
+    OrderNotFound { order_id: String },
+    /// Non-2xx response to a signed call.
+    #[error("Backpack {label} error: {body}")]
+    Api {
+        label: &'static str,
+        status: u16,
+        body: String,
+    },
+}
+
+impl BackpackError {
+    /// A post-only order that would have taken liquidity.
+    pub fn is_post_only_rejection(&self) -> bool {
+        match self {
+            Self::Api { body, .. } => {
+                body.contains("immediately match") || body.contains("PostOnly")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Signature validity window sent as `X-Window` (ms)
+const WINDOW_MS: u32 = 5000;
+
+/// Page size used by the paged history helpers (API maximum)
+pub const FILL_PAGE_LIMIT: u32 = 1000;
 
 pub struct BackpackClient {
     client: Client,
     api_key: String,
     base_url: String,
     signing_key: SigningKey,
+    /// Server clock minus local clock (ms), applied to every signed timestamp
+    clock_offset_ms: AtomicI64,
+}
+
+/// Process-wide Backpack clients, one per API key, so every strategy and
+/// binary on the same account shares one connection pool (and TLS sessions).
+pub struct BackpackClientPool;
+
+static CLIENT_POOL: OnceLock<Mutex<HashMap<String, Arc<BackpackClient>>>> = OnceLock::new();
+
+impl BackpackClientPool {
+    /// The pooled client for `api_key`, created from the secret and base URL
+    /// on first use (later calls ignore both).
+    pub fn get_or_create(
+        api_key: &str,
+        api_secret_b64: &str,
+        base_url: &str,
+    ) -> Result<Arc<BackpackClient>> {
+        let mut pool = CLIENT_POOL.get_or_init(Default::default).lock();
+        if let Some(client) = pool.get(api_key) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(BackpackClient::new(api_key, api_secret_b64, base_url)?);
+        pool.insert(api_key.to_string(), client.clone());
+        Ok(client)
+    }
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -25,35 +92,64 @@ pub struct BackpackAccountStats {
 
 impl BackpackClient {
     pub fn new(api_key: &str, api_secret_b64: &str, base_url: &str) -> Result<Self> {
-        let secret_bytes = BASE64
-            .decode(api_secret_b64)
-            .context("Failed to decode backpack API secret from base64")?;
-
-        let signing_key = if secret_bytes.len() == 32 {
-            SigningKey::from_bytes(secret_bytes.as_slice().try_into().unwrap())
-        } else if secret_bytes.len() == 64 {
-            let mut arr = [0u8; 32];
-            arr.copy_from_slice(&secret_bytes[..32]); // Take seed only
-            SigningKey::from_bytes(&arr)
-        } else {
-            return Err(anyhow!("Invalid Ed25519 private key length"));
+        let secret_bytes = Secret::new(
+            BASE64
+                .decode(api_secret_b64)
+                .context("Failed to decode backpack API secret from base64")?,
+        );
+
+        // 32-byte seed, or a 64-byte keypair whose first half is the seed
+        let seed = match secret_bytes.expose().len() {
+            32 | 64 => Secret::new(secret_bytes.expose()[..32].to_vec()),
+            _ => return Err(anyhow!("Invalid Ed25519 private key length")),
         };
+        let signing_key = SigningKey::from_bytes(seed.expose().as_slice().try_into()?);
 
         Ok(Self {
-            client: Client::builder().build()?,
+            client: Client::builder()
+                .pool_max_idle_per_host(10)
+                .tcp_keepalive(Duration::from_secs(60))
+                .build()?,
             api_key: api_key.to_string(),
             base_url: base_url.to_string(),
             signing_key,
+            clock_offset_ms: AtomicI64::new(0),
         })
     }
 
-    fn generate_signature(
-        &self,
+    /// Canonical signing payload:
+    /// `instruction=<ix>&<sorted k=v...>&timestamp=<ts>&window=<w>`
+    fn build_sign_string(
         instruction: &str,
         params: &serde_json::Map<String, Value>,
         timestamp: u128,
         window: u32,
     ) -> String {
+        let payload = Self::instruction_payload(instruction, params);
+        format!("{}&timestamp={}&window={}", payload, timestamp, window)
+    }
+
+    /// Batch payload: one `instruction=<ix>&<sorted k=v...>` per item, in
+    /// body order, then `&timestamp=<ts>&window=<w>` once.
+    fn build_batch_sign_string(
+        instruction: &str,
+        items: &[serde_json::Map<String, Value>],
+        timestamp: u128,
+        window: u32,
+    ) -> String {
+        let payload: Vec<String> = items
+            .iter()
+            .map(|params| Self::instruction_payload(instruction, params))
+            .collect();
+        format!(
+            "{}&timestamp={}&window={}",
+            payload.join("&"),
+            timestamp,
+            window
+        )
+    }
+
+    fn instruction_payload(instruction: &str, params: &serde_json::Map<String, Value>) -> String {
         let mut sorted_keys: Vec<&String> = params.keys().collect();
         sorted_keys.sort();
 
@@ -62,47 +158,206 @@ impl BackpackClient {
 
         for k in sorted_keys {
             if let Some(v) = params.get(k) {
-                let val_str = match v {
-                    Value::String(s) => s.to_string(),
-                    Value::Bool(b) => b.to_string().to_lowercase(),
-                    Value::Number(n) => n.to_string(),
-                    _ => v.to_string(),
-                };
-                query_parts.push(format!("{}={}", k, val_str));
+                query_parts.push(format!("{}={}", k, value_to_string(v)));
             }
         }
 
-        query_parts.push(format!("timestamp={}", timestamp));
-        query_parts.push(format!("window={}", window));
+        query_parts.join("&")
+    }
 
-        let sign_string = query_parts.join("&");
-        // tracing::debug!("Backpack Sign Payload: {}", sign_string);
+    #[cfg(test)]
+    fn generate_signature(
+        &self,
+        instruction: &str,
+        params: &serde_json::Map<String, Value>,
+        timestamp: u128,
+        window: u32,
+    ) -> String {
+        self.sign(&Self::build_sign_string(
+            instruction,
+            params,
+            timestamp,
+            window,
+        ))
+    }
 
+    fn sign(&self, sign_string: &str) -> String {
         let signature = self.signing_key.sign(sign_string.as_bytes());
         BASE64.encode(signature.to_bytes())
     }
 
-    pub async fn get_open_positions(&self) -> Result<Vec<BackpackPosition>> {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-        let params = serde_json::Map::new();
-        let signature = self.generate_signature("positionQuery", &params, timestamp, 5000);
+    /// Local clock corrected by the last measured server offset (ms).
+    fn timestamp_ms(&self) -> u128 {
+        (local_ms() + self.clock_offset_ms.load(Ordering::Relaxed)).max(0) as u128
+    }
+
+    pub fn clock_offset_ms(&self) -> i64 {
+        self.clock_offset_ms.load(Ordering::Relaxed)
+    }
 
+    pub fn set_clock_offset_ms(&self, offset_ms: i64) {
+        self.clock_offset_ms.store(offset_ms, Ordering::Relaxed);
+    }
+
+    /// Measure the server − local clock offset from `GET /api/v1/time`
+    /// (server time taken at the midpoint of the round trip) and apply it.
+    pub async fn sync_clock(&self) -> Result<i64> {
+        let sent = local_ms();
+        let url = format!("{}/api/v1/time", self.base_url);
+        let resp = self.client.get(&url).send().await?;
+        if !resp.status().is_success() {
+            let txt = resp.text().await?;
+            return Err(anyhow!("Backpack time error: {}", txt));
+        }
+        let server: i64 = resp
+            .text()
+            .await?
+            .trim()
+            .parse()
+            .context("Backpack time: bad body")?;
+        let offset = server - (sent + local_ms()) / 2;
+        self.set_clock_offset_ms(offset);
+        Ok(offset)
+    }
+
+    /// Resync the clock now and every `every` on the current runtime (no-op
+    /// outside one). Stops once the client is dropped.
+    pub fn spawn_clock_sync(self: &Arc<Self>, every: Duration) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let client: Weak<Self> = Arc::downgrade(self);
+        handle.spawn(async move {
+            let mut ticker = tokio::time::interval(every.max(Duration::from_secs(1)));
+            loop {
+                ticker.tick().await;
+                let Some(client) = client.upgrade() else {
+                    break;
+                };
+                match client.sync_clock().await {
+                    Ok(offset) if offset.abs() > WINDOW_MS as i64 / 2 => {
+                        warn!(
+                            "⏱️ [BP] Local clock is {}ms off exchange time (corrected)",
+                            -offset
+                        )
+                    }
+                    Ok(offset) => debug!("⏱️ [BP] Clock offset {}ms", offset),
+                    Err(e) => warn!("⚠️ [BP] Clock sync failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Auth headers for `instruction` signed at `timestamp`.
+    #[cfg(test)]
+    fn signed_headers(
+        &self,
+        instruction: &str,
+        params: &serde_json::Map<String, Value>,
+        timestamp: u128,
+    ) -> Result<HeaderMap> {
+        let sign_string = Self::build_sign_string(instruction, params, timestamp, WINDOW_MS);
+        self.headers_for(&sign_string, timestamp)
+    }
+
+    fn headers_for(&self, sign_string: &str, timestamp: u128) -> Result<HeaderMap> {
+        let signature = self.sign(sign_string);
         let mut headers = HeaderMap::new();
         headers.insert("X-API-Key", HeaderValue::from_str(&self.api_key)?);
         headers.insert(
             "X-Timestamp",
             HeaderValue::from_str(&timestamp.to_string())?,
         );
-        headers.insert("X-Window", HeaderValue::from_static("5000"));
+        headers.insert("X-Window", HeaderValue::from(WINDOW_MS));
         headers.insert("X-Signature", HeaderValue::from_str(&signature)?);
+        Ok(headers)
+    }
 
-        let url = format!("{}/api/v1/position", self.base_url);
-        let resp = self.client.get(&url).headers(headers).send().await?;
+    /// Send a signed request: `params` go in the query string for GET and in
+    /// the JSON body otherwise. A "timestamp outside the window" rejection
+    /// triggers one clock resync and retry. Non-2xx → [`BackpackError::Api`].
+    async fn send_signed(
+        &self,
+        label: &'static str,
+        method: Method,
+        path: &str,
+        instruction: &str,
+        params: &serde_json::Map<String, Value>,
+    ) -> Result<reqwest::Response> {
+        let is_get = method == Method::GET;
+        self.send_signed_with(
+            label,
+            method,
+            path,
+            |ts| Self::build_sign_string(instruction, params, ts, WINDOW_MS),
+            |req| {
+                if is_get {
+                    let query: Vec<(&String, String)> = params
+                        .iter()
+                        .map(|(k, v)| (k, value_to_string(v)))
+                        .collect();
+                    req.query(&query)
+                } else {
+                    // Backpack strict req: send JSON exactly matching the signed map
+                    req.header(CONTENT_TYPE, "application/json; charset=utf-8")
+                        .json(params)
+                }
+            },
+        )
+        .await
+    }
 
-        if !resp.status().is_success() {
-            let txt = resp.text().await?;
-            return Err(anyhow!("Backpack get_open_positions error: {}", txt));
+    /// [`Self::send_signed`] with a caller-built sign string and request body.
+    async fn send_signed_with(
+        &self,
+        label: &'static str,
+        method: Method,
+        path: &str,
+        sign_string: impl Fn(u128) -> String,
+        body: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut resynced = false;
+        loop {
+            let timestamp = self.timestamp_ms();
+            let headers = self.headers_for(&sign_string(timestamp), timestamp)?;
+            let req = body(self.client.request(method.clone(), &url).headers(headers));
+            let resp = req.send().await?;
+            if resp.status().is_success() {
+                return Ok(resp);
+            }
+
+            let status = resp.status().as_u16();
+            let body = resp.text().await?;
+            if !resynced && is_timestamp_rejection(&body) {
+                resynced = true;
+                let offset = self.sync_clock().await?;
+                warn!(
+                    "⏱️ [BP] {} timestamp rejected, offset now {}ms, retrying",
+                    label, offset
+                );
+                continue;
+            }
+            return Err(BackpackError::Api {
+                label,
+                status,
+                body,
+            }
+            .into());
         }
+    }
+
+    pub async fn get_open_positions(&self) -> Result<Vec<BackpackPosition>> {
+        let params = serde_json::Map::new();
+        let resp = self
+            .send_signed(
+                "get_open_positions",
+                Method::GET,
+                "/api/v1/position",
+                "positionQuery",
+                &params,
+            )
+            .await?;
 
         let json: Value = resp.json().await?;
         if json.as_array().is_some() {
@@ -121,144 +376,283 @@ impl BackpackClient {
         &self,
         order: &BackpackOrderRequest,
     ) -> Result<BackpackOrderResponse> {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-
-        let mut params_map = serde_json::Map::new();
-        let body_val = serde_json::to_value(order)?;
-        if let Value::Object(m) = body_val {
-            params_map = m.clone();
-        }
-
-        let signature = self.generate_signature("orderExecute", &params_map, timestamp, 5000);
-
-        let mut headers = HeaderMap::new();
-        headers.insert("X-API-Key", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(
-            "X-Timestamp",
-            HeaderValue::from_str(&timestamp.to_string())?,
-        );
-        headers.insert("X-Window", HeaderValue::from_static("5000"));
-        headers.insert("X-Signature", HeaderValue::from_str(&signature)?);
-        headers.insert(
-            CONTENT_TYPE,
-            HeaderValue::from_static("application/json; charset=utf-8"),
-        );
+        let params_map = Self::order_params(order)?;
+        let resp = self
+            .send_signed(
+                "create_order",
+                Method::POST,
+                "/api/v1/order",
+                "orderExecute",
+                &params_map,
+            )
+            .await?;
 
-        let url = format!("{}/api/v1/order", self.base_url);
+        let ok_resp: BackpackOrderResponse = resp.json().await?;
+        Ok(ok_resp)
+    }
 
-        // Backpack strict req: send JSON exactly matching map
+    /// Submit several orders in one signed request (`POST /api/v1/orders`).
+    ///
+    /// Results come back per order, in request order, so one rejection does
+    /// not fail the rest; the outer error covers the request as a whole.
+    pub async fn create_orders_batch(
+        &self,
+        orders: &[BackpackOrderRequest],
+    ) -> Result<Vec<Result<BackpackOrderResponse>>> {
+        if orders.is_empty() {
+            return Ok(Vec::new());
+        }
+        let items = orders
+            .iter()
+            .map(Self::order_params)
+            .collect::<Result<Vec<_>>>()?;
         let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&params_map)
-            .send()
+            .send_signed_with(
+                "create_orders_batch",
+                Method::POST,
+                "/api/v1/orders",
+                |ts| Self::build_batch_sign_string("orderExecute", &items, ts, WINDOW_MS),
+                |req| {
+                    req.header(CONTENT_TYPE, "application/json; charset=utf-8")
+                        .json(&items)
+                },
+            )
             .await?;
+        let status = resp.status().as_u16();
+        let results: Vec<Value> = resp.json().await?;
+        Ok(parse_batch_results(orders.len(), results, status))
+    }
 
-        if !resp.status().is_success() {
-            let txt = resp.text().await?;
-            return Err(anyhow!("Backpack create_order error: {}", txt));
+    /// Amend price and/or quantity of a resting order, keeping its order ID.
+    pub async fn amend_order(
+        &self,
+        amend: &BackpackAmendOrderRequest,
+    ) -> Result<BackpackOrderResponse> {
+        let mut params_map = serde_json::Map::new();
+        if let Value::Object(m) = serde_json::to_value(amend)? {
+            params_map = m;
         }
 
+        let resp = self
+            .send_signed(
+                "amend_order",
+                Method::PATCH,
+                "/api/v1/order",
+                "orderModify",
+                &params_map,
+            )
+            .await?;
+
         let ok_resp: BackpackOrderResponse = resp.json().await?;
         Ok(ok_resp)
     }
 
-    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<()> {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    /// Set account leverage for a perpetual market (`leverageUpdate`), within
+    /// the market's `spec.max_leverage`.
+    pub async fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: u32,
+        spec: &ContractSpec,
+    ) -> Result<()> {
+        if leverage == 0 {
+            return Err(anyhow!("Backpack set_leverage: leverage must be >= 1"));
+        }
+        if leverage > spec.max_leverage {
+            return Err(anyhow!(
+                "Backpack set_leverage: {}x exceeds {} max of {}x",
+                leverage,
+                symbol,
+                spec.max_leverage
+            ));
+        }
+        let params = Self::leverage_params(symbol, leverage);
+        self.send_signed(
+            "set_leverage",
+            Method::POST,
+            "/api/v1/position/leverage",
+            "leverageUpdate",
+            &params,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Resting reduce-only stop-market order: closes `qty` at market once the
+    /// price trades through `trigger_price` (`side` is the closing side).
+    pub async fn place_stop_loss(
+        &self,
+        symbol: &str,
+        side: &str,
+        qty: &str,
+        trigger_price: &str,
+    ) -> Result<BackpackOrderResponse> {
+        self.create_order(&Self::stop_loss_request(symbol, side, qty, trigger_price))
+            .await
+    }
 
+    fn stop_loss_request(
+        symbol: &str,
+        side: &str,
+        qty: &str,
+        trigger_price: &str,
+    ) -> BackpackOrderRequest {
+        BackpackOrderRequest {
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            order_type: "Market".to_string(),
+            price: String::new(),
+            quantity: qty.to_string(),
+            client_id: None,
+            post_only: None,
+            time_in_force: None,
+            reduce_only: Some(true),
+            trigger_price: Some(trigger_price.to_string()),
+            trigger_quantity: Some(qty.to_string()),
+        }
+    }
+
+    /// `orderExecute` params: the request's camelCase fields, unset options omitted.
+    fn order_params(order: &BackpackOrderRequest) -> Result<serde_json::Map<String, Value>> {
+        match serde_json::to_value(order)? {
+            Value::Object(m) => Ok(m),
+            _ => Ok(serde_json::Map::new()),
+        }
+    }
+
+    fn leverage_params(symbol: &str, leverage: u32) -> serde_json::Map<String, Value> {
         let mut params = serde_json::Map::new();
         params.insert("symbol".to_string(), Value::String(symbol.to_string()));
+        params.insert("leverage".to_string(), Value::String(leverage.to_string()));
+        params
+    }
 
-        let signature = self.generate_signature("orderCancelAll", &params, timestamp, 5000);
+    /// Cancel every open order on the symbol; returns how many were cancelled.
+    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<usize> {
+        self.cancel_orders_of_type(symbol, None).await
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert("X-API-Key", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(
-            "X-Timestamp",
-            HeaderValue::from_str(&timestamp.to_string())?,
-        );
-        headers.insert("X-Window", HeaderValue::from_static("5000"));
-        headers.insert("X-Signature", HeaderValue::from_str(&signature)?);
-        headers.insert(
-            CONTENT_TYPE,
-            HeaderValue::from_static("application/json; charset=utf-8"),
-        );
+    /// Cancel the resting limit orders only, leaving trigger (stop) orders in place.
+    pub async fn cancel_resting_orders(&self, symbol: &str) -> Result<usize> {
+        self.cancel_orders_of_type(symbol, Some("RestingLimitOrder"))
+            .await
+    }
+
+    async fn cancel_orders_of_type(&self, symbol: &str, order_type: Option<&str>) -> Result<usize> {
+        let mut params = serde_json::Map::new();
+        params.insert("symbol".to_string(), Value::String(symbol.to_string()));
+        if let Some(order_type) = order_type {
+            params.insert(
+                "orderType".to_string(),
+                Value::String(order_type.to_string()),
+            );
+        }
 
-        let url = format!("{}/api/v1/orders", self.base_url);
         let resp = self
-            .client
-            .delete(&url)
-            .headers(headers)
-            .json(&params)
-            .send()
+            .send_signed(
+                "cancel_all_orders",
+                Method::DELETE,
+                "/api/v1/orders",
+                "orderCancelAll",
+                &params,
+            )
             .await?;
+        // The cancelled orders come back as a list
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        Ok(body.as_array().map_or(0, Vec::len))
+    }
 
-        if !resp.status().is_success() {
-            let txt = resp.text().await?;
-            return Err(anyhow!("Backpack cancel_all_orders error: {}", txt));
+    /// Cancel one resting order (`orderCancel`).
+    ///
+    /// Fails with [`BackpackError::OrderNotFound`] when the order is no longer open.
+    pub async fn cancel_order(
+        &self,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<BackpackOrderResponse> {
+        let cancel = BackpackCancelOrderRequest {
+            symbol: symbol.to_string(),
+            order_id: order_id.to_string(),
+        };
+        let mut params = serde_json::Map::new();
+        if let Value::Object(m) = serde_json::to_value(&cancel)? {
+            params = m;
         }
 
-        Ok(())
-    }
+        let resp = self
+            .send_signed(
+                "cancel_order",
+                Method::DELETE,
+                "/api/v1/order",
+                "orderCancel",
+                &params,
+            )
+            .await
+            .map_err(|e| match e.downcast_ref::<BackpackError>() {
+                Some(BackpackError::Api { status, body, .. }) if is_not_found(*status, body) => {
+                    BackpackError::OrderNotFound {
+                        order_id: order_id.to_string(),
+                    }
+                    .into()
+                }
+                _ => e,
+            })?;
 
-    pub async fn get_balances(&self) -> Result<std::collections::HashMap<String, BackpackBalance>> {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-        let params = serde_json::Map::new();
-        let signature = self.generate_signature("balanceQuery", &params, timestamp, 5000);
+        let ok_resp: BackpackOrderResponse = resp.json().await?;
+        Ok(ok_resp)
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert("X-API-Key", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(
-            "X-Timestamp",
-            HeaderValue::from_str(&timestamp.to_string())?,
-        );
-        headers.insert("X-Window", HeaderValue::from_static("5000"));
-        headers.insert("X-Signature", HeaderValue::from_str(&signature)?);
+    /// All resting orders on `symbol` (`orderQueryAll`).
+    pub async fn get_open_orders(&self, symbol: &str) -> Result<Vec<BackpackOpenOrder>> {
+        let mut params = serde_json::Map::new();
+        params.insert("symbol".to_string(), Value::String(symbol.to_string()));
+        let resp = self
+            .send_signed(
+                "get_open_orders",
+                Method::GET,
+                "/api/v1/orders",
+                "orderQueryAll",
+                &params,
+            )
+            .await?;
 
-        let url = format!("{}/api/v1/capital", self.base_url);
-        let resp = self.client.get(&url).headers(headers).send().await?;
+        let json: Value = resp.json().await?;
+        let orders: Vec<BackpackOpenOrder> = serde_json::from_value(json)
+            .map_err(|e| anyhow!("Backpack get_open_orders decode error: {}", e))?;
+        Ok(orders)
+    }
 
-        if !resp.status().is_success() {
-            let txt = resp.text().await?;
-            return Err(anyhow!("Backpack get_balances error: {}", txt));
-        }
+    pub async fn get_balances(&self) -> Result<std::collections::HashMap<String, BackpackBalance>> {
+        let params = serde_json::Map::new();
+        let resp = self
+            .send_signed(
+                "get_balances",
+                Method::GET,
+                "/api/v1/capital",
+                "balanceQuery",
+                &params,
+            )
+            .await?;
 
         let json: Value = resp.json().await?;
         tracing::debug!("🔍 [BP] Raw balance response: {}", json);
-        let mut balances = std::collections::HashMap::new();
-        if let Some(obj) = json.as_object() {
-            for (asset, data) in obj {
-                if let Ok(b) = serde_json::from_value::<BackpackBalance>(data.clone()) {
-                    balances.insert(asset.clone(), b);
-                } else {
-                    // Try parsing manually if nested different
-                    let available = data
-                        .get("available")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("0");
-                    let locked = data.get("locked").and_then(|v| v.as_str()).unwrap_or("0");
-                    balances.insert(
-                        asset.clone(),
-                        BackpackBalance {
-                            symbol: asset.clone(),
-                            available: available.to_string(),
-                            locked: locked.to_string(),
-                        },
-                    );
-                }
-            }
-        }
+        let balances = serde_json::from_value(json)
+            .map_err(|e| anyhow!("Backpack get_balances decode error: {}", e))?;
         Ok(balances)
     }
 
+    /// One asset's balance (`None` if the account holds none). Backpack has
+    /// no single-asset endpoint, so this picks it out of `get_balances`.
+    pub async fn get_balance(&self, asset: &str) -> Result<Option<BackpackBalance>> {
+        Ok(self.get_balances().await?.remove(asset))
+    }
+
     pub async fn get_recent_fills(
         &self,
         symbol: &str,
         limit: u32,
         offset: u32,
     ) -> Result<Vec<BackpackFill>> {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
         let mut params = serde_json::Map::new();
         params.insert("symbol".to_string(), Value::String(symbol.to_string()));
         params.insert(
@@ -269,68 +663,112 @@ impl BackpackClient {
             "offset".to_string(),
             Value::Number(serde_json::Number::from(offset)),
         );
-        let signature = self.generate_signature("fillHistoryQueryAll", &params, timestamp, 5000);
-
-        let mut headers = HeaderMap::new();
-        headers.insert("X-API-Key", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(
-            "X-Timestamp",
-            HeaderValue::from_str(&timestamp.to_string())?,
-        );
-        headers.insert("X-Window", HeaderValue::from_static("5000"));
-        headers.insert("X-Signature", HeaderValue::from_str(&signature)?);
-
-        let url = format!(
-            "{}/wapi/v1/history/fills?symbol={}&limit={}&offset={}",
-            self.base_url, symbol, limit, offset
-        );
-        let resp = self.client.get(&url).headers(headers).send().await?;
-
-        if !resp.status().is_success() {
-            let txt = resp.text().await?;
-            return Err(anyhow!("Backpack get_recent_fills error: {}", txt));
-        }
+        let resp = self
+            .send_signed(
+                "get_recent_fills",
+                Method::GET,
+                "/wapi/v1/history/fills",
+                "fillHistoryQueryAll",
+                &params,
+            )
+            .await?;
 
         let json: Value = resp.json().await?;
-        let fills: Vec<BackpackFill> = serde_json::from_value(json).unwrap_or_default();
+        let fills: Vec<BackpackFill> = serde_json::from_value(json)
+            .map_err(|e| anyhow!("Backpack get_recent_fills decode error: {}", e))?;
         Ok(fills)
     }
 
-    /// Get margin account collateral information (for perpetual trading)
-    /// This returns the actual trading account equity, not just spot balances
-    pub async fn get_collateral(&self) -> Result<f64> {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
-        let params = serde_json::Map::new();
-        let signature = self.generate_signature("collateralQuery", &params, timestamp, 5000);
-
-        let mut headers = HeaderMap::new();
-        headers.insert("X-API-Key", HeaderValue::from_str(&self.api_key)?);
-        headers.insert(
-            "X-Timestamp",
-            HeaderValue::from_str(&timestamp.to_string())?,
-        );
-        headers.insert("X-Window", HeaderValue::from_static("5000"));
-        headers.insert("X-Signature", HeaderValue::from_str(&signature)?);
+    /// Every fill on `symbol` at or after `since_ms` (Unix ms), newest first.
+    ///
+    /// Walks the history in pages of [`FILL_PAGE_LIMIT`] until a short page or
+    /// a fill older than `since_ms` shows there is nothing further back.
+    pub async fn get_all_fills_since(
+        &self,
+        symbol: &str,
+        since_ms: u64,
+    ) -> Result<Vec<BackpackFill>> {
+        let mut fills = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = self
+                .get_recent_fills(symbol, FILL_PAGE_LIMIT, offset)
+                .await?;
+            let page_len = page.len() as u32;
+            let more = collect_page_since(&mut fills, page, since_ms, |f| f.timestamp);
+            if !more || page_len < FILL_PAGE_LIMIT {
+                return Ok(fills);
+            }
+            offset += page_len;
+        }
+    }
 
-        let url = format!("{}/api/v1/capital/collateral", self.base_url);
-        let resp = self.client.get(&url).headers(headers).send().await?;
+    /// Orders (filled, cancelled, expired, ...) created in `[start_ms, end_ms]`,
+    /// newest first, optionally for one symbol. Pages through
+    /// `orderHistoryQueryAll` until it reaches orders older than `start_ms`.
+    pub async fn get_trade_history(
+        &self,
+        symbol: Option<&str>,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<BackpackHistoricalOrder>> {
+        let mut orders = Vec::new();
+        let mut offset = 0;
+        loop {
+            let mut params = serde_json::Map::new();
+            if let Some(symbol) = symbol {
+                params.insert("symbol".to_string(), Value::String(symbol.to_string()));
+            }
+            params.insert("limit".to_string(), Value::from(FILL_PAGE_LIMIT));
+            params.insert("offset".to_string(), Value::from(offset));
+            let resp = self
+                .send_signed(
+                    "get_trade_history",
+                    Method::GET,
+                    "/wapi/v1/history/orders",
+                    "orderHistoryQueryAll",
+                    &params,
+                )
+                .await?;
+            let page: Vec<BackpackHistoricalOrder> = resp
+                .json()
+                .await
+                .map_err(|e| anyhow!("Backpack get_trade_history decode error: {}", e))?;
 
-        if !resp.status().is_success() {
-            let txt = resp.text().await?;
-            return Err(anyhow!("Backpack get_collateral error: {}", txt));
+            let page_len = page.len() as u32;
+            let more = collect_page_since(&mut orders, page, start_ms, |o| o.created_at);
+            if !more || page_len < FILL_PAGE_LIMIT {
+                break;
+            }
+            offset += page_len;
         }
+        orders.retain(|o| o.created_at <= end_ms);
+        Ok(orders)
+    }
+
+    /// Margin account totals (net equity incl. unrealized PnL, locked and
+    /// available margin), as opposed to the raw spot balances.
+    pub async fn get_account_collateral(&self) -> Result<BackpackCollateral> {
+        let params = serde_json::Map::new();
+        let resp = self
+            .send_signed(
+                "get_collateral",
+                Method::GET,
+                "/api/v1/capital/collateral",
+                "collateralQuery",
+                &params,
+            )
+            .await?;
 
         let json: Value = resp.json().await?;
         tracing::debug!("🔍 [BP] Collateral response: {}", json);
+        serde_json::from_value(json)
+            .map_err(|e| anyhow!("Backpack get_collateral decode error: {}", e))
+    }
 
-        // Extract netEquity from the response
-        let net_equity = json
-            .get("netEquity")
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.0);
-
-        Ok(net_equity)
+    /// Margin account net equity (USD)
+    pub async fn get_collateral(&self) -> Result<f64> {
+        Ok(self.get_account_collateral().await?.net_equity)
     }
 
     /// Compute total account equity in USD by summing all non-zero spot balances
@@ -350,9 +788,7 @@ impl BackpackClient {
         let mut total_usd = 0.0_f64;
 
         for (symbol, bal) in &balances {
-            let available: f64 = bal.available.parse().unwrap_or(0.0);
-            let locked: f64 = bal.locked.parse().unwrap_or(0.0);
-            let qty = available + locked;
+            let qty = bal.total();
             if qty < 0.001 {
                 continue;
             }
@@ -369,7 +805,13 @@ impl BackpackClient {
             }
 
             // Look up USD price via public ticker
-            let ticker_symbol = format!("{}_USDC", symbol);
+            let Ok(ticker_symbol) = SymbolMapper::default().to_exchange(
+                &Symbol::pair(symbol, "USDC"),
+                "backpack",
+                Market::Spot,
+            ) else {
+                continue;
+            };
             let url = format!("{}/api/v1/ticker?symbol={}", self.base_url, ticker_symbol);
             if let Ok(resp) = self.client.get(&url).send().await
                 && resp.status().is_success()
@@ -399,10 +841,65 @@ impl BackpackClient {
         Ok(total_usd)
     }
 
+    /// Current funding rate and next settlement (public `markPrices`, no auth).
+    pub async fn get_funding_rate(&self, symbol: &str) -> Result<FundingRate> {
+        let url = format!("{}/api/v1/markPrices?symbol={}", self.base_url, symbol);
+        let resp = self.client.get(&url).send().await?;
+        if !resp.status().is_success() {
+            let txt = resp.text().await?;
+            return Err(anyhow!("Backpack markPrices error: {}", txt));
+        }
+
+        let marks: Vec<BackpackMarkPrice> = resp.json().await?;
+        let mark = marks
+            .into_iter()
+            .find(|m| m.symbol == symbol)
+            .ok_or_else(|| anyhow!("Backpack markPrices: no entry for {}", symbol))?;
+        Ok(FundingRate {
+            rate: mark
+                .funding_rate
+                .parse()
+                .context("Backpack markPrices: bad fundingRate")?,
+            next_funding_ms: mark.next_funding_timestamp,
+        })
+    }
+
+    /// Tick / lot size and leverage cap of a market (public `markets`, no auth).
+    pub async fn get_contract_spec(&self, symbol: &str) -> Result<ContractSpec> {
+        let url = format!("{}/api/v1/markets", self.base_url);
+        let resp = self.client.get(&url).send().await?;
+        if !resp.status().is_success() {
+            let txt = resp.text().await?;
+            return Err(anyhow!("Backpack markets error: {}", txt));
+        }
+
+        let markets: Vec<BackpackMarket> = resp.json().await?;
+        let market = markets
+            .iter()
+            .find(|m| m.symbol == symbol)
+            .ok_or_else(|| anyhow!("Backpack markets: no entry for {}", symbol))?;
+        Self::contract_spec(market)
+    }
+
+    /// Backpack publishes no notional floor; `minQuantity` covers it.
+    fn contract_spec(market: &BackpackMarket) -> Result<ContractSpec> {
+        let filters = &market.filters;
+        Ok(ContractSpec {
+            tick_size: filters.price.tick_size.parse().context("bad tickSize")?,
+            lot_size: filters.quantity.step_size.parse().context("bad stepSize")?,
+            min_notional: 0.0,
+            max_leverage: match &filters.leverage {
+                Some(l) => l.max_leverage.parse().context("bad maxLeverage")?,
+                None => 1,
+            },
+            settlement_asset: market.quote_symbol.clone(),
+        })
+    }
+
     pub async fn get_account_stats(&self) -> Result<BackpackAccountStats> {
         let total_equity = self.get_total_equity().await?;
         let positions = self.get_open_positions().await?;
-        
+
         // Sum position notional for leverage calculation
         let mut total_notional = 0.0;
         let mut main_pos = 0.0;
@@ -416,8 +913,453 @@ impl BackpackClient {
             available_balance: total_equity, // Backpack treats all spot as collateral
             portfolio_value: total_equity,
             position: main_pos,
-            leverage: if total_equity > 0.0 { total_notional / total_equity } else { 0.0 },
-            margin_usage: if total_equity > 0.0 { (total_notional / total_equity) / 20.0 } else { 0.0 }, // Assuming 20x max
+            leverage: if total_equity > 0.0 {
+                total_notional / total_equity
+            } else {
+                0.0
+            },
+            margin_usage: if total_equity > 0.0 {
+                (total_notional / total_equity) / 20.0
+            } else {
+                0.0
+            }, // Assuming 20x max
         })
     }
 }
+
+fn local_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Query-string / signing form of a parameter value.
+fn value_to_string(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.to_string(),
+        Value::Bool(b) => b.to_string().to_lowercase(),
+        Value::Number(n) => n.to_string(),
+        _ => v.to_string(),
+    }
+}
+
+/// Backpack's "request expired / timestamp outside the window" rejection.
+fn is_timestamp_rejection(body: &str) -> bool {
+    let body = body.to_ascii_lowercase();
+    body.contains("expired") || (body.contains("timestamp") && !body.contains("not found"))
+}
+
+/// One result per submitted order: an order object, or the item's error body
+/// as [`BackpackError::Api`]. Missing trailing items count as failed.
+fn parse_batch_results(
+    expected: usize,
+    results: Vec<Value>,
+    status: u16,
+) -> Vec<Result<BackpackOrderResponse>> {
+    let mut results = results.into_iter();
+    (0..expected)
+        .map(|i| {
+            let Some(item) = results.next() else {
+                return Err(anyhow!("no result for batch order {}", i));
+            };
+            if item.get("id").is_some() {
+                return Ok(serde_json::from_value(item)?);
+            }
+            Err(BackpackError::Api {
+                label: "create_orders_batch",
+                status,
+                body: item.to_string(),
+            }
+            .into())
+        })
+        .collect()
+}
+
+/// Backpack answers cancels of unknown orders with 404 / `RESOURCE_NOT_FOUND`.
+fn is_not_found(status: u16, body: &str) -> bool {
+    status == 404 || body.contains("RESOURCE_NOT_FOUND") || body.contains("Order not found")
+}
+
+/// Append the entries of one newest-first page created at or after `since_ms`.
+/// Returns `false` once the page reaches past `since_ms` (stop paging).
+fn collect_page_since<T>(
+    out: &mut Vec<T>,
+    page: Vec<T>,
+    since_ms: u64,
+    timestamp_ms: impl Fn(&T) -> u64,
+) -> bool {
+    let mut more = true;
+    for item in page {
+        if timestamp_ms(&item) < since_ms {
+            more = false;
+            continue;
+        }
+        out.push(item);
+    }
+    more
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier};
+
+    #[test]
+    fn test_leverage_update_signature() {
+        let seed = [7u8; 32];
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode(seed), "http://localhost").unwrap();
+        let params = BackpackClient::leverage_params("ETH_USDC_PERP", 5);
+
+        let sign_string =
+            BackpackClient::build_sign_string("leverageUpdate", &params, 1_700_000_000_000, 5000);
+        assert_eq!(
+            sign_string,
+            "instruction=leverageUpdate&leverage=5&symbol=ETH_USDC_PERP&timestamp=1700000000000&window=5000"
+        );
+
+        let sig_b64 = client.generate_signature("leverageUpdate", &params, 1_700_000_000_000, 5000);
+        let sig_bytes: [u8; 64] = BASE64.decode(sig_b64).unwrap().try_into().unwrap();
+        let verifying_key = SigningKey::from_bytes(&seed).verifying_key();
+        assert!(
+            verifying_key
+                .verify(sign_string.as_bytes(), &Signature::from_bytes(&sig_bytes))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_reduce_only_ioc_order_signature() {
+        let seed = [7u8; 32];
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode(seed), "http://localhost").unwrap();
+        let order = BackpackOrderRequest {
+            symbol: "ETH_USDC_PERP".to_string(),
+            side: "Ask".to_string(),
+            order_type: "Limit".to_string(),
+            price: "1996.00".to_string(),
+            quantity: "0.25".to_string(),
+            client_id: None,
+            post_only: Some(false),
+            time_in_force: Some("IOC".to_string()),
+            reduce_only: Some(true),
+            trigger_price: None,
+            trigger_quantity: None,
+        };
+        let params = BackpackClient::order_params(&order).unwrap();
+
+        let sign_string =
+            BackpackClient::build_sign_string("orderExecute", &params, 1_700_000_000_000, 5000);
+        assert_eq!(
+            sign_string,
+            "instruction=orderExecute&orderType=Limit&postOnly=false&price=1996.00\
+             &quantity=0.25&reduceOnly=true&side=Ask&symbol=ETH_USDC_PERP&timeInForce=IOC\
+             &timestamp=1700000000000&window=5000"
+        );
+
+        let sig_b64 = client.generate_signature("orderExecute", &params, 1_700_000_000_000, 5000);
+        let sig_bytes: [u8; 64] = BASE64.decode(sig_b64).unwrap().try_into().unwrap();
+        let verifying_key = SigningKey::from_bytes(&seed).verifying_key();
+        assert!(
+            verifying_key
+                .verify(sign_string.as_bytes(), &Signature::from_bytes(&sig_bytes))
+                .is_ok()
+        );
+
+        // Unset flags stay out of both the body and the signature
+        let plain = BackpackOrderRequest {
+            reduce_only: None,
+            time_in_force: None,
+            ..order
+        };
+        let params = BackpackClient::order_params(&plain).unwrap();
+        assert!(!params.contains_key("reduceOnly") && !params.contains_key("timeInForce"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_orders_signed_over_every_item_with_partial_failure() {
+        use crate::exchanges::mock_http::MockHttpServer;
+        let server = MockHttpServer::start(|_| {
+            let body = r#"[{"id":"111","symbol":"ETH_USDC_PERP","side":"Bid","status":"New"},
+                {"code":"INVALID_ORDER","message":"Order would immediately match and take."}]"#;
+            (200, body.to_string())
+        })
+        .await;
+        let seed = [7u8; 32];
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode(seed), &server.base_url).unwrap();
+        let order = |side: &str, price: &str| BackpackOrderRequest {
+            symbol: "ETH_USDC_PERP".to_string(),
+            side: side.to_string(),
+            order_type: "Limit".to_string(),
+            price: price.to_string(),
+            quantity: "0.10".to_string(),
+            client_id: None,
+            post_only: Some(true),
+            time_in_force: None,
+            reduce_only: None,
+            trigger_price: None,
+            trigger_quantity: None,
+        };
+        let orders = [order("Bid", "1999.00"), order("Ask", "1999.50")];
+
+        let results = client.create_orders_batch(&orders).await.unwrap();
+        assert_eq!(results[0].as_ref().unwrap().id, "111");
+        let err = results[1].as_ref().unwrap_err();
+        assert!(
+            err.downcast_ref::<BackpackError>()
+                .unwrap()
+                .is_post_only_rejection()
+        );
+
+        let req = &server.requests()[0];
+        assert_eq!(
+            (req.method.as_str(), req.path.as_str()),
+            ("POST", "/api/v1/orders")
+        );
+        let items: Vec<serde_json::Map<String, Value>> = serde_json::from_str(&req.body).unwrap();
+        assert_eq!(items.len(), 2);
+        let ts: u128 = req.headers["x-timestamp"].parse().unwrap();
+        let sign_string = BackpackClient::build_batch_sign_string("orderExecute", &items, ts, 5000);
+        assert!(sign_string.starts_with(
+            "instruction=orderExecute&orderType=Limit&postOnly=true&price=1999.00\
+             &quantity=0.10&side=Bid&symbol=ETH_USDC_PERP&instruction=orderExecute"
+        ));
+        let sig_bytes: [u8; 64] = BASE64
+            .decode(&req.headers["x-signature"])
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let verifying_key = SigningKey::from_bytes(&seed).verifying_key();
+        assert!(
+            verifying_key
+                .verify(sign_string.as_bytes(), &Signature::from_bytes(&sig_bytes))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_client_pool_shares_one_client_per_api_key() {
+        let secret = BASE64.encode([7u8; 32]);
+        let get = |key| BackpackClientPool::get_or_create(key, &secret, "http://localhost");
+        let a = get("pool-key-a").unwrap();
+        assert!(Arc::ptr_eq(&a, &get("pool-key-a").unwrap()));
+        assert!(!Arc::ptr_eq(&a, &get("pool-key-b").unwrap()));
+        assert!(BackpackClientPool::get_or_create("pool-key-c", "not base64", "").is_err());
+    }
+
+    #[test]
+    fn test_stop_loss_request_is_reduce_only_trigger_market() {
+        let order = BackpackClient::stop_loss_request("ETH_USDC_PERP", "Ask", "0.25", "1980.00");
+        let params = BackpackClient::order_params(&order).unwrap();
+        let sign_string =
+            BackpackClient::build_sign_string("orderExecute", &params, 1_700_000_000_000, 5000);
+        // No limit price on a market order
+        assert_eq!(
+            sign_string,
+            "instruction=orderExecute&orderType=Market&quantity=0.25&reduceOnly=true&side=Ask\
+             &symbol=ETH_USDC_PERP&triggerPrice=1980.00&triggerQuantity=0.25\
+             &timestamp=1700000000000&window=5000"
+        );
+    }
+
+    #[test]
+    fn test_collect_page_since_stops_at_cutoff() {
+        let fill = |ts: u64| BackpackFill {
+            symbol: "ETH_USDC_PERP".to_string(),
+            price: 2000.0,
+            quantity: 0.01,
+            side: "Bid".to_string(),
+            is_maker: true,
+            timestamp: ts,
+            fee: 0.0,
+            fee_symbol: "USDC".to_string(),
+        };
+        let mut out = Vec::new();
+        let ts = |f: &BackpackFill| f.timestamp;
+        assert!(collect_page_since(
+            &mut out,
+            vec![fill(300), fill(200)],
+            150,
+            ts
+        ));
+        let page = vec![fill(160), fill(150), fill(100)];
+        assert!(!collect_page_since(&mut out, page, 150, ts));
+        let ts: Vec<u64> = out.iter().map(|f| f.timestamp).collect();
+        assert_eq!(ts, vec![300, 200, 160, 150]);
+    }
+
+    #[tokio::test]
+    async fn test_contract_spec_from_markets() {
+        use crate::exchanges::mock_http::MockHttpServer;
+        let server = MockHttpServer::start(|_| {
+            let body = r#"[{"symbol":"SOL_USDC","baseSymbol":"SOL","quoteSymbol":"USDC",
+                "filters":{"price":{"minPrice":"0.01","tickSize":"0.01"},
+                "quantity":{"minQuantity":"0.01","stepSize":"0.01"}}},
+                {"symbol":"ETH_USDC_PERP","baseSymbol":"ETH","quoteSymbol":"USDC",
+                "filters":{"price":{"minPrice":"0.1","tickSize":"0.1"},
+                "quantity":{"minQuantity":"0.001","stepSize":"0.001"},
+                "leverage":{"minLeverage":"1","maxLeverage":"50","stepSize":"1"}}}]"#;
+            (200, body.to_string())
+        })
+        .await;
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode([7u8; 32]), &server.base_url).unwrap();
+        let spec = client.get_contract_spec("ETH_USDC_PERP").await.unwrap();
+        assert_eq!(
+            spec,
+            ContractSpec {
+                tick_size: 0.1,
+                lot_size: 0.001,
+                min_notional: 0.0,
+                max_leverage: 50,
+                settlement_asset: "USDC".to_string(),
+            }
+        );
+        assert_eq!(
+            client
+                .get_contract_spec("SOL_USDC")
+                .await
+                .unwrap()
+                .max_leverage,
+            1
+        );
+        assert!(client.get_contract_spec("BTC_USDC_PERP").await.is_err());
+        assert_eq!(server.requests()[0].path, "/api/v1/markets");
+    }
+
+    #[tokio::test]
+    async fn test_set_leverage_rejects_above_contract_max() {
+        use crate::exchanges::mock_http::MockHttpServer;
+        let server = MockHttpServer::start(|_| (200, String::new())).await;
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode([7u8; 32]), &server.base_url).unwrap();
+        let spec = ContractSpec {
+            tick_size: 0.1,
+            lot_size: 0.001,
+            min_notional: 0.0,
+            max_leverage: 20,
+            settlement_asset: "USDC".to_string(),
+        };
+
+        let err = client
+            .set_leverage("ETH_USDC_PERP", 25, &spec)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("max of 20x"), "{err}");
+        assert!(
+            client
+                .set_leverage("ETH_USDC_PERP", 0, &spec)
+                .await
+                .is_err()
+        );
+        assert!(server.requests().is_empty());
+
+        client
+            .set_leverage("ETH_USDC_PERP", 20, &spec)
+            .await
+            .unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/api/v1/position/leverage");
+    }
+
+    #[tokio::test]
+    async fn test_single_asset_balance() {
+        use crate::exchanges::mock_http::MockHttpServer;
+        let server = MockHttpServer::start(|_| {
+            let body = r#"{"USDC":{"available":"1500.5","locked":"20","staked":"0"},
+                "SOL":{"available":"3","locked":"0","staked":"1"}}"#;
+            (200, body.to_string())
+        })
+        .await;
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode([7u8; 32]), &server.base_url).unwrap();
+        let usdc = client.get_balance("USDC").await.unwrap().unwrap();
+        assert_eq!(
+            (usdc.available, usdc.locked, usdc.total()),
+            (1500.5, 20.0, 1520.5)
+        );
+        assert!(client.get_balance("USDT").await.unwrap().is_none());
+        let request = &server.requests()[0];
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("GET", "/api/v1/capital")
+        );
+    }
+
+    #[test]
+    fn test_order_cancel_signature_and_not_found_mapping() {
+        let cancel = BackpackCancelOrderRequest {
+            symbol: "ETH_USDC_PERP".to_string(),
+            order_id: "114000000000000001".to_string(),
+        };
+        let Value::Object(params) = serde_json::to_value(&cancel).unwrap() else {
+            panic!("cancel request must serialize to an object");
+        };
+        assert_eq!(
+            BackpackClient::build_sign_string("orderCancel", &params, 1_700_000_000_000, 5000),
+            "instruction=orderCancel&orderId=114000000000000001&symbol=ETH_USDC_PERP\
+             &timestamp=1700000000000&window=5000"
+        );
+
+        assert!(is_not_found(404, ""));
+        assert!(is_not_found(
+            400,
+            r#"{"code":"RESOURCE_NOT_FOUND","message":"Order not found"}"#
+        ));
+        assert!(!is_not_found(400, r#"{"code":"INVALID_CLIENT_REQUEST"}"#));
+
+        let api = |body: &str| BackpackError::Api {
+            label: "create_order",
+            status: 400,
+            body: body.to_string(),
+        };
+        let crossed = r#"{"code":"INVALID_ORDER","message":"Order would immediately match"}"#;
+        assert!(api(crossed).is_post_only_rejection());
+        assert!(!api(r#"{"code":"INSUFFICIENT_FUNDS"}"#).is_post_only_rejection());
+    }
+
+    #[test]
+    fn test_clock_offset_is_applied_to_signed_timestamp() {
+        let seed = [7u8; 32];
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode(seed), "http://localhost").unwrap();
+        // Local clock one minute behind the exchange
+        client.set_clock_offset_ms(60_000);
+
+        let params = BackpackClient::leverage_params("ETH_USDC_PERP", 5);
+        let local = local_ms() as u128;
+        let headers = client
+            .signed_headers("leverageUpdate", &params, client.timestamp_ms())
+            .unwrap();
+        let ts: u128 = headers["X-Timestamp"].to_str().unwrap().parse().unwrap();
+        assert!(
+            ts >= local + 60_000 && ts < local + 61_000,
+            "ts={ts} local={local}"
+        );
+        assert_eq!(headers["X-Window"], "5000");
+
+        // The signature covers the corrected timestamp
+        let sign_string = BackpackClient::build_sign_string("leverageUpdate", &params, ts, 5000);
+        let sig_bytes: [u8; 64] = BASE64
+            .decode(headers["X-Signature"].to_str().unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let verifying_key = SigningKey::from_bytes(&seed).verifying_key();
+        assert!(
+            verifying_key
+                .verify(sign_string.as_bytes(), &Signature::from_bytes(&sig_bytes))
+                .is_ok()
+        );
+
+        assert!(is_timestamp_rejection(
+            r#"{"code":"INVALID_CLIENT_REQUEST","message":"Request has expired"}"#
+        ));
+        assert!(is_timestamp_rejection(
+            "Invalid timestamp: outside of the window"
+        ));
+        assert!(!is_timestamp_rejection(r#"{"code":"RESOURCE_NOT_FOUND"}"#));
+    }
+}
@@ -3,11 +3,12 @@
 //! Wraps BackpackClient to implement the unified Exchange trait.
 
 use super::client::BackpackClient;
-use super::model::BackpackOrderRequest;
+use super::model::{BackpackAmendOrderRequest, BackpackOrderRequest};
+use crate::config::{format_price, format_size};
 use crate::error::TradingError;
 use crate::exchange::{
     BatchAction, BatchOrderParams, BatchOrderResult, BatchResult, Exchange, OrderInfo, OrderParams,
-    OrderResult, OrderType, PlaceResult,
+    OrderResult, OrderType, PlaceResult, Side,
 };
 // use anyhow::anyhow;
 use async_trait::async_trait;
@@ -16,11 +17,26 @@ use std::sync::Arc;
 pub struct BackpackGateway {
     client: Arc<BackpackClient>,
     symbol: String,
+    // Order precision (defaults match the ETH perp)
+    tick_size: f64,
+    step_size: f64,
 }
 
 impl BackpackGateway {
     pub fn new(client: Arc<BackpackClient>, symbol: String) -> Self {
-        Self { client, symbol }
+        Self {
+            client,
+            symbol,
+            tick_size: 0.01,
+            step_size: 0.01,
+        }
+    }
+
+    /// Round amend and IOC prices / sizes to the market's tick and step size.
+    pub fn with_precision(mut self, tick_size: f64, step_size: f64) -> Self {
+        self.tick_size = tick_size;
+        self.step_size = step_size;
+        self
     }
 
     pub async fn place_order(&self, params: OrderParams) -> anyhow::Result<OrderResult> {
@@ -37,6 +53,9 @@ impl BackpackGateway {
             client_id: None,
             post_only: Some(true),
             time_in_force: None,
+            reduce_only: None,
+            trigger_price: None,
+            trigger_quantity: None,
         };
 
         let resp = self.client.create_order(&order).await.map_err(|e| {
@@ -47,7 +66,10 @@ impl BackpackGateway {
             if err_str.contains("insufficient balance") || err_str.contains("insufficient funds") {
                 TradingError::InsufficientMargin
             } else if err_str.contains("Rate limit") || err_str.contains("429") {
-                TradingError::ApiError { status: 429, message: err_str }
+                TradingError::ApiError {
+                    status: 429,
+                    message: err_str,
+                }
             } else {
                 TradingError::OrderFailed(err_str)
             }
@@ -71,6 +93,9 @@ impl Exchange for BackpackGateway {
             client_id: None,
             post_only: Some(true),
             time_in_force: None,
+            reduce_only: None,
+            trigger_price: None,
+            trigger_quantity: None,
         };
 
         let resp = self.client.create_order(&order).await?;
@@ -90,6 +115,9 @@ impl Exchange for BackpackGateway {
             client_id: None,
             post_only: Some(true),
             time_in_force: None,
+            reduce_only: None,
+            trigger_price: None,
+            trigger_quantity: None,
         };
 
         let resp = self.client.create_order(&order).await?;
@@ -111,20 +139,56 @@ impl Exchange for BackpackGateway {
         })
     }
 
-    async fn cancel_order(&self, _order_id: i64) -> anyhow::Result<()> {
-        // Backpack uses string order IDs, not supported in generic trait
-        Err(TradingError::OrderFailed("cancel_order by ID not supported for Backpack".to_string()).into())
+    async fn cancel_order(&self, order_id: i64) -> anyhow::Result<()> {
+        // Backpack order IDs are numeric strings
+        self.client
+            .cancel_order(&self.symbol, &order_id.to_string())
+            .await?;
+        Ok(())
+    }
+
+    async fn amend_order(
+        &self,
+        order_id: i64,
+        _side: Side,
+        new_price: f64,
+        new_size: f64,
+    ) -> anyhow::Result<OrderResult> {
+        // Backpack supports native in-place amendment (PATCH /api/v1/order)
+        let amend = BackpackAmendOrderRequest {
+            symbol: self.symbol.clone(),
+            order_id: order_id.to_string(),
+            price: Some(format_price(new_price, self.tick_size)),
+            quantity: Some(format_size(new_size, self.step_size)),
+        };
+        let resp = self.client.amend_order(&amend).await?;
+        Ok(OrderResult {
+            tx_hash: resp.id,
+            client_order_index: 0,
+        })
     }
 
     async fn cancel_all(&self) -> anyhow::Result<u32> {
-        self.client.cancel_all_orders(&self.symbol).await?;
-        Ok(0) // Backpack doesn't return count
+        Ok(self.client.cancel_all_orders(&self.symbol).await? as u32)
     }
 
     async fn get_active_orders(&self) -> anyhow::Result<Vec<OrderInfo>> {
-        // Backpack doesn't have a direct "get open orders" API
-        // Would need to implement via order history filtering
-        Ok(vec![])
+        let orders = self.client.get_open_orders(&self.symbol).await?;
+        Ok(orders
+            .into_iter()
+            .map(|o| OrderInfo {
+                side: if o.side == "Bid" {
+                    Side::Buy
+                } else {
+                    Side::Sell
+                },
+                order_id: o.id,
+                client_order_index: o.client_id.map_or(0, |id| id as i64),
+                price: o.price,
+                size: o.quantity,
+                filled: o.executed_quantity,
+            })
+            .collect())
     }
 
     async fn close_all_positions(&self, current_price: f64) -> anyhow::Result<()> {
@@ -151,6 +215,9 @@ impl Exchange for BackpackGateway {
                 client_id: None,
                 post_only: None,
                 time_in_force: None,
+                reduce_only: Some(true),
+                trigger_price: None,
+                trigger_quantity: None,
             };
 
             self.client.create_order(&order).await?;
@@ -190,7 +257,9 @@ impl Exchange for BackpackGateway {
         })
     }
 
-    async fn get_account_stats(&self) -> anyhow::Result<crate::strategy::inventory_neutral_mm::AccountStats> {
+    async fn get_account_stats(
+        &self,
+    ) -> anyhow::Result<crate::strategy::inventory_neutral_mm::AccountStats> {
         let stats = self.client.get_account_stats().await?;
         Ok(crate::strategy::inventory_neutral_mm::AccountStats {
             available_balance: stats.available_balance,
@@ -205,4 +274,127 @@ impl Exchange for BackpackGateway {
     fn limit_order_type(&self) -> OrderType {
         OrderType::PostOnly
     }
+
+    async fn get_contract_spec(&self) -> anyhow::Result<crate::types::ContractSpec> {
+        self.client.get_contract_spec(&self.symbol).await
+    }
+}
+
+#[async_trait]
+impl crate::strategy::funding_arb::FundingVenue for BackpackGateway {
+    fn name(&self) -> &str {
+        "backpack"
+    }
+
+    async fn funding_rate(&self) -> anyhow::Result<crate::types::FundingRate> {
+        self.client.get_funding_rate(&self.symbol).await
+    }
+
+    async fn equity(&self) -> anyhow::Result<f64> {
+        self.client.get_total_equity().await
+    }
+
+    async fn take(
+        &self,
+        side: Side,
+        size: f64,
+        price: f64,
+        reduce_only: bool,
+    ) -> anyhow::Result<crate::strategy::funding_arb::TakeFill> {
+        let order = BackpackOrderRequest {
+            symbol: self.symbol.clone(),
+            side: match side {
+                Side::Buy => "Bid",
+                Side::Sell => "Ask",
+            }
+            .to_string(),
+            order_type: "Limit".to_string(),
+            price: format_price(price, self.tick_size),
+            quantity: format_size(size, self.step_size),
+            client_id: None,
+            post_only: Some(false),
+            time_in_force: Some("IOC".to_string()),
+            reduce_only: reduce_only.then_some(true),
+            trigger_price: None,
+            trigger_quantity: None,
+        };
+        // IOC responses are final: the executed part is already in the ack
+        let resp = self.client.create_order(&order).await?;
+        Ok(crate::strategy::funding_arb::TakeFill {
+            filled: resp.executed_quantity,
+            avg_price: resp.avg_fill_price(),
+            order: OrderResult {
+                tx_hash: resp.id,
+                client_order_index: 0,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::mock_http::MockHttpServer;
+    use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+
+    #[tokio::test]
+    async fn test_amend_rounds_to_tick_and_step() {
+        let server = MockHttpServer::start(|_| {
+            let body = r#"{"id":"42","symbol":"ETH_USDC_PERP","side":"Bid","status":"New"}"#;
+            (200, body.to_string())
+        })
+        .await;
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode([7u8; 32]), &server.base_url).unwrap();
+        let gateway = BackpackGateway::new(Arc::new(client), "ETH_USDC_PERP".to_string())
+            .with_precision(0.1, 0.001);
+
+        let res = gateway
+            .amend_order(42, Side::Buy, 1999.9400000001, 0.1 + 0.2)
+            .await
+            .unwrap();
+        assert_eq!(res.tx_hash, "42");
+
+        let req = &server.requests()[0];
+        assert_eq!(
+            (req.method.as_str(), req.path.as_str()),
+            ("PATCH", "/api/v1/order")
+        );
+        let body: serde_json::Value = serde_json::from_str(&req.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "symbol": "ETH_USDC_PERP",
+                "orderId": "42",
+                "price": "1999.9",
+                "quantity": "0.300",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_take_rounds_to_tick_and_step() {
+        use crate::strategy::funding_arb::FundingVenue;
+        let server = MockHttpServer::start(|_| {
+            let body = r#"{"id":"7","symbol":"ETH_USDC_PERP","side":"Bid","status":"Filled",
+                "executedQuantity":"0.004","executedQuoteQuantity":"8.0004"}"#;
+            (200, body.to_string())
+        })
+        .await;
+        let client =
+            BackpackClient::new("test-key", &BASE64.encode([7u8; 32]), &server.base_url).unwrap();
+        let gateway = BackpackGateway::new(Arc::new(client), "ETH_USDC_PERP".to_string())
+            .with_precision(0.01, 0.001);
+
+        let fill = gateway
+            .take(Side::Buy, 0.004, 2000.123, false)
+            .await
+            .unwrap();
+        assert_eq!(fill.filled, 0.004);
+
+        let body: serde_json::Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(body["price"], "2000.12");
+        assert_eq!(body["quantity"], "0.004");
+        assert_eq!(body["timeInForce"], "IOC");
+    }
 }
@@ -1,3 +1,4 @@
+use crate::types::MarginSummary;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -6,14 +7,117 @@ pub struct BackpackOrderRequest {
     pub side: String,
     #[serde(rename = "orderType")]
     pub order_type: String,
+    /// Limit price; left empty (and omitted) for market orders
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub price: String,
     pub quantity: String,
     #[serde(rename = "clientId", skip_serializing_if = "Option::is_none")]
     pub client_id: Option<String>,
     #[serde(rename = "postOnly", skip_serializing_if = "Option::is_none")]
     pub post_only: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "timeInForce", skip_serializing_if = "Option::is_none")]
     pub time_in_force: Option<String>,
+    /// Only shrink the position (stop-loss / flatten); rejected if it would flip it
+    #[serde(rename = "reduceOnly", skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    /// Rests as a conditional order until the mark trades through this price
+    #[serde(rename = "triggerPrice", skip_serializing_if = "Option::is_none")]
+    pub trigger_price: Option<String>,
+    #[serde(rename = "triggerQuantity", skip_serializing_if = "Option::is_none")]
+    pub trigger_quantity: Option<String>,
+}
+
+/// In-place order amendment (`PATCH /api/v1/order`); omitted fields are left unchanged.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackpackAmendOrderRequest {
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<String>,
+}
+
+/// Single-order cancel (`DELETE /api/v1/order`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackpackCancelOrderRequest {
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+}
+
+/// Resting order from `GET /api/v1/orders` (`orderQueryAll`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackpackOpenOrder {
+    pub id: String,
+    #[serde(default, rename = "clientId")]
+    pub client_id: Option<u64>,
+    pub symbol: String,
+    pub side: String,
+    #[serde(rename = "orderType")]
+    pub order_type: String,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "deserialize_f64")]
+    pub quantity: f64,
+    #[serde(
+        default,
+        rename = "executedQuantity",
+        deserialize_with = "deserialize_f64"
+    )]
+    pub executed_quantity: f64,
+    pub status: String,
+    /// Creation time in Unix milliseconds
+    #[serde(rename = "createdAt", deserialize_with = "deserialize_timestamp_ms")]
+    pub created_at: u64,
+}
+
+/// Finished or live order from `GET /wapi/v1/history/orders` (`orderHistoryQueryAll`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackpackHistoricalOrder {
+    pub id: String,
+    pub symbol: String,
+    pub side: String,
+    #[serde(rename = "orderType")]
+    pub order_type: String,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub price: f64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub quantity: f64,
+    #[serde(
+        default,
+        rename = "executedQuantity",
+        deserialize_with = "deserialize_f64"
+    )]
+    pub executed_quantity: f64,
+    /// `Filled`, `Cancelled`, `Expired`, ...
+    pub status: String,
+    #[serde(default, rename = "postOnly")]
+    pub post_only: bool,
+    /// Why an order left the book unfilled (`PostOnlyTaker`, `ImmediateOrCancel`, ...)
+    #[serde(default, rename = "cancelReason", alias = "expiryReason")]
+    pub cancel_reason: Option<String>,
+    /// Creation time in Unix milliseconds
+    #[serde(rename = "createdAt", deserialize_with = "deserialize_timestamp_ms")]
+    pub created_at: u64,
+}
+
+impl BackpackHistoricalOrder {
+    /// Post-only order the matching engine refused because it would have taken.
+    pub fn is_post_only_rejection(&self) -> bool {
+        matches!(
+            self.cancel_reason.as_deref(),
+            Some("PostOnlyTaker" | "POST_ONLY_REJECTED")
+        )
+    }
+}
+
+/// Share of post-only orders rejected for crossing (`None` without post-only orders).
+pub fn post_only_rejection_rate(orders: &[BackpackHistoricalOrder]) -> Option<f64> {
+    let post_only = orders.iter().filter(|o| o.post_only).count();
+    let rejected = orders.iter().filter(|o| o.is_post_only_rejection()).count();
+    (post_only > 0).then(|| rejected as f64 / post_only as f64)
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,9 +127,33 @@ pub struct BackpackOrderResponse {
     pub side: String,
     pub price: Option<String>,
     pub quantity: Option<String>,
+    #[serde(
+        default,
+        rename = "executedQuantity",
+        deserialize_with = "deserialize_f64"
+    )]
+    pub executed_quantity: f64,
+    /// Quote notional of the executed quantity
+    #[serde(
+        default,
+        rename = "executedQuoteQuantity",
+        deserialize_with = "deserialize_f64"
+    )]
+    pub executed_quote_quantity: f64,
     pub status: String,
 }
 
+impl BackpackOrderResponse {
+    /// Average fill price, 0 when nothing executed.
+    pub fn avg_fill_price(&self) -> f64 {
+        if self.executed_quantity > 0.0 {
+            self.executed_quote_quantity / self.executed_quantity
+        } else {
+            0.0
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BackpackPosition {
     pub symbol: String,
@@ -34,24 +162,274 @@ pub struct BackpackPosition {
     pub average_entry_price: Option<String>,
 }
 
+/// Entry of the public `/api/v1/markPrices` endpoint.
+#[derive(Debug, Deserialize)]
+pub struct BackpackMarkPrice {
+    pub symbol: String,
+    #[serde(rename = "fundingRate")]
+    pub funding_rate: String,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    #[serde(rename = "nextFundingTimestamp")]
+    pub next_funding_timestamp: u64,
+}
+
+/// Entry of the public `/api/v1/markets` endpoint (fields we use).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackpackMarket {
+    pub symbol: String,
+    pub quote_symbol: String,
+    pub filters: BackpackMarketFilters,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackpackMarketFilters {
+    pub price: BackpackPriceFilter,
+    pub quantity: BackpackQuantityFilter,
+    /// Perps only
+    #[serde(default)]
+    pub leverage: Option<BackpackLeverageFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackpackPriceFilter {
+    pub tick_size: String,
+}
+
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackpackQuantityFilter {
+    pub step_size: String,
+    pub min_quantity: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackpackLeverageFilter {
+    pub max_leverage: String,
+}
+
+/// Entry of `GET /wapi/v1/history/fills` (newest first).
+#[derive(Debug, Clone, Deserialize)]
 pub struct BackpackFill {
     pub symbol: String,
-    pub price: String,
-    pub quantity: String,
+    #[serde(deserialize_with = "deserialize_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "deserialize_f64")]
+    pub quantity: f64,
     pub side: String,
     #[serde(rename = "isMaker")]
     pub is_maker: bool,
-    pub timestamp: Option<serde_json::Value>,
-    #[serde(default)]
-    pub fee: String,
+    /// Fill time in Unix milliseconds
+    #[serde(deserialize_with = "deserialize_timestamp_ms")]
+    pub timestamp: u64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub fee: f64,
     #[serde(default, rename = "feeSymbol")]
     pub fee_symbol: String,
 }
 
-#[derive(Debug, Deserialize)]
+/// Per-asset entry of `GET /api/v1/capital` (keyed by asset symbol).
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
 pub struct BackpackBalance {
-    pub symbol: String,
-    pub available: String,
-    pub locked: String,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub available: f64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub locked: f64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub staked: f64,
+}
+
+impl BackpackBalance {
+    pub fn total(&self) -> f64 {
+        self.available + self.locked + self.staked
+    }
+}
+
+/// `GET /api/v1/capital/collateral` (margin account totals, USD).
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackpackCollateral {
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub net_equity: f64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub net_equity_available: f64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub net_equity_locked: f64,
+    #[serde(default, deserialize_with = "deserialize_f64")]
+    pub pnl_unrealized: f64,
+}
+
+impl BackpackCollateral {
+    pub fn summary(&self) -> MarginSummary {
+        MarginSummary {
+            net_equity: self.net_equity,
+            used_margin: self.net_equity_locked,
+            available_margin: self.net_equity_available,
+        }
+    }
+}
+
+/// Decimal that Backpack sends either as a string (`"0.01"`) or a JSON number.
+fn deserialize_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Num(f64),
+        Str(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Num(n) => Ok(n),
+        Raw::Str(s) if s.is_empty() => Ok(0.0),
+        Raw::Str(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Timestamp as an ISO-8601 string (`2024-05-08T12:34:56.789`, UTC when no
+/// offset is given) or an integer in s / ms / µs, normalized to milliseconds.
+fn deserialize_timestamp_ms<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Int(u64),
+        Str(String),
+    }
+    let from_int = |n: u64| match n {
+        n if n < 100_000_000_000 => n * 1_000,
+        n if n > 100_000_000_000_000 => n / 1_000,
+        n => n,
+    };
+    match Raw::deserialize(deserializer)? {
+        Raw::Int(n) => Ok(from_int(n)),
+        Raw::Str(s) => {
+            if let Ok(n) = s.parse::<u64>() {
+                return Ok(from_int(n));
+            }
+            let ms = chrono::DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.timestamp_millis())
+                .or_else(|_| {
+                    chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f")
+                        .map(|dt| dt.and_utc().timestamp_millis())
+                })
+                .map_err(|e| serde::de::Error::custom(format!("bad timestamp {s:?}: {e}")))?;
+            u64::try_from(ms).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_timestamp_accepts_iso_and_integer_forms() {
+        let fill = |ts: &str| -> BackpackFill {
+            serde_json::from_str(&format!(
+                r#"{{"symbol":"ETH_USDC_PERP","price":"2000.5","quantity":"0.01",
+                    "side":"Bid","isMaker":true,"timestamp":{ts},"fee":"0.001"}}"#
+            ))
+            .unwrap()
+        };
+        let expected = 1_715_171_696_789;
+        assert_eq!(fill(r#""2024-05-08T12:34:56.789""#).timestamp, expected);
+        assert_eq!(fill(r#""2024-05-08T12:34:56.789Z""#).timestamp, expected);
+        assert_eq!(fill("1715171696789").timestamp, expected);
+        assert_eq!(fill(r#""1715171696789""#).timestamp, expected);
+        assert_eq!(fill("1715171696789000").timestamp, expected);
+        assert_eq!(fill("1715171696").timestamp, 1_715_171_696_000);
+
+        let f = fill("1715171696789");
+        assert_eq!((f.price, f.quantity, f.fee), (2000.5, 0.01, 0.001));
+    }
+
+    #[test]
+    fn test_open_order_parses_typed_fields() {
+        let o: BackpackOpenOrder = serde_json::from_str(
+            r#"{"id":"114000000000000001","clientId":42,"symbol":"ETH_USDC_PERP","side":"Bid",
+                "orderType":"Limit","price":"1999.5","quantity":"0.02","executedQuantity":"0.005",
+                "status":"PartiallyFilled","createdAt":1715171696789,"postOnly":true}"#,
+        )
+        .unwrap();
+        assert_eq!(o.client_id, Some(42));
+        assert_eq!(
+            (o.price, o.quantity, o.executed_quantity),
+            (1999.5, 0.02, 0.005)
+        );
+        assert_eq!(o.created_at, 1_715_171_696_789);
+    }
+
+    #[test]
+    fn test_order_response_reports_executed_quantity() {
+        let o: BackpackOrderResponse = serde_json::from_str(
+            r#"{"id":"1","symbol":"ETH_USDC_PERP","side":"Bid","price":"2001","quantity":"0.1",
+                "executedQuantity":"0.04","executedQuoteQuantity":"80.02","status":"Expired"}"#,
+        )
+        .unwrap();
+        assert_eq!(o.executed_quantity, 0.04);
+        assert!((o.avg_fill_price() - 2000.5).abs() < 1e-9);
+
+        let unfilled: BackpackOrderResponse = serde_json::from_str(
+            r#"{"id":"2","symbol":"ETH_USDC_PERP","side":"Ask","status":"Expired"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            (unfilled.executed_quantity, unfilled.avg_fill_price()),
+            (0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_historical_orders_expose_cancel_reason_and_rejection_rate() {
+        let orders: Vec<BackpackHistoricalOrder> = serde_json::from_str(
+            r#"[{"id":"1","symbol":"ETH_USDC_PERP","side":"Bid","orderType":"Limit",
+                 "price":"2000","quantity":"0.1","executedQuantity":"0","status":"Expired",
+                 "postOnly":true,"expiryReason":"PostOnlyTaker","createdAt":1715171696789},
+                {"id":"2","symbol":"ETH_USDC_PERP","side":"Ask","orderType":"Limit",
+                 "price":"2001","quantity":"0.1","executedQuantity":"0.1","status":"Filled",
+                 "postOnly":true,"createdAt":"2024-05-08T12:34:57"},
+                {"id":"3","symbol":"ETH_USDC_PERP","side":"Ask","orderType":"Market",
+                 "quantity":"0.1","status":"Filled","createdAt":1715171698000}]"#,
+        )
+        .unwrap();
+        assert_eq!(orders[0].cancel_reason.as_deref(), Some("PostOnlyTaker"));
+        assert!(orders[0].is_post_only_rejection());
+        assert_eq!(orders[1].created_at, 1_715_171_697_000);
+        assert_eq!(orders[2].price, 0.0);
+        assert_eq!(post_only_rejection_rate(&orders), Some(0.5));
+        assert_eq!(post_only_rejection_rate(&orders[2..]), None);
+    }
+
+    #[test]
+    fn test_balance_parses_string_amounts_and_missing_staked() {
+        let b: BackpackBalance =
+            serde_json::from_str(r#"{"available":"12.5","locked":"0.5"}"#).unwrap();
+        assert_eq!((b.available, b.locked, b.staked), (12.5, 0.5, 0.0));
+        assert_eq!(b.total(), 13.0);
+    }
+
+    #[test]
+    fn test_collateral_summary_maps_margin_fields() {
+        let c: BackpackCollateral = serde_json::from_str(
+            r#"{"netEquity":"1040.5","netEquityAvailable":"820.5","netEquityLocked":"220",
+                "pnlUnrealized":"40.5","imf":"0.02","collateral":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(c.pnl_unrealized, 40.5);
+        assert_eq!(
+            c.summary(),
+            MarginSummary {
+                net_equity: 1040.5,
+                used_margin: 220.0,
+                available_margin: 820.5,
+            }
+        );
+    }
 }
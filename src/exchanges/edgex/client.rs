@@ -1,5 +1,9 @@
-use super::model::CreateOrderRequest;
+use super::model::{
+    CancelOrdersByIdRequest, CreateOrderEnvelope, CreateOrderRequest, CreateOrderResponse, MetaData,
+};
 use super::signature::SignatureManager;
+use crate::types::ContractSpec;
+use futures::StreamExt;
 use reqwest::Client;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde_json::Value;
@@ -8,16 +12,126 @@ use thiserror::Error;
 
 const BASE_URL: &str = "https://pro.edgex.exchange";
 
+/// `createOrder` calls in flight per [`EdgeXClient::create_orders_batch`]
+const BATCH_CONCURRENCY: usize = 4;
+
 #[derive(Error, Debug)]
 pub enum ClientError {
     #[error("HTTP error: {0}")]
     HttpError(#[from] reqwest::Error),
     #[error("Signature error: {0}")]
     SignatureError(#[from] super::signature::SignatureError),
-    #[error("API error: {0}")]
-    ApiError(String),
+    /// Non-2xx response, or a 2xx whose `code` is not `SUCCESS`.
+    /// Display flattens the body onto one line for log aggregation.
+    #[error("API error: status={status} body={}", single_line(.body))]
+    ApiError { status: u16, body: String },
     #[error("JSON serialization/deserialization error: {0}")]
     JsonError(String),
+    /// Request reached EdgeX and was refused (non-`SUCCESS` code in a 2xx body).
+    #[error("Rejected: code={code} msg={msg}")]
+    Rejected { code: String, msg: String },
+}
+
+impl ClientError {
+    /// The request may have reached the exchange but we never saw the answer
+    /// (timeout, dropped/garbled response, 5xx), so the order state is unknown.
+    pub fn is_unknown_outcome(&self) -> bool {
+        match self {
+            Self::HttpError(e) => e.is_timeout() || e.is_body() || e.is_decode(),
+            Self::ApiError { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// A post-only order that would have crossed the book.
+    pub fn is_post_only_rejection(&self) -> bool {
+        match self {
+            Self::Rejected { code, msg } => {
+                code.to_ascii_uppercase().contains("POST_ONLY")
+                    || msg.to_ascii_uppercase().contains("POST_ONLY")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Fills from a `getHistoryOrderFillTransactionPage` `data` field (paged
+/// `dataList`, or a bare list).
+fn parse_fills(mut data: Value) -> Result<Vec<crate::edgex_api::model::Fill>, ClientError> {
+    let list = match data.get_mut("dataList") {
+        Some(list) => list.take(),
+        None => data,
+    };
+    serde_json::from_value(list).map_err(|e| ClientError::JsonError(e.to_string()))
+}
+
+/// Spec of `contract_id` from a `getMetaData` `data` field. The leverage cap is
+/// the first (smallest position) risk tier's; no notional floor is published.
+fn parse_contract_spec(data: Value, contract_id: u64) -> Result<ContractSpec, ClientError> {
+    let meta: MetaData =
+        serde_json::from_value(data).map_err(|e| ClientError::JsonError(e.to_string()))?;
+    let id = contract_id.to_string();
+    let contract = meta
+        .contract_list
+        .iter()
+        .find(|c| c.contract_id == id)
+        .ok_or_else(|| ClientError::JsonError(format!("no contract {} in metadata", id)))?;
+    let settlement_asset = meta
+        .coin_list
+        .iter()
+        .find(|c| c.coin_id == contract.quote_coin_id)
+        .map_or_else(|| contract.quote_coin_id.clone(), |c| c.coin_name.clone());
+    let num = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|e| ClientError::JsonError(format!("{}: {:?}", e, s)))
+    };
+    let max_leverage = match contract.risk_tier_list.first() {
+        Some(tier) => num(&tier.max_leverage)? as u32,
+        None => 1,
+    };
+    Ok(ContractSpec {
+        tick_size: num(&contract.tick_size)?,
+        lot_size: num(&contract.step_size)?,
+        min_notional: 0.0,
+        max_leverage,
+        settlement_asset,
+    })
+}
+
+/// Typed `createOrder` result; a non-`SUCCESS` code becomes [`ClientError::Rejected`].
+fn parse_create_order_response(
+    json: Value,
+    client_order_id: &str,
+) -> Result<CreateOrderResponse, ClientError> {
+    let envelope: CreateOrderEnvelope =
+        serde_json::from_value(json).map_err(|e| ClientError::JsonError(e.to_string()))?;
+    if envelope.code != "SUCCESS" && envelope.code != "OK" {
+        let msg = envelope
+            .error_param
+            .map(|p| p.to_string())
+            .or(envelope.msg)
+            .unwrap_or_default();
+        return Err(ClientError::Rejected {
+            code: envelope.code,
+            msg,
+        });
+    }
+    let data = envelope
+        .data
+        .ok_or_else(|| ClientError::JsonError("createOrder: missing data".to_string()))?;
+    Ok(CreateOrderResponse {
+        code: envelope.code,
+        order_id: data.order_id,
+        client_order_id: data
+            .client_order_id
+            .unwrap_or_else(|| client_order_id.to_string()),
+        status: data.status,
+        msg: envelope.msg,
+    })
+}
+
+fn single_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 pub struct EdgeXClient {
@@ -83,10 +197,153 @@ impl EdgeXClient {
         format!("{}{}{}{}", timestamp, method, path, body_str)
     }
 
-    pub async fn create_order(&self, req: &CreateOrderRequest) -> Result<Value, ClientError> {
+    /// GET signing payload: `{timestamp}GET{path}{k=v&...}` with keys sorted.
+    fn build_get_sign_content(timestamp: &str, path: &str, params: &[(&str, String)]) -> String {
+        let mut sorted: Vec<&(&str, String)> = params.iter().collect();
+        sorted.sort_by_key(|(k, _)| *k);
+        let query: Vec<String> = sorted.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        format!("{}GET{}{}", timestamp, path, query.join("&"))
+    }
+
+    /// Signed private GET; returns the `data` field of a `SUCCESS` response.
+    async fn signed_get(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<Value, ClientError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let sign_payload = Self::build_get_sign_content(&timestamp, path, params);
+        let header_signature = self.signature_manager.sign_message(&sign_payload)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-edgeX-Api-Timestamp",
+            HeaderValue::from_str(&timestamp).unwrap(),
+        );
+        headers.insert(
+            "X-edgeX-Api-Signature",
+            HeaderValue::from_str(header_signature.trim_start_matches("0x")).unwrap(),
+        );
+
+        let res = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .headers(headers)
+            .query(params)
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await?;
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+
+        let mut json: Value = res.json().await?;
+        if json.get("code").and_then(|c| c.as_str()) != Some("SUCCESS") {
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: json.to_string(),
+            });
+        }
+        Ok(json.get_mut("data").map(Value::take).unwrap_or(Value::Null))
+    }
+
+    /// Look up one of our orders by the `clientOrderId` we sent on create
+    /// (`None` = the exchange never accepted it).
+    pub async fn get_order_by_client_order_id(
+        &self,
+        account_id: u64,
+        client_order_id: &str,
+    ) -> Result<Option<crate::edgex_api::model::OrderDetail>, ClientError> {
+        let data = self
+            .signed_get(
+                "/api/v1/private/order/getOrderByClientOrderId",
+                &Self::client_order_id_params(account_id, client_order_id),
+            )
+            .await?;
+        let orders: Vec<crate::edgex_api::model::OrderDetail> = match data {
+            Value::Null => vec![],
+            data => {
+                serde_json::from_value(data).map_err(|e| ClientError::JsonError(e.to_string()))?
+            }
+        };
+        Ok(orders
+            .into_iter()
+            .find(|o| o.client_order_id == client_order_id))
+    }
+
+    /// Look up one of our orders by exchange order id (`None` = unknown id).
+    pub async fn get_order_by_id(
+        &self,
+        account_id: u64,
+        order_id: u64,
+    ) -> Result<Option<crate::edgex_api::model::OrderDetail>, ClientError> {
+        let params = [
+            ("accountId", account_id.to_string()),
+            ("orderIdList", order_id.to_string()),
+        ];
+        let data = self
+            .signed_get("/api/v1/private/order/getOrderById", &params)
+            .await?;
+        let orders: Vec<crate::edgex_api::model::OrderDetail> = match data {
+            Value::Null => vec![],
+            data => {
+                serde_json::from_value(data).map_err(|e| ClientError::JsonError(e.to_string()))?
+            }
+        };
+        Ok(orders.into_iter().find(|o| o.id == order_id))
+    }
+
+    fn client_order_id_params(
+        account_id: u64,
+        client_order_id: &str,
+    ) -> [(&'static str, String); 2] {
+        [
+            ("accountId", account_id.to_string()),
+            ("clientOrderIdList", client_order_id.to_string()),
+        ]
+    }
+
+    /// Cancel by `clientOrderId`: resolves the exchange order id, then cancels
+    /// it if still live. Returns the order as found (`None` = never accepted).
+    pub async fn cancel_order_by_client_order_id(
+        &self,
+        account_id: u64,
+        client_order_id: &str,
+    ) -> Result<Option<crate::edgex_api::model::OrderDetail>, ClientError> {
+        let Some(order) = self
+            .get_order_by_client_order_id(account_id, client_order_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if order.status.is_live() {
+            let req = crate::edgex_api::model::CancelOrderRequest {
+                account_id,
+                order_id: Some(order.id),
+                client_order_id: None,
+                contract_id: order.contract_id,
+            };
+            self.cancel_order(&req).await?;
+        }
+        Ok(Some(order))
+    }
+
+    pub async fn create_order(
+        &self,
+        req: &CreateOrderRequest,
+    ) -> Result<CreateOrderResponse, ClientError> {
         let url = format!("{}/api/v1/private/order/createOrder", self.base_url);
 
-        let body = serde_json::to_string(req).map_err(|e| ClientError::ApiError(e.to_string()))?;
+        let body = serde_json::to_string(req).map_err(|e| ClientError::JsonError(e.to_string()))?;
         let body_val: Value = serde_json::to_value(req).unwrap();
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -122,14 +379,14 @@ impl EdgeXClient {
         let status = res.status();
         if !status.is_success() {
             let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
         }
 
         let json: Value = res.json().await?;
-        Ok(json)
+        parse_create_order_response(json, &req.client_order_id)
     }
 
     pub async fn cancel_order(
@@ -139,7 +396,7 @@ impl EdgeXClient {
         let url = format!("{}/api/v1/private/order/cancelOrderById", self.base_url);
         // Uses same Header auth mechanism
 
-        let body = serde_json::to_string(req).map_err(|e| ClientError::ApiError(e.to_string()))?;
+        let body = serde_json::to_string(req).map_err(|e| ClientError::JsonError(e.to_string()))?;
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -172,16 +429,95 @@ impl EdgeXClient {
         let status = res.status();
         if !status.is_success() {
             let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
         }
 
         let json: Value = res.json().await?;
         Ok(json)
     }
 
+    /// Submit several orders, at most [`BATCH_CONCURRENCY`] in flight over the
+    /// shared connection pool. EdgeX has no batch create endpoint, so this
+    /// pipelines `createOrder`; results are per order, in request order.
+    pub async fn create_orders_batch(
+        &self,
+        reqs: &[CreateOrderRequest],
+    ) -> Vec<Result<CreateOrderResponse, ClientError>> {
+        // Futures are built up front: a closure in the stream type breaks `Send`
+        // inference for callers inside `tokio::spawn`
+        let calls: Vec<_> = reqs.iter().map(|req| self.create_order(req)).collect();
+        futures::stream::iter(calls)
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Cancel several orders by exchange id in a single request.
+    pub async fn cancel_orders_by_id(
+        &self,
+        account_id: u64,
+        order_ids: &[u64],
+    ) -> Result<Value, ClientError> {
+        let req = CancelOrdersByIdRequest {
+            account_id,
+            order_id_list: order_ids.iter().map(u64::to_string).collect(),
+        };
+        let path = "/api/v1/private/order/cancelOrderById";
+        let body =
+            serde_json::to_string(&req).map_err(|e| ClientError::JsonError(e.to_string()))?;
+        let body_val: Value = serde_json::to_value(&req).unwrap();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+
+        let sign_payload = Self::build_sign_content(&timestamp, "POST", path, &body_val);
+        let header_signature = self.signature_manager.sign_message(&sign_payload)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-edgeX-Api-Timestamp",
+            HeaderValue::from_str(&timestamp).unwrap(),
+        );
+        headers.insert(
+            "X-edgeX-Api-Signature",
+            HeaderValue::from_str(header_signature.trim_start_matches("0x")).unwrap(),
+        );
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let res = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .headers(headers)
+            .body(body)
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await?;
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+
+        let json: Value = res.json().await?;
+        if let Some(code) = json.get("code").and_then(|c| c.as_str())
+            && code != "SUCCESS"
+        {
+            return Err(ClientError::Rejected {
+                code: code.to_string(),
+                msg: json.get("msg").map(|m| m.to_string()).unwrap_or_default(),
+            });
+        }
+        Ok(json)
+    }
+
     pub async fn cancel_all_orders(
         &self,
         req: &crate::edgex_api::model::CancelAllOrderRequest,
@@ -189,7 +525,7 @@ impl EdgeXClient {
         let url = format!("{}/api/v1/private/order/cancelAllOrder", self.base_url);
 
         // EdgeX cancelAllOrder does not require l2_signature in the body, just the HTTP header signature.
-        let body = serde_json::to_string(req).map_err(|e| ClientError::ApiError(e.to_string()))?;
+        let body = serde_json::to_string(req).map_err(|e| ClientError::JsonError(e.to_string()))?;
         let body_val: Value = serde_json::to_value(req).unwrap();
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -224,10 +560,10 @@ impl EdgeXClient {
         let status = res.status();
         if !status.is_success() {
             let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
         }
 
         let json: Value = res.json().await?;
@@ -248,9 +584,8 @@ impl EdgeXClient {
             .to_string();
 
         let sign_payload = format!("{}GET{}{}", timestamp, path, query_str);
-        tracing::info!("GET Sign Payload: {}", sign_payload);
+        tracing::debug!("GET Sign Payload: {}", sign_payload);
         let header_signature = self.signature_manager.sign_message(&sign_payload)?;
-        tracing::info!("GET Signature: {}", header_signature);
 
         let mut headers = HeaderMap::new();
         headers.insert(
@@ -270,13 +605,13 @@ impl EdgeXClient {
             .send()
             .await?;
 
-        if !res.status().is_success() {
-            let status = res.status();
+        let status = res.status();
+        if !status.is_success() {
             let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
         }
 
         let json: Value = res.json().await?;
@@ -293,6 +628,103 @@ impl EdgeXClient {
         Ok(vec![])
     }
 
+    /// Predicted funding rate for a contract (public endpoint, no auth).
+    /// Uses `forecastFundingRate` when present, else the current `fundingRate`.
+    pub async fn get_funding_rate(
+        &self,
+        contract_id: u64,
+    ) -> Result<crate::types::FundingRate, ClientError> {
+        let url = format!(
+            "{}/api/v1/public/funding/getLatestFundingRate",
+            self.base_url
+        );
+        let res = self
+            .client
+            .get(&url)
+            .query(&[("contractId", contract_id.to_string())])
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await?;
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+
+        let json: Value = res.json().await?;
+        let info: crate::edgex_api::model::FundingRateInfo = json
+            .get("data")
+            .and_then(|d| d.as_array())
+            .and_then(|list| list.first())
+            .cloned()
+            .ok_or_else(|| ClientError::JsonError(format!("no funding data: {}", json)))
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| ClientError::JsonError(e.to_string()))
+            })?;
+
+        let rate_str = info
+            .forecast_funding_rate
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .unwrap_or(&info.funding_rate);
+        let parse_err = |e: std::num::ParseFloatError| ClientError::JsonError(e.to_string());
+        Ok(crate::types::FundingRate {
+            rate: rate_str.parse().map_err(parse_err)?,
+            next_funding_ms: info
+                .funding_time
+                .parse()
+                .map_err(|e: std::num::ParseIntError| ClientError::JsonError(e.to_string()))?,
+        })
+    }
+
+    /// Tick / lot size and leverage cap of a contract (public `getMetaData`).
+    pub async fn get_contract_spec(&self, contract_id: u64) -> Result<ContractSpec, ClientError> {
+        let url = format!("{}/api/v1/public/meta/getMetaData", self.base_url);
+        let res = self.client.get(&url).send().await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await?;
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+
+        let mut json: Value = res.json().await?;
+        parse_contract_spec(json["data"].take(), contract_id)
+    }
+
+    /// Account margin totals from `getAccountAsset` (first collateral asset,
+    /// USDC on EdgeX): total equity incl. unrealized PnL, used and free margin.
+    pub async fn get_account_margin(
+        &self,
+        account_id: u64,
+    ) -> Result<crate::types::MarginSummary, ClientError> {
+        let data = self
+            .signed_get(
+                "/api/v1/private/account/getAccountAsset",
+                &[("accountId", account_id.to_string())],
+            )
+            .await?;
+        Self::parse_account_margin(&data)
+    }
+
+    fn parse_account_margin(data: &Value) -> Result<crate::types::MarginSummary, ClientError> {
+        let asset = data
+            .get("collateralAssetModelList")
+            .and_then(|l| l.as_array())
+            .and_then(|l| l.first())
+            .cloned()
+            .ok_or_else(|| ClientError::JsonError(format!("no collateral asset: {}", data)))?;
+        let asset: crate::edgex_api::model::CollateralAsset =
+            serde_json::from_value(asset).map_err(|e| ClientError::JsonError(e.to_string()))?;
+        Ok(asset.summary())
+    }
+
     pub async fn get_balances(
         &self,
         account_id: u64,
@@ -327,20 +759,23 @@ impl EdgeXClient {
             .send()
             .await?;
 
-        if !res.status().is_success() {
-            let status = res.status();
+        let status = res.status();
+        if !status.is_success() {
             let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
         }
 
         let json: Value = res.json().await?;
         if let Some(code) = json.get("code")
             && code.as_str() != Some("SUCCESS")
         {
-            return Err(ClientError::ApiError(format!("EdgeX API error: {}", json)));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: json.to_string(),
+            });
         }
         if let Some(data) = json.get("data")
             && let Some(asset_list) = data.get("assetList")
@@ -395,10 +830,10 @@ impl EdgeXClient {
         let status = res.status();
         if !status.is_success() {
             let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: text,
+            });
         }
 
         // Response structure might be { "code": "...", "data": [...] }
@@ -407,14 +842,17 @@ impl EdgeXClient {
         if let Some(code) = json.get("code")
             && code.as_str() != Some("SUCCESS")
         {
-            return Err(ClientError::ApiError(format!("EdgeX API error: {}", json)));
+            return Err(ClientError::ApiError {
+                status: status.as_u16(),
+                body: json.to_string(),
+            });
         }
 
         if let Some(data) = json.get("data") {
             if let Some(list) = data.get("dataList") {
                 let orders: Vec<crate::edgex_api::model::OpenOrder> =
                     serde_json::from_value(list.clone())
-                        .map_err(|e| ClientError::ApiError(e.to_string()))?;
+                        .map_err(|e| ClientError::JsonError(e.to_string()))?;
                 return Ok(orders);
             }
             let orders: Vec<crate::edgex_api::model::OpenOrder> =
@@ -427,83 +865,34 @@ impl EdgeXClient {
         }
     }
 
+    /// One page of fill history (`page` from 1), newest first.
     pub async fn get_fills(
         &self,
         account_id: u64,
         page: u32,
-        size: u32,
+        page_size: u32,
     ) -> Result<Vec<crate::edgex_api::model::Fill>, ClientError> {
-        let url = format!(
-            "{}/api/v1/private/order/getHistoryOrderFillTransactionPage",
-            self.base_url
-        );
-        let params = [
-            ("accountId", account_id.to_string()),
-            ("page", page.to_string()),
-            ("size", size.to_string()),
-        ];
-
-        // Similar GET auth pattern
-        let path = "/api/v1/private/order/getHistoryOrderFillTransactionPage";
-        let query_str = format!("accountId={}&page={}&size={}", account_id, page, size);
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis()
-            .to_string();
-
-        let sign_payload = format!("{}GET{}{}", timestamp, path, query_str);
-        let header_signature = self.signature_manager.sign_message(&sign_payload)?;
-
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            "X-edgeX-Api-Timestamp",
-            HeaderValue::from_str(&timestamp).unwrap(),
-        );
-        headers.insert(
-            "X-edgeX-Api-Signature",
-            HeaderValue::from_str(header_signature.trim_start_matches("0x")).unwrap(),
-        );
-
-        let res = self
-            .client
-            .get(&url)
-            .headers(headers)
-            .query(&params)
-            .send()
+        let data = self
+            .signed_get(
+                "/api/v1/private/order/getHistoryOrderFillTransactionPage",
+                &Self::fills_params(account_id, page, page_size),
+            )
             .await?;
+        parse_fills(data)
+    }
 
-        let status = res.status();
-        if !status.is_success() {
-            let text = res.text().await?;
-            return Err(ClientError::ApiError(format!(
-                "Status: {}, Body: {}",
-                status, text
-            )));
-        }
-
-        let json: Value = res.json().await?;
-        if let Some(code) = json.get("code")
-            && code.as_str() != Some("SUCCESS")
-        {
-            return Err(ClientError::ApiError(format!("EdgeX API error: {}", json)));
-        }
-
-        if let Some(data) = json.get("data") {
-            let target = data.get("dataList").unwrap_or(data);
-            let fills: Vec<crate::edgex_api::model::Fill> = serde_json::from_value(target.clone())
-                .unwrap_or_else(|e| {
-                    tracing::error!("EDGEX RAW: {}", target);
-                    tracing::error!("EdgeX serde error: {}", e);
-                    vec![]
-                });
-            Ok(fills)
-        } else {
-            Err(ClientError::JsonError("Missing 'data' field in get_fills response".to_string()))
-        }
+    fn fills_params(account_id: u64, page: u32, page_size: u32) -> [(&'static str, String); 3] {
+        [
+            ("accountId", account_id.to_string()),
+            ("page", page.to_string()),
+            ("pageSize", page_size.to_string()),
+        ]
     }
 
-    pub async fn get_account_stats(&self, account_id: u64) -> Result<EdgeXAccountStats, ClientError> {
+    pub async fn get_account_stats(
+        &self,
+        account_id: u64,
+    ) -> Result<EdgeXAccountStats, ClientError> {
         let balances = self.get_balances(account_id).await?;
         let positions = self.get_positions(account_id).await?;
 
@@ -520,7 +909,7 @@ impl EdgeXClient {
             let size: f64 = pos.open_size.parse().unwrap_or(0.0);
             // EdgeX model currently lacks entry_price in Position struct
             // We use a dummy price of 0.0 for now to fix compilation
-            let px: f64 = 0.0; 
+            let px: f64 = 0.0;
             total_notional += size.abs() * px;
             main_pos += size;
         }
@@ -529,8 +918,248 @@ impl EdgeXClient {
             available_balance: portfolio_value, // Simplification
             portfolio_value,
             position: main_pos,
-            leverage: if portfolio_value > 0.0 { total_notional / portfolio_value } else { 0.0 },
-            margin_usage: if portfolio_value > 0.0 { (total_notional / portfolio_value) / 20.0 } else { 0.0 },
+            leverage: if portfolio_value > 0.0 {
+                total_notional / portfolio_value
+            } else {
+                0.0
+            },
+            margin_usage: if portfolio_value > 0.0 {
+                (total_notional / portfolio_value) / 20.0
+            } else {
+                0.0
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_create_signs_each_order_and_keeps_per_order_results() {
+        use crate::edgex_api::model::{OrderSide, OrderType, TimeInForce};
+        use crate::exchanges::mock_http::MockHttpServer;
+        let server = MockHttpServer::start(|req| {
+            let body = if req.body.contains("MM-2") {
+                r#"{"code":"ORDER_POST_ONLY_WOULD_TRADE","msg":"post only"}"#.to_string()
+            } else {
+                r#"{"code":"SUCCESS","data":{"orderId":"612345678901234567"}}"#.to_string()
+            };
+            (200, body)
         })
+        .await;
+        let client = EdgeXClient::new("0x1234567890abcdef", Some(server.base_url.clone())).unwrap();
+        let order = |client_order_id: &str| CreateOrderRequest {
+            price: "1999.50".to_string(),
+            size: "0.10".to_string(),
+            r#type: OrderType::Limit,
+            time_in_force: TimeInForce::PostOnly,
+            reduce_only: false,
+            account_id: 542_312,
+            contract_id: 10000002,
+            side: OrderSide::Buy,
+            client_order_id: client_order_id.to_string(),
+            expire_time: 1_700_000_000_000,
+            l2_nonce: 7,
+            l2_value: "199.9500".to_string(),
+            l2_size: "0.10".to_string(),
+            l2_limit_fee: "0.068".to_string(),
+            l2_expire_time: 1_700_864_000_000,
+            l2_signature: "00".to_string(),
+        };
+
+        let results = client
+            .create_orders_batch(&[order("MM-1"), order("MM-2")])
+            .await;
+        assert_eq!(
+            results[0].as_ref().unwrap().order_id,
+            612_345_678_901_234_567
+        );
+        assert!(results[1].as_ref().unwrap_err().is_post_only_rejection());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        for req in requests {
+            let body: Value = serde_json::from_str(&req.body).unwrap();
+            let ts = &req.headers["x-edgex-api-timestamp"];
+            let path = "/api/v1/private/order/createOrder";
+            let payload = EdgeXClient::build_sign_content(ts, "POST", path, &body);
+            let expected = client.signature_manager.sign_message(&payload).unwrap();
+            assert_eq!(req.headers["x-edgex-api-signature"], expected);
+        }
+    }
+
+    #[test]
+    fn test_api_error_display_is_single_line() {
+        let err = ClientError::ApiError {
+            status: 400,
+            body: "{\n  \"code\": \"INVALID_PARAM\",\n  \"msg\": \"bad price\"\n}".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            r#"API error: status=400 body={ "code": "INVALID_PARAM", "msg": "bad price" }"#
+        );
+        assert!(matches!(err, ClientError::ApiError { status: 400, .. }));
+    }
+
+    #[test]
+    fn test_get_order_by_client_order_id_sign_content() {
+        let params = EdgeXClient::client_order_id_params(542_312, "MM-3735928559");
+        assert_eq!(
+            EdgeXClient::build_get_sign_content(
+                "1700000000000",
+                "/api/v1/private/order/getOrderByClientOrderId",
+                &params
+            ),
+            "1700000000000GET/api/v1/private/order/getOrderByClientOrderId\
+             accountId=542312&clientOrderIdList=MM-3735928559"
+        );
+
+        // Keys are sorted regardless of the order they were passed in
+        let unsorted = [
+            ("size", "50".to_string()),
+            ("accountId", "1".to_string()),
+            ("page", "2".to_string()),
+        ];
+        assert_eq!(
+            EdgeXClient::build_get_sign_content("1", "/p", &unsorted),
+            "1GET/paccountId=1&page=2&size=50"
+        );
+    }
+
+    #[test]
+    fn test_fills_page_sign_content_and_parse() {
+        let params = EdgeXClient::fills_params(542_312, 2, 100);
+        assert_eq!(
+            EdgeXClient::build_get_sign_content(
+                "1700000000000",
+                "/api/v1/private/order/getHistoryOrderFillTransactionPage",
+                &params
+            ),
+            "1700000000000GET/api/v1/private/order/getHistoryOrderFillTransactionPage\
+             accountId=542312&page=2&pageSize=100"
+        );
+
+        let fills = parse_fills(serde_json::json!({"dataList": [{
+            "id": "1", "orderId": "612345678901234567", "contractId": "10000002",
+            "fillPrice": "1999.50", "fillSize": "0.10", "orderSide": "BUY",
+            "matchTime": "1700000000000", "fillFee": "0.068"
+        }]}))
+        .unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].fill_price, "1999.50");
+        assert_eq!(fills[0].match_time, "1700000000000");
+        assert!(parse_fills(serde_json::json!({"dataList": [{"id": "1"}]})).is_err());
+    }
+
+    #[test]
+    fn test_contract_spec_from_metadata() {
+        let data = serde_json::json!({
+            "coinList": [{"coinId": "1000", "coinName": "USDC"}],
+            "contractList": [
+                {"contractId": "10000001", "contractName": "BTCUSD", "quoteCoinId": "1000",
+                 "tickSize": "0.1", "stepSize": "0.001", "riskTierList": []},
+                {"contractId": "10000002", "contractName": "ETHUSD", "quoteCoinId": "1000",
+                 "tickSize": "0.01", "stepSize": "0.01", "minOrderSize": "0.01",
+                 "riskTierList": [{"tier": 1, "maxLeverage": "100"},
+                                  {"tier": 2, "maxLeverage": "50"}]}
+            ]
+        });
+        let spec = parse_contract_spec(data.clone(), 10000002).unwrap();
+        assert_eq!(
+            spec,
+            ContractSpec {
+                tick_size: 0.01,
+                lot_size: 0.01,
+                min_notional: 0.0,
+                max_leverage: 100,
+                settlement_asset: "USDC".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_contract_spec(data.clone(), 10000001)
+                .unwrap()
+                .max_leverage,
+            1
+        );
+        assert!(parse_contract_spec(data, 10000003).is_err());
+    }
+
+    #[test]
+    fn test_create_order_response_success_and_reject() {
+        let ok = parse_create_order_response(
+            serde_json::json!({"code": "SUCCESS", "data": {"orderId": "612345678901234567"}}),
+            "MM-7",
+        )
+        .unwrap();
+        assert_eq!(ok.order_id, 612_345_678_901_234_567);
+        assert_eq!(ok.client_order_id, "MM-7");
+        assert_eq!(ok.status, None);
+
+        // HTTP 200 with an error code must not look like success
+        let err = parse_create_order_response(
+            serde_json::json!({
+                "code": "INSUFFICIENT_MARGIN",
+                "msg": "insufficient margin",
+                "errorParam": {"need": "12.5"},
+                "data": null
+            }),
+            "MM-8",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::Rejected { ref code, ref msg }
+                if code == "INSUFFICIENT_MARGIN" && msg.contains("12.5")
+        ));
+        assert!(!err.is_unknown_outcome());
+        assert!(!err.is_post_only_rejection());
+
+        let crossed = ClientError::Rejected {
+            code: "ORDER_POST_ONLY_WOULD_TRADE".to_string(),
+            msg: String::new(),
+        };
+        assert!(crossed.is_post_only_rejection());
+    }
+
+    #[test]
+    fn test_account_margin_uses_collateral_totals() {
+        let data = serde_json::json!({
+            "collateralList": [{"coinId": "1000", "amount": "950.00"}],
+            "collateralAssetModelList": [{
+                "coinId": "1000",
+                "totalEquity": "1012.50",
+                "initialMarginRequirement": "150.00",
+                "orderFrozenAmount": "12.50",
+                "availableAmount": "850.00"
+            }]
+        });
+        let margin = EdgeXClient::parse_account_margin(&data).unwrap();
+        assert_eq!(margin.net_equity, 1012.5);
+        assert_eq!(margin.used_margin, 162.5);
+        assert_eq!(margin.available_margin, 850.0);
+        assert!(EdgeXClient::parse_account_margin(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_order_detail_parses_status_and_fills() {
+        let order: crate::edgex_api::model::OrderDetail = serde_json::from_str(
+            r#"{"id":"612345678901234567","clientOrderId":"MM-1","contractId":"10000002",
+                "side":"BUY","price":"1999.50","size":"0.10","cumFillSize":"0.04",
+                "cumFillValue":"79.96","status":"OPEN","type":"LIMIT"}"#,
+        )
+        .unwrap();
+        assert_eq!(order.id, 612_345_678_901_234_567);
+        assert_eq!(
+            (order.price, order.size, order.cum_fill_size),
+            (1999.5, 0.1, 0.04)
+        );
+        assert!((order.avg_fill_price() - 1999.0).abs() < 1e-9);
+        assert!(order.status.is_live());
+
+        let status: crate::edgex_api::model::OrderStatus =
+            serde_json::from_str(r#""SOMETHING_NEW""#).unwrap();
+        assert!(!status.is_live());
     }
 }
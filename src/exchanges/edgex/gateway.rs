@@ -2,22 +2,26 @@
 //!
 //! Wraps EdgeXClient to implement the unified Exchange trait with full L2 signature support.
 
-use super::client::EdgeXClient;
+use super::client::{ClientError, EdgeXClient};
 use super::model::{
     CancelAllOrderRequest, CancelOrderRequest, CreateOrderRequest, OrderSide,
     OrderType as EdgeXOrderType, TimeInForce,
 };
-use crate::error::{TradingError};
+use crate::error::TradingError;
 use crate::exchange::{
-    BatchAction, BatchOrderParams, BatchOrderResult, BatchResult, Exchange, OrderInfo, OrderParams,
-    OrderResult, OrderType, PlaceResult, Side,
+    BatchAction, BatchOrderParams, BatchOrderResult, BatchResult, CancelResult, Exchange,
+    OrderInfo, OrderParams, OrderResult, OrderType, PlaceResult, Side,
 };
 use anyhow::anyhow;
 use async_trait::async_trait;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+/// Status lookups after an IOC before giving up on a final state
+const IOC_STATUS_POLLS: usize = 5;
+const IOC_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// EdgeX Gateway configuration
 pub struct EdgeXConfig {
     pub account_id: u64,
@@ -127,6 +131,19 @@ impl EdgeXGateway {
         side: Side,
         size: f64,
         price: f64,
+    ) -> anyhow::Result<OrderResult> {
+        self.create_order_with(side, size, price, TimeInForce::PostOnly, false)
+            .await
+    }
+
+    /// Signed limit order with explicit time-in-force and reduce-only flag.
+    pub async fn create_order_with(
+        &self,
+        side: Side,
+        size: f64,
+        price: f64,
+        time_in_force: TimeInForce,
+        reduce_only: bool,
     ) -> anyhow::Result<OrderResult> {
         let is_buy = matches!(side, Side::Buy);
 
@@ -206,8 +223,8 @@ impl EdgeXGateway {
             price: format!("{:.2}", price), // Round to 2 decimals to avoid floating point issues
             size: format!("{:.4}", size),   // Round to 4 decimals
             r#type: EdgeXOrderType::Limit,
-            time_in_force: TimeInForce::PostOnly,
-            reduce_only: false, // Not a reduce-only order
+            time_in_force,
+            reduce_only,
             account_id: self.config.account_id,
             contract_id: self.config.contract_id,
             side: Self::side_to_edgex(side),
@@ -224,48 +241,17 @@ impl EdgeXGateway {
             l2_signature,
         };
 
-        // Submit order
-        let resp = self
-            .client
-            .create_order(&req)
-            .await
-            .map_err(|e| anyhow!("EdgeX create_order failed: {}", e))?;
-
-        // Debug: Log the full response
-        tracing::debug!(
-            "EdgeX API Response: {}",
-            serde_json::to_string_pretty(&resp).unwrap_or_else(|_| format!("{:?}", resp))
-        );
-
-        // EdgeX uses a wrapper format: {"code": "...", "data": {...}, "errorParam": {...}}
-        // Check for error code
-        if let Some(code) = resp.get("code").and_then(|v| v.as_str())
-            && code != "SUCCESS"
-            && code != "OK"
-        {
-            let error_msg = resp
-                .get("errorParam")
-                .and_then(|v| serde_json::to_string(v).ok())
-                .unwrap_or_else(|| code.to_string());
-            
-            if code == "INSUFFICIENT_MARGIN" || error_msg.contains("insufficient margin") {
-                return Err(TradingError::InsufficientMargin.into());
+        // Submit order (non-SUCCESS codes arrive as ClientError::Rejected)
+        let resp = self.client.create_order(&req).await.map_err(|e| match e {
+            ClientError::Rejected { ref code, ref msg }
+                if code == "INSUFFICIENT_MARGIN" || msg.contains("insufficient margin") =>
+            {
+                anyhow::Error::from(TradingError::InsufficientMargin)
             }
-            
-            return Err(anyhow!("EdgeX API error: {} - {}", code, error_msg));
-        }
-
-        // Extract order_id from data field
-        let order_id = resp
-            .get("data")
-            .and_then(|data| data.get("orderId"))
-            .and_then(|v| v.as_str())
-            .or_else(|| {
-                resp.get("data")
-                    .and_then(|data| data.get("order_id"))
-                    .and_then(|v| v.as_str())
-            })
-            .ok_or_else(|| anyhow!("Missing orderId in response data"))?;
+            e => anyhow!("EdgeX create_order failed: {}", e),
+        })?;
+        tracing::debug!("EdgeX API Response: {:?}", resp);
+        let order_id = resp.order_id;
 
         Ok(OrderResult {
             tx_hash: order_id.to_string(),
@@ -316,7 +302,31 @@ impl Exchange for EdgeXGateway {
         Ok(())
     }
 
+    /// Native `cancelOrderById` for the whole list in one request.
+    async fn cancel_orders(&self, order_ids: &[i64]) -> anyhow::Result<Vec<CancelResult>> {
+        if order_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<u64> = order_ids.iter().map(|&id| id as u64).collect();
+        let error = self
+            .client
+            .cancel_orders_by_id(self.config.account_id, &ids)
+            .await
+            .err()
+            .map(|e| e.to_string());
+        Ok(order_ids
+            .iter()
+            .map(|&order_id| CancelResult {
+                order_id,
+                error: error.clone(),
+            })
+            .collect())
+    }
+
     async fn cancel_all(&self) -> anyhow::Result<u32> {
+        // cancelAllOrder returns no count; what was open before the sweep is
+        // what it cancelled. The sweep still runs if the listing fails.
+        let open = self.get_active_orders().await.map_or(0, |o| o.len() as u32);
         let req = CancelAllOrderRequest {
             account_id: self.config.account_id,
             filter_contract_id_list: vec![self.config.contract_id],
@@ -326,7 +336,7 @@ impl Exchange for EdgeXGateway {
             .cancel_all_orders(&req)
             .await
             .map_err(|e| anyhow!("EdgeX cancel_all failed: {}", e))?;
-        Ok(0) // EdgeX doesn't return count
+        Ok(open)
     }
 
     async fn get_active_orders(&self) -> anyhow::Result<Vec<OrderInfo>> {
@@ -352,7 +362,8 @@ impl Exchange for EdgeXGateway {
     }
 
     async fn close_all_positions(&self, current_price: f64) -> anyhow::Result<()> {
-        let positions = self.client
+        let positions = self
+            .client
             .get_positions(self.config.account_id)
             .await
             .map_err(|e| anyhow!("EdgeX get_positions failed: {}", e))?;
@@ -368,9 +379,9 @@ impl Exchange for EdgeXGateway {
                 continue;
             }
 
-            // Close position with market order
+            // Close position; reduce-only so a stale size can never flip it
             let side = if size > 0.0 { Side::Sell } else { Side::Buy };
-            self.create_order_internal(side, size.abs(), current_price)
+            self.create_order_with(side, size.abs(), current_price, TimeInForce::PostOnly, true)
                 .await?;
         }
 
@@ -408,8 +419,13 @@ impl Exchange for EdgeXGateway {
         })
     }
 
-    async fn get_account_stats(&self) -> anyhow::Result<crate::strategy::inventory_neutral_mm::AccountStats> {
-        let stats = self.client.get_account_stats(self.config.account_id).await?;
+    async fn get_account_stats(
+        &self,
+    ) -> anyhow::Result<crate::strategy::inventory_neutral_mm::AccountStats> {
+        let stats = self
+            .client
+            .get_account_stats(self.config.account_id)
+            .await?;
         Ok(crate::strategy::inventory_neutral_mm::AccountStats {
             available_balance: stats.available_balance,
             portfolio_value: stats.portfolio_value,
@@ -423,4 +439,93 @@ impl Exchange for EdgeXGateway {
     fn limit_order_type(&self) -> OrderType {
         OrderType::PostOnly
     }
+
+    async fn get_contract_spec(&self) -> anyhow::Result<crate::types::ContractSpec> {
+        Ok(self
+            .client
+            .get_contract_spec(self.config.contract_id)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl crate::strategy::funding_arb::FundingVenue for EdgeXGateway {
+    fn name(&self) -> &str {
+        "edgex"
+    }
+
+    async fn funding_rate(&self) -> anyhow::Result<crate::types::FundingRate> {
+        Ok(self
+            .client
+            .get_funding_rate(self.config.contract_id)
+            .await?)
+    }
+
+    async fn equity(&self) -> anyhow::Result<f64> {
+        let margin = self
+            .client
+            .get_account_margin(self.config.account_id)
+            .await?;
+        Ok(margin.net_equity)
+    }
+
+    async fn take(
+        &self,
+        side: Side,
+        size: f64,
+        price: f64,
+        reduce_only: bool,
+    ) -> anyhow::Result<crate::strategy::funding_arb::TakeFill> {
+        let order = self
+            .create_order_with(
+                side,
+                size,
+                price,
+                TimeInForce::ImmediateOrCancel,
+                reduce_only,
+            )
+            .await?;
+        let order_id: u64 = order
+            .tx_hash
+            .parse()
+            .map_err(|_| anyhow!("EdgeX order id {:?} is not numeric", order.tx_hash))?;
+
+        // The create ack carries no fill info: poll until the IOC is done
+        let account_id = self.config.account_id;
+        let mut last = None;
+        for _ in 0..IOC_STATUS_POLLS {
+            tokio::time::sleep(IOC_STATUS_POLL_INTERVAL).await;
+            match self.client.get_order_by_id(account_id, order_id).await {
+                Ok(Some(detail)) if !detail.status.is_live() => {
+                    last = Some(detail);
+                    break;
+                }
+                Ok(detail) => last = detail.or(last),
+                Err(e) => tracing::warn!("⚠️ [EDGEX] IOC {} status lookup: {}", order_id, e),
+            }
+        }
+        // Without a final state the fill so far is not the outcome: never report it as one
+        let detail = match last {
+            Some(detail) if !detail.status.is_live() => detail,
+            Some(detail) => {
+                return Err(TradingError::UnknownOutcome(format!(
+                    "EdgeX IOC {} still {:?} after {} polls (filled {} so far)",
+                    order_id, detail.status, IOC_STATUS_POLLS, detail.cum_fill_size
+                ))
+                .into());
+            }
+            None => {
+                return Err(TradingError::UnknownOutcome(format!(
+                    "EdgeX IOC {} status could not be read",
+                    order_id
+                ))
+                .into());
+            }
+        };
+        Ok(crate::strategy::funding_arb::TakeFill {
+            order,
+            filled: detail.cum_fill_size,
+            avg_price: detail.avg_fill_price(),
+        })
+    }
 }
@@ -1,5 +1,6 @@
 pub mod client;
 pub mod gateway;
 pub mod model;
+pub mod order_builder;
 pub mod pedersen;
 pub mod signature;
@@ -58,6 +58,14 @@ pub struct CancelOrderRequest {
     pub contract_id: u64,
 }
 
+/// `cancelOrderById` with several orders in one request.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrdersByIdRequest {
+    pub account_id: u64,
+    pub order_id_list: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelAllOrderRequest {
@@ -74,6 +82,42 @@ pub struct OrderResponse {
     // Add other fields as discovered from API responses
 }
 
+/// Raw `createOrder` envelope: `{"code", "msg", "errorParam", "data": {"orderId", ...}}`.
+/// EdgeX answers HTTP 200 for rejects too, so `code` must be checked.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderEnvelope {
+    pub code: String,
+    #[serde(default)]
+    pub msg: Option<String>,
+    #[serde(default)]
+    pub error_param: Option<serde_json::Value>,
+    #[serde(default)]
+    pub data: Option<CreateOrderData>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderData {
+    #[serde(deserialize_with = "deserialize_string_or_number_to_u64")]
+    pub order_id: u64,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub status: Option<OrderStatus>,
+}
+
+/// Accepted `createOrder` result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateOrderResponse {
+    pub code: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+    /// Not always echoed by the API
+    pub status: Option<OrderStatus>,
+    pub msg: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenOrder {
@@ -88,6 +132,108 @@ pub struct OpenOrder {
     pub remaining_size: String,
 }
 
+/// Order as returned by `getOrderById` / `getOrderByClientOrderId`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderDetail {
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub id: u64,
+    pub client_order_id: String,
+    #[serde(deserialize_with = "deserialize_string_to_u64")]
+    pub contract_id: u64,
+    pub side: OrderSide,
+    #[serde(deserialize_with = "deserialize_string_to_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "deserialize_string_to_f64")]
+    pub size: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_f64")]
+    pub cum_fill_size: f64,
+    /// Quote notional of `cum_fill_size`
+    #[serde(default, deserialize_with = "deserialize_string_to_f64")]
+    pub cum_fill_value: f64,
+    pub status: OrderStatus,
+}
+
+impl OrderDetail {
+    /// Average fill price, 0 when nothing filled.
+    pub fn avg_fill_price(&self) -> f64 {
+        if self.cum_fill_size > 0.0 {
+            self.cum_fill_value / self.cum_fill_size
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderStatus {
+    Pending,
+    Open,
+    Filled,
+    Canceling,
+    Canceled,
+    Untriggered,
+    #[serde(other)]
+    Unknown,
+}
+
+impl OrderStatus {
+    /// Still able to trade (resting, accepted, or waiting on its trigger).
+    pub fn is_live(self) -> bool {
+        matches!(self, Self::Pending | Self::Open | Self::Untriggered)
+    }
+}
+
+/// Per-collateral totals from `getAccountAsset` (`collateralAssetModelList`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollateralAsset {
+    pub coin_id: String,
+    #[serde(deserialize_with = "deserialize_string_to_f64")]
+    pub total_equity: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_f64")]
+    pub initial_margin_requirement: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_f64")]
+    pub order_frozen_amount: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_f64")]
+    pub available_amount: f64,
+}
+
+impl CollateralAsset {
+    pub fn summary(&self) -> crate::types::MarginSummary {
+        crate::types::MarginSummary {
+            net_equity: self.total_equity,
+            used_margin: self.initial_margin_requirement + self.order_frozen_amount,
+            available_margin: self.available_amount,
+        }
+    }
+}
+
+fn deserialize_string_to_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    s.parse::<f64>().map_err(serde::de::Error::custom)
+}
+
+fn deserialize_string_or_number_to_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Num(u64),
+        Str(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Num(n) => Ok(n),
+        Raw::Str(s) => s.parse::<u64>().map_err(serde::de::Error::custom),
+    }
+}
+
 fn deserialize_string_to_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -116,6 +262,52 @@ pub struct Position {
     pub open_size: String,
 }
 
+/// `data` of the public `getMetaData` endpoint (fields we use).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaData {
+    pub coin_list: Vec<CoinMeta>,
+    pub contract_list: Vec<ContractMeta>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinMeta {
+    pub coin_id: String,
+    pub coin_name: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractMeta {
+    pub contract_id: String,
+    pub contract_name: String,
+    pub quote_coin_id: String,
+    pub tick_size: String,
+    pub step_size: String,
+    /// Ascending position-value tiers, each with its own leverage cap
+    #[serde(default)]
+    pub risk_tier_list: Vec<RiskTier>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskTier {
+    pub max_leverage: String,
+}
+
+/// Entry of the public `getLatestFundingRate` endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRateInfo {
+    pub contract_id: String,
+    pub funding_rate: String,
+    #[serde(default)]
+    pub forecast_funding_rate: Option<String>,
+    /// Settlement time (ms) of the current funding period
+    pub funding_time: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Balance {
@@ -0,0 +1,252 @@
+//! Signed `CreateOrderRequest` construction.
+//!
+//! An EdgeX limit order carries a StarkEx L2 signature over the quantized
+//! amounts (synthetic, collateral, fee), a nonce derived from the
+//! `clientOrderId`, the account and the expiry in hours. `EdgeXOrderBuilder`
+//! does that arithmetic and the signing in one place. `build` runs the
+//! Pedersen hash and ECDSA inline, so async callers put it on the blocking pool.
+
+use super::client::EdgeXClient;
+use super::model::{CreateOrderRequest, OrderSide, OrderType, TimeInForce};
+use super::signature::SignatureError;
+use crate::config::{format_price, format_size};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// StarkEx asset id of ETH, the synthetic of contract 10000002
+pub const ETH_SYNTHETIC_ASSET_ID: &str = "0x4554482d3900000000000000000000";
+/// USDC collateral (also the fee asset)
+pub const USDC_COLLATERAL_ASSET_ID: &str =
+    "0x2ce625e94458d39dd0bf3b45a843544dd4a14b8169045a3a3d15aa564b936c5";
+
+const SYNTHETIC_RESOLUTION: f64 = 1_000_000_000.0;
+const COLLATERAL_RESOLUTION: f64 = 1_000_000.0;
+const DEFAULT_FEE_RATE: f64 = 0.00034;
+/// `expireTime` sits this far before the signed `l2ExpireTime`
+const EXPIRE_TIME_MARGIN_MS: u64 = 864_000_000;
+
+#[derive(Error, Debug)]
+pub enum OrderBuildError {
+    #[error("no side set: call buy() or sell()")]
+    MissingSide,
+    #[error("invalid order: price {price}, size {size}")]
+    InvalidOrder { price: f64, size: f64 },
+    #[error("signing failed: {0}")]
+    Signature(#[from] SignatureError),
+}
+
+pub struct EdgeXOrderBuilder<'a> {
+    client: &'a EdgeXClient,
+    account_id: u64,
+    contract_id: u64,
+    synthetic_asset_id: &'a str,
+    /// (is_buy, price, size)
+    order: Option<(bool, f64, f64)>,
+    expire_in: Duration,
+    time_in_force: TimeInForce,
+    reduce_only: bool,
+    client_order_id: Option<String>,
+    tick_size: f64,
+    step_size: f64,
+    fee_rate: f64,
+}
+
+impl<'a> EdgeXOrderBuilder<'a> {
+    /// Good-til-cancel limit order on the ETH synthetic, expiring in 30 days.
+    pub fn new(client: &'a EdgeXClient, account_id: u64, contract_id: u64) -> Self {
+        Self {
+            client,
+            account_id,
+            contract_id,
+            synthetic_asset_id: ETH_SYNTHETIC_ASSET_ID,
+            order: None,
+            expire_in: Duration::from_secs(30 * 24 * 60 * 60),
+            time_in_force: TimeInForce::GoodTilCancel,
+            reduce_only: false,
+            client_order_id: None,
+            tick_size: 0.01,
+            step_size: 0.01,
+            fee_rate: DEFAULT_FEE_RATE,
+        }
+    }
+
+    /// `price` / `size_eth` are signed as given: put them on the grid first
+    /// (`order_builder::OrderBuilder`).
+    pub fn buy(mut self, price: f64, size_eth: f64) -> Self {
+        self.order = Some((true, price, size_eth));
+        self
+    }
+
+    pub fn sell(mut self, price: f64, size_eth: f64) -> Self {
+        self.order = Some((false, price, size_eth));
+        self
+    }
+
+    pub fn expire_in(mut self, expire_in: Duration) -> Self {
+        self.expire_in = expire_in;
+        self
+    }
+
+    pub fn post_only(mut self) -> Self {
+        self.time_in_force = TimeInForce::PostOnly;
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Defaults to a random UUID; the L2 nonce is derived from it.
+    pub fn client_order_id(mut self, id: String) -> Self {
+        self.client_order_id = Some(id);
+        self
+    }
+
+    /// Tick / step used to format `price` and `size` (default 0.01 / 0.01).
+    pub fn increments(mut self, tick_size: f64, step_size: f64) -> Self {
+        self.tick_size = tick_size;
+        self.step_size = step_size;
+        self
+    }
+
+    /// Synthetic asset of a contract other than ETH.
+    pub fn synthetic_asset_id(mut self, id: &'a str) -> Self {
+        self.synthetic_asset_id = id;
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: f64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    pub fn build(self) -> Result<CreateOrderRequest, OrderBuildError> {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.build_at(now_ms)
+    }
+
+    fn build_at(self, now_ms: u64) -> Result<CreateOrderRequest, OrderBuildError> {
+        let (is_buy, price, size) = self.order.ok_or(OrderBuildError::MissingSide)?;
+        if !(price > 0.0 && size > 0.0 && price.is_finite() && size.is_finite()) {
+            return Err(OrderBuildError::InvalidOrder { price, size });
+        }
+
+        // Quantized amounts exactly as the L2 hash expects them
+        let value = price * size;
+        let amount_synthetic = (size * SYNTHETIC_RESOLUTION).round() as u64;
+        let amount_collateral = (value * COLLATERAL_RESOLUTION).round() as u64;
+        let amount_fee = (value * self.fee_rate * COLLATERAL_RESOLUTION).ceil() as u64;
+
+        let client_order_id = self
+            .client_order_id
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let l2_nonce = l2_nonce(&client_order_id);
+        let l2_expire_time = now_ms + self.expire_in.as_millis() as u64;
+        let expire_time_hours = l2_expire_time / (60 * 60 * 1000);
+
+        let signer = &self.client.signature_manager;
+        let hash = signer.calc_limit_order_hash(
+            self.synthetic_asset_id,
+            USDC_COLLATERAL_ASSET_ID,
+            USDC_COLLATERAL_ASSET_ID,
+            is_buy,
+            amount_synthetic,
+            amount_collateral,
+            amount_fee,
+            l2_nonce,
+            self.account_id,
+            expire_time_hours,
+        )?;
+        let l2_signature = signer.sign_l2_action(hash)?;
+
+        Ok(CreateOrderRequest {
+            price: format_price(price, self.tick_size),
+            size: format_size(size, self.step_size),
+            r#type: OrderType::Limit,
+            time_in_force: self.time_in_force,
+            reduce_only: self.reduce_only,
+            account_id: self.account_id,
+            contract_id: self.contract_id,
+            side: if is_buy {
+                OrderSide::Buy
+            } else {
+                OrderSide::Sell
+            },
+            client_order_id,
+            expire_time: l2_expire_time.saturating_sub(EXPIRE_TIME_MARGIN_MS),
+            l2_nonce,
+            l2_value: format!("{:.4}", value),
+            l2_size: format_size(size, self.step_size),
+            l2_limit_fee: format!("{:.6}", amount_fee as f64 / COLLATERAL_RESOLUTION),
+            l2_expire_time,
+            l2_signature,
+        })
+    }
+}
+
+/// `l2Nonce = hexToLong(sha256(clientOrderId)[0..8])`
+fn l2_nonce(client_order_id: &str) -> u64 {
+    let digest = Sha256::digest(client_order_id.as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_matches_manual_signing() {
+        let client = EdgeXClient::new("0x1234567890abcdef", None).unwrap();
+        let now_ms = 1_700_000_000_000;
+        let req = EdgeXOrderBuilder::new(&client, 42, 10000002)
+            .sell(2000.01, 0.29)
+            .post_only()
+            .client_order_id("MM-7".to_string())
+            .expire_in(Duration::from_secs(30 * 24 * 60 * 60))
+            .build_at(now_ms)
+            .unwrap();
+
+        let l2_expire_time = now_ms + 2_592_000_000;
+        let digest = Sha256::digest(b"MM-7");
+        let nonce = u64::from_str_radix(&hex::encode(digest)[..8], 16).unwrap();
+        assert_eq!(req.l2_nonce, nonce);
+        assert_eq!((req.price.as_str(), req.size.as_str()), ("2000.01", "0.29"));
+        assert_eq!(req.l2_value, "580.0029");
+        // ceil(580.0029 × 0.00034 × 1e6) / 1e6
+        assert_eq!(req.l2_limit_fee, "0.197201");
+        assert_eq!(req.l2_expire_time, l2_expire_time);
+        assert_eq!(req.expire_time, l2_expire_time - 864_000_000);
+        assert!(matches!(req.side, OrderSide::Sell));
+        assert!(matches!(req.time_in_force, TimeInForce::PostOnly));
+
+        // Same signature as hashing the quantized amounts by hand
+        let signer = &client.signature_manager;
+        let hash = signer
+            .calc_limit_order_hash(
+                ETH_SYNTHETIC_ASSET_ID,
+                USDC_COLLATERAL_ASSET_ID,
+                USDC_COLLATERAL_ASSET_ID,
+                false,
+                290_000_000,
+                580_002_900,
+                197_201,
+                nonce,
+                42,
+                l2_expire_time / 3_600_000,
+            )
+            .unwrap();
+        assert_eq!(req.l2_signature, signer.sign_l2_action(hash).unwrap());
+
+        let missing = EdgeXOrderBuilder::new(&client, 42, 10000002).build();
+        assert!(matches!(missing, Err(OrderBuildError::MissingSide)));
+        let zero = EdgeXOrderBuilder::new(&client, 42, 10000002)
+            .buy(2000.0, 0.0)
+            .build();
+        assert!(matches!(zero, Err(OrderBuildError::InvalidOrder { .. })));
+    }
+}
@@ -0,0 +1,455 @@
+//! In-memory `Exchange` test double.
+//!
+//! Records every call, lets tests script how the next order placement behaves
+//! (rest, immediate fill, partial fill after N polls, reject, transport error),
+//! and keeps a quote balance + base position that react to simulated fills.
+//!
+//! Compiled for unit tests and, for downstream crates, behind the `testing` feature.
+
+use crate::error::TradingError;
+use crate::exchange::{
+    BatchAction, BatchOrderParams, BatchOrderResult, BatchResult, Exchange, OrderInfo, OrderResult,
+    OrderType, PlaceResult, Side,
+};
+use crate::strategy::inventory_neutral_mm::AccountStats;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::RangeInclusive;
+
+/// A call observed by the mock, in invocation order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCall {
+    Place { side: Side, size: f64, price: f64 },
+    CancelOrder(i64),
+    CancelAll,
+    GetActiveOrders,
+    ClosePositions { price: f64 },
+    GetAccountStats,
+}
+
+/// Scripted behaviour for an order placement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockResponse {
+    /// Accept and rest on the book until canceled.
+    Rest,
+    /// Accept and fill completely at the limit price.
+    Fill,
+    /// Accept, then fill `fraction` of the size once `after_polls` calls to
+    /// `get_active_orders` have been made.
+    PartialFill { fraction: f64, after_polls: u32 },
+    /// Exchange-level rejection (`TradingError::OrderFailed`).
+    Reject(String),
+    /// Transport/API failure.
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+struct PendingFill {
+    fraction: f64,
+    polls_left: u32,
+}
+
+#[derive(Debug)]
+struct MockState {
+    calls: Vec<MockCall>,
+    script: VecDeque<MockResponse>,
+    default_response: MockResponse,
+    orders: BTreeMap<i64, (OrderInfo, Option<PendingFill>)>,
+    next_order_index: i64,
+    query_error: Option<String>,
+    balance: f64,
+    position: f64,
+}
+
+pub struct MockExchange {
+    state: Mutex<MockState>,
+    order_type: OrderType,
+}
+
+impl Default for MockExchange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockExchange {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                calls: Vec::new(),
+                script: VecDeque::new(),
+                default_response: MockResponse::Rest,
+                orders: BTreeMap::new(),
+                next_order_index: 1,
+                query_error: None,
+                balance: 0.0,
+                position: 0.0,
+            }),
+            order_type: OrderType::PostOnly,
+        }
+    }
+
+    /// Starting quote-currency balance.
+    pub fn with_balance(self, balance: f64) -> Self {
+        self.state.lock().balance = balance;
+        self
+    }
+
+    /// Behaviour used once the scripted queue is exhausted.
+    pub fn with_default_response(self, response: MockResponse) -> Self {
+        self.state.lock().default_response = response;
+        self
+    }
+
+    /// Queue the behaviour for the next order placement.
+    pub fn push_response(&self, response: MockResponse) {
+        self.state.lock().script.push_back(response);
+    }
+
+    /// Make `get_active_orders` / `get_account_stats` fail (or succeed again with `None`).
+    pub fn set_query_error(&self, error: Option<&str>) {
+        self.state.lock().query_error = error.map(str::to_string);
+    }
+
+    /// Insert an order directly into the book, bypassing the call log.
+    pub fn seed_open_order(&self, order: OrderInfo) {
+        let mut state = self.state.lock();
+        state.next_order_index = state.next_order_index.max(order.client_order_index + 1);
+        state.orders.insert(order.client_order_index, (order, None));
+    }
+
+    /// Simulate an out-of-band fill of a resting order (e.g. a taker hit us).
+    pub fn fill_order(&self, client_order_index: i64, size: f64) {
+        let mut state = self.state.lock();
+        state.fill(client_order_index, size);
+    }
+
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.lock().calls.clone()
+    }
+
+    /// Panic unless some placement on `side` had its price and size inside
+    /// the given ranges.
+    #[track_caller]
+    pub fn assert_order_placed(
+        &self,
+        side: Side,
+        price: RangeInclusive<f64>,
+        size: RangeInclusive<f64>,
+    ) {
+        let calls = self.calls();
+        let placed = calls.iter().any(|call| {
+            matches!(call, MockCall::Place { side: s, size: q, price: p }
+                if *s == side && price.contains(p) && size.contains(q))
+        });
+        assert!(
+            placed,
+            "no {:?} order with price in {:?} and size in {:?}; calls: {:?}",
+            side, price, size, calls
+        );
+    }
+
+    pub fn open_orders(&self) -> Vec<OrderInfo> {
+        self.state
+            .lock()
+            .orders
+            .values()
+            .map(|(o, _)| o.clone())
+            .collect()
+    }
+
+    pub fn balance(&self) -> f64 {
+        self.state.lock().balance
+    }
+
+    pub fn position(&self) -> f64 {
+        self.state.lock().position
+    }
+
+    fn place(&self, side: Side, size: f64, price: f64) -> anyhow::Result<OrderResult> {
+        let mut state = self.state.lock();
+        state.calls.push(MockCall::Place { side, size, price });
+
+        let response = state
+            .script
+            .pop_front()
+            .unwrap_or_else(|| state.default_response.clone());
+
+        let pending = match response {
+            MockResponse::Reject(reason) => return Err(TradingError::OrderFailed(reason).into()),
+            MockResponse::Error(msg) => return Err(anyhow::anyhow!(msg)),
+            MockResponse::Rest | MockResponse::Fill => None,
+            MockResponse::PartialFill {
+                fraction,
+                after_polls,
+            } => Some(PendingFill {
+                fraction,
+                polls_left: after_polls,
+            }),
+        };
+
+        let index = state.next_order_index;
+        state.next_order_index += 1;
+        state.orders.insert(
+            index,
+            (
+                OrderInfo {
+                    order_id: index.to_string(),
+                    client_order_index: index,
+                    side,
+                    price,
+                    size,
+                    filled: 0.0,
+                },
+                pending,
+            ),
+        );
+        if response == MockResponse::Fill {
+            state.fill(index, size);
+        }
+
+        Ok(OrderResult {
+            tx_hash: format!("mock-{}", index),
+            client_order_index: index,
+        })
+    }
+}
+
+impl MockState {
+    fn fill(&mut self, index: i64, size: f64) {
+        let Some((order, _)) = self.orders.get_mut(&index) else {
+            return;
+        };
+        let qty = size.min(order.size - order.filled).max(0.0);
+        order.filled += qty;
+        let signed = match order.side {
+            Side::Buy => qty,
+            Side::Sell => -qty,
+        };
+        let price = order.price;
+        if order.size - order.filled <= 1e-12 {
+            self.orders.remove(&index);
+        }
+        self.position += signed;
+        self.balance -= signed * price;
+    }
+
+    fn advance_polls(&mut self) {
+        let mut due = Vec::new();
+        for (index, (order, pending)) in self.orders.iter_mut() {
+            if let Some(p) = pending {
+                if p.polls_left == 0 {
+                    due.push((*index, order.size * p.fraction));
+                    *pending = None;
+                } else {
+                    p.polls_left -= 1;
+                }
+            }
+        }
+        for (index, qty) in due {
+            self.fill(index, qty);
+        }
+    }
+}
+
+#[async_trait]
+impl Exchange for MockExchange {
+    async fn buy(&self, size: f64, price: f64) -> anyhow::Result<OrderResult> {
+        self.place(Side::Buy, size, price)
+    }
+
+    async fn sell(&self, size: f64, price: f64) -> anyhow::Result<OrderResult> {
+        self.place(Side::Sell, size, price)
+    }
+
+    async fn place_batch(&self, params: BatchOrderParams) -> anyhow::Result<BatchOrderResult> {
+        let bid = self.place(Side::Buy, params.bid_size, params.bid_price)?;
+        let ask = self.place(Side::Sell, params.ask_size, params.ask_price)?;
+        Ok(BatchOrderResult {
+            tx_hashes: vec![bid.tx_hash, ask.tx_hash],
+            bid_client_order_index: bid.client_order_index,
+            ask_client_order_index: ask.client_order_index,
+        })
+    }
+
+    async fn cancel_order(&self, order_id: i64) -> anyhow::Result<()> {
+        let mut state = self.state.lock();
+        state.calls.push(MockCall::CancelOrder(order_id));
+        match state.orders.remove(&order_id) {
+            Some(_) => Ok(()),
+            None => Err(TradingError::OrderFailed(format!("order {} not found", order_id)).into()),
+        }
+    }
+
+    async fn cancel_all(&self) -> anyhow::Result<u32> {
+        let mut state = self.state.lock();
+        state.calls.push(MockCall::CancelAll);
+        let count = state.orders.len() as u32;
+        state.orders.clear();
+        Ok(count)
+    }
+
+    async fn get_active_orders(&self) -> anyhow::Result<Vec<OrderInfo>> {
+        let mut state = self.state.lock();
+        state.calls.push(MockCall::GetActiveOrders);
+        if let Some(err) = &state.query_error {
+            return Err(anyhow::anyhow!(err.clone()));
+        }
+        state.advance_polls();
+        Ok(state.orders.values().map(|(o, _)| o.clone()).collect())
+    }
+
+    async fn close_all_positions(&self, current_price: f64) -> anyhow::Result<()> {
+        let mut state = self.state.lock();
+        state.calls.push(MockCall::ClosePositions {
+            price: current_price,
+        });
+        let position = state.position;
+        state.balance += position * current_price;
+        state.position = 0.0;
+        Ok(())
+    }
+
+    async fn execute_batch(&self, actions: Vec<BatchAction>) -> anyhow::Result<BatchResult> {
+        let mut tx_hashes = Vec::new();
+        let mut place_results = Vec::new();
+        for action in actions {
+            match action {
+                BatchAction::Cancel(id) => self.cancel_order(id).await?,
+                BatchAction::Place(params) => {
+                    let res = self.place(params.side, params.size, params.price)?;
+                    tx_hashes.push(res.tx_hash);
+                    place_results.push(PlaceResult {
+                        client_order_index: res.client_order_index,
+                        side: params.side,
+                        price: params.price,
+                        size: params.size,
+                    });
+                }
+            }
+        }
+        Ok(BatchResult {
+            tx_hashes,
+            place_results,
+        })
+    }
+
+    async fn get_account_stats(&self) -> anyhow::Result<AccountStats> {
+        let mut state = self.state.lock();
+        state.calls.push(MockCall::GetAccountStats);
+        if let Some(err) = &state.query_error {
+            return Err(anyhow::anyhow!(err.clone()));
+        }
+        Ok(AccountStats {
+            available_balance: state.balance,
+            portfolio_value: state.balance,
+            position: state.position,
+            ..AccountStats::default()
+        })
+    }
+
+    fn limit_order_type(&self) -> OrderType {
+        self.order_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scripted_responses_drive_fills_and_balances() {
+        let mock = MockExchange::new().with_balance(1_000.0);
+        mock.push_response(MockResponse::Fill);
+        mock.push_response(MockResponse::Reject("post-only would cross".into()));
+        mock.push_response(MockResponse::Error("timeout".into()));
+
+        let filled = mock.buy(0.1, 2000.0).await.unwrap();
+        assert!(mock.open_orders().is_empty());
+        assert!((mock.position() - 0.1).abs() < 1e-12);
+        assert!((mock.balance() - 800.0).abs() < 1e-9);
+        assert_eq!(filled.client_order_index, 1);
+
+        let rejected = mock.sell(0.1, 2001.0).await.unwrap_err();
+        assert!(rejected.downcast_ref::<TradingError>().is_some());
+        let errored = mock.sell(0.1, 2001.0).await.unwrap_err();
+        assert_eq!(errored.to_string(), "timeout");
+
+        // Script exhausted -> default Rest
+        mock.sell(0.1, 2002.0).await.unwrap();
+        assert_eq!(mock.open_orders().len(), 1);
+        assert_eq!(mock.calls().len(), 4);
+        mock.assert_order_placed(Side::Buy, 1999.0..=2000.0, 0.1..=0.1);
+        mock.assert_order_placed(Side::Sell, 2002.0..=2002.0, 0.05..=0.2);
+        let missing = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            mock.assert_order_placed(Side::Buy, 2001.0..=2100.0, 0.0..=1.0)
+        }));
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_after_polls() {
+        let mock = MockExchange::new();
+        mock.push_response(MockResponse::PartialFill {
+            fraction: 0.5,
+            after_polls: 1,
+        });
+        let res = mock.sell(0.2, 2000.0).await.unwrap();
+
+        let first = mock.get_active_orders().await.unwrap();
+        assert_eq!(first[0].filled, 0.0);
+        let second = mock.get_active_orders().await.unwrap();
+        assert!((second[0].filled - 0.1).abs() < 1e-12);
+        assert!((mock.position() + 0.1).abs() < 1e-12);
+
+        mock.cancel_order(res.client_order_index).await.unwrap();
+        assert!(mock.cancel_order(res.client_order_index).await.is_err());
+        assert!(mock.get_active_orders().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_default_cancel_orders_reports_each_order() {
+        let mock = MockExchange::new();
+        let a = mock.buy(0.1, 2000.0).await.unwrap().client_order_index;
+        let b = mock.sell(0.1, 2010.0).await.unwrap().client_order_index;
+
+        let results = mock.cancel_orders(&[a, 999, b]).await.unwrap();
+        let failed: Vec<i64> = results
+            .iter()
+            .filter(|r| r.error.is_some())
+            .map(|r| r.order_id)
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(failed, vec![999]);
+        assert!(mock.open_orders().is_empty());
+        assert_eq!(mock.cancel_all().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_default_amend_falls_back_to_cancel_and_place() {
+        let mock = MockExchange::new();
+        let placed = mock.buy(0.1, 2000.0).await.unwrap();
+
+        let amended = mock
+            .amend_order(placed.client_order_index, Side::Buy, 2001.0, 0.2)
+            .await
+            .unwrap();
+
+        assert_ne!(amended.client_order_index, placed.client_order_index);
+        let open = mock.open_orders();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].price, 2001.0);
+        assert_eq!(
+            mock.calls()[1..],
+            [
+                MockCall::CancelOrder(placed.client_order_index),
+                MockCall::Place {
+                    side: Side::Buy,
+                    size: 0.2,
+                    price: 2001.0
+                },
+            ]
+        );
+    }
+}
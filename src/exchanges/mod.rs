@@ -1,3 +1,8 @@
 pub mod backpack;
 pub mod edgex;
 pub mod lighter;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
+#[cfg(test)]
+pub(crate) mod mock_http;
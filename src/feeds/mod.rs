@@ -0,0 +1,5 @@
+//! Derived market-data feeds built on top of raw ticker/BBO streams.
+
+pub mod ohlcv;
+
+pub use ohlcv::{Candle, OHLCVAggregator};
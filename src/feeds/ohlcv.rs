@@ -0,0 +1,203 @@
+//! OHLCV candle aggregation from ticker events.
+//!
+//! Buckets `Ticker` updates into fixed, wall-clock aligned intervals and emits
+//! each completed `Candle` on a flume channel as soon as the first ticker of
+//! the next interval arrives.
+
+use crate::types::{Symbol, Ticker};
+use flume::{Receiver, Sender, unbounded};
+use rust_decimal::Decimal;
+use std::time::Duration;
+
+/// Completed OHLCV candle. Timestamps are milliseconds, `timestamp_end` is exclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub symbol: Symbol,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub timestamp_start: u64,
+    pub timestamp_end: u64,
+}
+
+impl Candle {
+    fn open_at(ticker: &Ticker, start: u64, interval_ms: u64) -> Self {
+        Self {
+            symbol: ticker.symbol.clone(),
+            open: ticker.last,
+            high: ticker.last,
+            low: ticker.last,
+            close: ticker.last,
+            volume: Decimal::ZERO,
+            timestamp_start: start,
+            timestamp_end: start + interval_ms,
+        }
+    }
+
+    fn update(&mut self, price: Decimal) {
+        if price > self.high {
+            self.high = price;
+        }
+        if price < self.low {
+            self.low = price;
+        }
+        self.close = price;
+    }
+}
+
+/// Aggregates tickers into fixed-interval candles.
+///
+/// Volume is derived from the change in the ticker's rolling `volume_24h`
+/// between consecutive updates; decreases (the 24h window rolling off old
+/// trades) are ignored rather than producing negative volume.
+pub struct OHLCVAggregator {
+    interval_ms: u64,
+    current: Option<Candle>,
+    last_volume_24h: Option<Decimal>,
+    tx: Sender<Candle>,
+    rx: Receiver<Candle>,
+}
+
+impl OHLCVAggregator {
+    pub fn new(interval: Duration) -> Self {
+        let (tx, rx) = unbounded();
+        Self {
+            interval_ms: (interval.as_millis() as u64).max(1),
+            current: None,
+            last_volume_24h: None,
+            tx,
+            rx,
+        }
+    }
+
+    /// Receiver for completed candles. May be cloned for multiple consumers.
+    pub fn receiver(&self) -> Receiver<Candle> {
+        self.rx.clone()
+    }
+
+    /// In-progress candle for the current interval, if any.
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    /// Feed a ticker. Out-of-order tickers older than the open candle are dropped.
+    pub fn on_ticker(&mut self, ticker: &Ticker) {
+        let bucket_start = ticker.timestamp - ticker.timestamp % self.interval_ms;
+
+        let volume_delta = match self.last_volume_24h {
+            Some(prev) if ticker.volume_24h > prev => ticker.volume_24h - prev,
+            _ => Decimal::ZERO,
+        };
+
+        match self.current.as_mut() {
+            // Dropped: its `volume_24h` must not become the baseline either
+            Some(candle) if bucket_start < candle.timestamp_start => return,
+            Some(candle) if bucket_start == candle.timestamp_start => {
+                candle.update(ticker.last);
+                candle.volume += volume_delta;
+            }
+            _ => {
+                // Volume traded before this ticker belongs to the previous interval
+                if let Some(mut done) = self.current.take() {
+                    done.volume += volume_delta;
+                    let _ = self.tx.send(done);
+                }
+                self.current = Some(Candle::open_at(ticker, bucket_start, self.interval_ms));
+            }
+        }
+        self.last_volume_24h = Some(ticker.volume_24h);
+    }
+
+    /// Emit the in-progress candle immediately (e.g. on shutdown).
+    pub fn flush(&mut self) {
+        if let Some(done) = self.current.take() {
+            let _ = self.tx.send(done);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn ticker(ts: u64, last: f64, volume_24h: f64) -> Ticker {
+        let last = Decimal::from_f64(last).unwrap();
+        Ticker {
+            symbol: Symbol::new("ETH"),
+            bid: last,
+            ask: last,
+            last,
+            volume_24h: Decimal::from_f64(volume_24h).unwrap(),
+            timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn test_aggregates_three_intervals_from_100_tickers() {
+        let mut agg = OHLCVAggregator::new(Duration::from_secs(1));
+        let rx = agg.receiver();
+
+        // 100 tickers, 30ms apart, starting at t=10_000ms → spans [10s, 13s)
+        for i in 0..100u64 {
+            let price = 2000.0 + (i % 7) as f64 - (i / 50) as f64 * 10.0;
+            agg.on_ticker(&ticker(10_000 + i * 30, price, 100.0 + i as f64));
+        }
+        agg.flush();
+
+        let candles: Vec<Candle> = rx.try_iter().collect();
+        assert_eq!(candles.len(), 3);
+
+        // Interval 0: i = 0..=33 (ts 10_000..=10_990)
+        let c0 = &candles[0];
+        assert_eq!(c0.timestamp_start, 10_000);
+        assert_eq!(c0.timestamp_end, 11_000);
+        assert_eq!(c0.open, Decimal::from(2000));
+        assert_eq!(c0.high, Decimal::from(2006));
+        assert_eq!(c0.low, Decimal::from(2000));
+        assert_eq!(c0.close, Decimal::from(2000 + 33 % 7));
+        assert_eq!(c0.volume, Decimal::from(34));
+
+        // Interval 1: i = 34..=66, price drops by 10 from i = 50
+        let c1 = &candles[1];
+        assert_eq!(c1.timestamp_start, 11_000);
+        assert_eq!(c1.open, Decimal::from(2000 + 34 % 7));
+        assert_eq!(c1.high, Decimal::from(2006));
+        assert_eq!(c1.low, Decimal::from(1990));
+        assert_eq!(c1.close, Decimal::from(1990 + 66 % 7));
+        assert_eq!(c1.volume, Decimal::from(33));
+
+        // Interval 2: i = 67..=99 (flushed partial)
+        let c2 = &candles[2];
+        assert_eq!(c2.timestamp_start, 12_000);
+        assert_eq!(c2.open, Decimal::from(1990 + 67 % 7));
+        assert_eq!(c2.close, Decimal::from(1990 + 99 % 7));
+        assert_eq!(c2.volume, Decimal::from(32));
+    }
+
+    #[test]
+    fn test_drops_stale_ticker_and_ignores_volume_rolloff() {
+        let mut agg = OHLCVAggregator::new(Duration::from_secs(1));
+        agg.on_ticker(&ticker(5_000, 100.0, 50.0));
+        agg.on_ticker(&ticker(4_500, 1.0, 60.0));
+        agg.on_ticker(&ticker(5_100, 101.0, 40.0));
+
+        let c = agg.current().unwrap();
+        assert_eq!(c.low, Decimal::from(100));
+        assert_eq!(c.close, Decimal::from(101));
+        assert_eq!(c.volume, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_stale_ticker_does_not_reset_volume_baseline() {
+        let mut agg = OHLCVAggregator::new(Duration::from_secs(1));
+        agg.on_ticker(&ticker(5_000, 100.0, 50.0));
+        agg.on_ticker(&ticker(4_500, 99.0, 40.0));
+        agg.on_ticker(&ticker(5_100, 101.0, 55.0));
+
+        // 55 − 50, not 55 − 40
+        assert_eq!(agg.current().unwrap().volume, Decimal::from(5));
+    }
+}
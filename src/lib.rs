@@ -1,10 +1,18 @@
 pub mod account_stats_reader;
+pub mod clock_sync;
 pub mod config;
+pub mod credentials;
 pub mod data_plane;
 pub mod error;
 pub mod exchange;
+pub mod exchange_state;
 pub mod exchanges;
+pub mod feeds;
+pub mod order_builder;
 pub mod order_tracker;
+pub mod orderbook;
+pub mod replay;
+pub mod secret;
 pub mod shadow_ledger;
 pub mod shm_depth_reader;
 pub mod shm_event_reader;
@@ -1,14 +1,34 @@
+use aleph_tx::clock_sync::ClockSync;
 use aleph_tx::config::{AppConfig, EXCH_BACKPACK, EXCH_EDGEX, SYM_ETH};
+use aleph_tx::credentials::Credentials;
 use aleph_tx::data_plane;
+use aleph_tx::exchange_state::account_caches;
+use aleph_tx::exchanges::backpack::gateway::BackpackGateway;
+use aleph_tx::exchanges::edgex::gateway::{EdgeXConfig, EdgeXGateway};
+use aleph_tx::replay::spawn_capture_thread;
 use aleph_tx::strategy::{
-    Strategy, arbitrage::ArbitrageEngine, backpack_mm::BackpackMMStrategy,
-    edgex_mm::MarketMakerStrategy,
+    Strategy,
+    arbitrage::{ArbitrageEngine, ExchangeRegistry},
+    backpack_mm::BackpackMMStrategy,
+    edgex_mm::{self, MarketMakerStrategy},
+    funding_arb::FundingArbStrategy,
+    runner::spawn_strategy_thread,
 };
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::runtime::Handle;
 use tokio::signal;
+use tokio::signal::unix::{SignalKind, signal as unix_signal};
 use tracing_subscriber::{EnvFilter, fmt};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Installed first so a SIGTERM during startup waits for the shutdown path
+    // instead of killing the process with the default handler
+    let mut sigterm = unix_signal(SignalKind::terminate())?;
+
     // 1. Initialize logger
     let filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info,aleph_tx=debug"));
@@ -22,71 +42,181 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("🦀 AlephTX Core v4 starting (Institutional Pipeline)...");
 
     // 2. Load configuration
-    let config = AppConfig::load_default();
-    
-    // 3. Initialize strategies
-    let mut strategies: Vec<Box<dyn Strategy>> = vec![
-        Box::new(ArbitrageEngine::new(25.0)),
+    let mut config = AppConfig::load_default();
+
+    // 3. Load credentials once; every strategy shares the same client per account
+    let credentials = Credentials::load();
+    let backpack_client = credentials.backpack_client();
+    let edgex = credentials.edgex_client();
+    let (edgex_client, edgex_account) = match &edgex {
+        Some((client, account_id)) => (Some(client.clone()), *account_id),
+        None => (None, 0),
+    };
+    let (backpack_state, edgex_state) = account_caches(&config, &backpack_client, &edgex);
+
+    // Tick / lot size from the venues' market metadata; config values are the fallback
+    if let Some(client) = &backpack_client {
+        match client.get_contract_spec(&config.backpack.symbol_name).await {
+            Ok(spec) => config.backpack.apply_contract_spec(&spec),
+            Err(e) => tracing::warn!("⚠️ [BP] Contract spec unavailable, config used: {:?}", e),
+        }
+    }
+    if let Some(client) = &edgex_client {
+        match client.get_contract_spec(edgex_mm::CONTRACT).await {
+            Ok(spec) => config.edgex.apply_contract_spec(&spec),
+            Err(e) => tracing::warn!("⚠️ [EX] Contract spec unavailable, config used: {}", e),
+        }
+    }
+
+    // Skewed host clock makes every signed request fail; warn but keep going
+    match ClockSync::default().check().await {
+        Ok(()) => tracing::info!("⏱️ Local clock within tolerance of exchange time"),
+        Err(e) => tracing::warn!("⚠️ Clock sync check: {}", e),
+    }
+
+    // 4. Initialize strategies
+    let mut arbitrage =
+        ArbitrageEngine::new_with_whitelist(25.0, config.arbitrage.allowed_symbol_ids.clone())
+            .with_max_quote_age(Duration::from_millis(config.arbitrage.max_quote_age_ms));
+    if config.arbitrage.execute {
+        match (
+            backpack_client.clone(),
+            edgex_client.clone(),
+            EdgeXConfig::from_env(),
+        ) {
+            (Some(bp), Some(ex), Ok(ex_cfg)) => {
+                let mut registry = ExchangeRegistry::default();
+                let symbol = config.backpack.symbol_name.clone();
+                let backpack = BackpackGateway::new(bp, symbol)
+                    .with_precision(config.backpack.tick_size, config.backpack.step_size);
+                registry.register(EXCH_BACKPACK, Arc::new(backpack));
+                registry.register(EXCH_EDGEX, Arc::new(EdgeXGateway::new(ex, ex_cfg)));
+                arbitrage = arbitrage.with_execution(
+                    Arc::new(registry),
+                    config.backpack.symbol_id,
+                    config.arbitrage.max_size,
+                );
+                tracing::info!(
+                    "💱 Arb execution armed (max {} per arb)",
+                    config.arbitrage.max_size
+                );
+            }
+            _ => {
+                tracing::warn!("⚠️ Arb execution disabled: Backpack/EdgeX client or config missing")
+            }
+        }
+    }
+    let mut strategies: Vec<Box<dyn Strategy + Send>> = vec![
+        Box::new(arbitrage),
         Box::new(MarketMakerStrategy::new(
-            EXCH_EDGEX, 
-            SYM_ETH, 
+            EXCH_EDGEX,
+            SYM_ETH,
             25.0,
             config.edgex.clone(),
+            edgex_client.clone(),
+            edgex_account,
+            edgex_state.clone(),
         )),
         Box::new(BackpackMMStrategy::new(
             EXCH_BACKPACK,
-            SYM_ETH,
+            config.backpack.symbol_id,
+            config.backpack.symbol_name.clone(),
             25.0,
             config.backpack.clone(),
+            backpack_client.clone(),
+            backpack_state.clone(),
         )),
     ];
+    if config.funding_arb.enabled {
+        match (backpack_client.clone(), edgex_client.clone()) {
+            (Some(bp), Some(ex)) => match FundingArbStrategy::from_clients(&config, bp, ex) {
+                Ok(arb) => strategies.push(Box::new(arb)),
+                Err(e) => tracing::warn!("⚠️ Funding arb disabled: {}", e),
+            },
+            _ => tracing::warn!("⚠️ Funding arb disabled: missing Backpack/EdgeX credentials"),
+        }
+    }
 
     tracing::info!(
         "⏳ Booted {} strategies. Waiting for market data...",
         strategies.len()
     );
 
-    // 4. Spawn dedicated data plane thread (decoupled from Tokio)
-    let bbo_rx = data_plane::spawn_data_plane_thread(
+    // 5. Spawn dedicated data plane thread (decoupled from Tokio), fanning out
+    //    to one latest-per-(symbol, exchange) mailbox per strategy.
+    //    `--capture <file>` also records every update for `replay`.
+    let stop = Arc::new(AtomicBool::new(false));
+    let (mut fanout, mailboxes) = data_plane::BboFanout::new(strategies.len());
+    let mut capture_thread = None;
+    if let Some(path) = capture_path() {
+        let (tx, rx) = flume::unbounded();
+        capture_thread = Some(spawn_capture_thread(&path, rx, stop.clone())?);
+        fanout = fanout.with_tap(tx);
+    }
+    data_plane::spawn_data_plane_fanout(
         "/dev/shm/aleph-matrix",
         2048,
-        Some(2), // Pin to CPU core 2
+        config.runtime.data_plane_core,
+        fanout,
     );
 
-    // 5. Main loop with graceful shutdown
-    let sigint = signal::ctrl_c();
-    tokio::pin!(sigint);
-    
-    loop {
-        // Async select: receive BBO updates from data plane, idle timeout, or shutdown signal
-        tokio::select! {
-             _ = &mut sigint => {
-                tracing::warn!("🛑 Ctrl+C received — shutting down gracefully...");
-                break;
-            }
-            Ok(update) = bbo_rx.recv_async() => {
-                // Process BBO update from data plane thread
-                if update.bbo.bid_price > 0.0 && update.bbo.ask_price > 0.0 {
-                    for strategy in strategies.iter_mut() {
-                        strategy.on_bbo_update(update.symbol_id, update.exchange_id, &update.bbo);
-                    }
-                }
-            }
-            _ = tokio::time::sleep(tokio::time::Duration::from_millis(1)) => {
-                // Idle timeout - call on_idle() for all strategies
-                for strategy in strategies.iter_mut() {
-                    strategy.on_idle();
-                }
-            }
+    // 6. One thread per strategy, each with its own idle timer
+    let idle = Duration::from_millis(config.runtime.idle_interval_ms.max(1));
+    let mut threads = Vec::with_capacity(strategies.len());
+    for (i, (strategy, mailbox)) in strategies.into_iter().zip(mailboxes).enumerate() {
+        let core = config.runtime.core_affinity.get(i).copied();
+        threads.push(spawn_strategy_thread(
+            strategy,
+            mailbox,
+            core,
+            idle,
+            config.runtime.max_spread_bps,
+            stop.clone(),
+            Handle::current(),
+        )?);
+    }
+
+    // 7. Wait for shutdown (SIGINT from terminal, SIGTERM from docker/systemd/k8s)
+    tokio::select! {
+        _ = signal::ctrl_c() => {
+            tracing::warn!("🛑 Ctrl+C received — shutting down gracefully...");
+        }
+        _ = sigterm.recv() => {
+            tracing::warn!("🛑 SIGTERM received — shutting down gracefully...");
         }
     }
 
-    // 6. Graceful Shutdown: Strategy hooks handle order cancellation
+    // 8. Graceful Shutdown: each strategy thread runs its on_shutdown hook
+    //    (order cancellation) before exiting
     tracing::info!("♻️ Executing strategy shutdown hooks...");
-    for strategy in strategies.iter_mut() {
-        strategy.on_shutdown().await;
+    stop.store(true, Ordering::Release);
+    for thread in threads {
+        let name = thread.thread().name().unwrap_or("strategy").to_string();
+        if tokio::task::spawn_blocking(move || thread.join())
+            .await?
+            .is_err()
+        {
+            tracing::error!("🚨 {} panicked during shutdown", name);
+        }
+    }
+    if let Some(thread) = capture_thread {
+        let _ = tokio::task::spawn_blocking(move || thread.join()).await?;
     }
 
     tracing::info!("🏁 AlephTX shutdown complete.");
     Ok(())
 }
+
+/// `--capture <file>` / `--capture=<file>`
+fn capture_path() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--capture" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--capture=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
@@ -0,0 +1,324 @@
+//! Order validation and rounding against exchange filters.
+//!
+//! Every venue rejects prices off its tick and sizes off its lot step, and
+//! most have a minimum size and notional. `OrderBuilder` applies those filters
+//! before an order is signed: resting prices round away from the touch (bids
+//! down, asks up) so a post-only quote never crosses by rounding, sizes round
+//! down so we never send more than intended, and what is left is checked
+//! against the minimums. Strict mode rejects off-grid input instead.
+
+use crate::config::ExchangeConfig;
+use thiserror::Error;
+
+/// Float noise tolerated when snapping to the grid (in ticks / steps)
+const GRID_EPS: f64 = 1e-4;
+
+/// Exchange filter metadata for one instrument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderFilters {
+    pub tick_size: f64,
+    pub step_size: f64,
+    pub min_qty: f64,
+    pub min_notional: f64,
+}
+
+impl OrderFilters {
+    pub fn from_config(cfg: &ExchangeConfig) -> Self {
+        Self {
+            tick_size: cfg.tick_size,
+            step_size: cfg.step_size,
+            min_qty: cfg.min_order_size.max(cfg.step_size),
+            min_notional: cfg.min_notional,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum OrderValidationError {
+    #[error("invalid price {0}")]
+    InvalidPrice(f64),
+    #[error("invalid quantity {0}")]
+    InvalidQuantity(f64),
+    #[error("price {price} is not on tick {tick}")]
+    PriceNotOnTick { price: f64, tick: f64 },
+    #[error("quantity {qty} is not on step {step}")]
+    QtyNotOnStep { qty: f64, step: f64 },
+    #[error("quantity {qty} below minimum {min}")]
+    BelowMinQty { qty: f64, min: f64 },
+    #[error("notional {notional:.4} below minimum {min}")]
+    BelowMinNotional { notional: f64, min: f64 },
+    /// Market orders need a reference price to check the notional.
+    #[error("no price for the min-notional check")]
+    MissingPrice,
+}
+
+/// An order that passed the filters; prices and sizes are on the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatedOrder {
+    pub is_buy: bool,
+    /// Limit price (`None` = market)
+    pub price: Option<f64>,
+    pub qty: f64,
+    pub post_only: bool,
+    pub reduce_only: bool,
+    price_decimals: usize,
+    qty_decimals: usize,
+}
+
+impl ValidatedOrder {
+    /// Limit price in the tick's precision (`None` for market orders).
+    pub fn price_str(&self) -> Option<String> {
+        self.price
+            .map(|p| format!("{:.prec$}", p, prec = self.price_decimals))
+    }
+
+    pub fn qty_str(&self) -> String {
+        format!("{:.prec$}", self.qty, prec = self.qty_decimals)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    filters: OrderFilters,
+    is_buy: bool,
+    price: Option<f64>,
+    reference_price: Option<f64>,
+    qty: f64,
+    post_only: bool,
+    reduce_only: bool,
+    strict: bool,
+}
+
+impl OrderBuilder {
+    pub fn new(filters: OrderFilters, is_buy: bool, qty: f64) -> Self {
+        Self {
+            filters,
+            is_buy,
+            price: None,
+            reference_price: None,
+            qty,
+            post_only: false,
+            reduce_only: false,
+            strict: false,
+        }
+    }
+
+    pub fn limit(mut self, price: f64) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    /// Price used for the min-notional check of a market order.
+    pub fn reference_price(mut self, price: f64) -> Self {
+        self.reference_price = Some(price);
+        self
+    }
+
+    pub fn post_only(mut self) -> Self {
+        self.post_only = true;
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Reject off-grid prices / sizes instead of rounding them.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn build(self) -> Result<ValidatedOrder, OrderValidationError> {
+        let OrderFilters {
+            tick_size,
+            step_size,
+            min_qty,
+            min_notional,
+        } = self.filters;
+        if !self.qty.is_finite() || self.qty <= 0.0 {
+            return Err(OrderValidationError::InvalidQuantity(self.qty));
+        }
+        let qty = snap(self.qty, step_size, false, self.strict).ok_or(
+            OrderValidationError::QtyNotOnStep {
+                qty: self.qty,
+                step: step_size,
+            },
+        )?;
+        if qty <= 0.0 || qty < min_qty - step_size * GRID_EPS {
+            return Err(OrderValidationError::BelowMinQty { qty, min: min_qty });
+        }
+
+        let price = match self.price {
+            Some(p) if !p.is_finite() || p <= 0.0 => {
+                return Err(OrderValidationError::InvalidPrice(p));
+            }
+            // Bids round down, asks up: never more aggressive than asked
+            Some(p) => Some(snap(p, tick_size, !self.is_buy, self.strict).ok_or(
+                OrderValidationError::PriceNotOnTick {
+                    price: p,
+                    tick: tick_size,
+                },
+            )?),
+            None => None,
+        };
+        if price == Some(0.0) {
+            return Err(OrderValidationError::InvalidPrice(0.0));
+        }
+
+        if min_notional > 0.0 {
+            let reference = price
+                .or(self.reference_price)
+                .ok_or(OrderValidationError::MissingPrice)?;
+            let notional = reference * qty;
+            if notional < min_notional {
+                return Err(OrderValidationError::BelowMinNotional {
+                    notional,
+                    min: min_notional,
+                });
+            }
+        }
+
+        Ok(ValidatedOrder {
+            is_buy: self.is_buy,
+            price,
+            qty,
+            post_only: self.post_only,
+            reduce_only: self.reduce_only,
+            price_decimals: decimals(tick_size),
+            qty_decimals: decimals(step_size),
+        })
+    }
+}
+
+/// Digits after the decimal point of a tick / step (0.0001 → 4, 0.25 → 2).
+fn decimals(increment: f64) -> usize {
+    (0..12)
+        .find(|&d| {
+            let scaled = increment * 10f64.powi(d as i32);
+            (scaled - scaled.round()).abs() < 1e-9 * scaled.max(1.0)
+        })
+        .unwrap_or(12)
+}
+
+/// `value` on the `increment` grid, rounded up or down; in strict mode `None`
+/// unless it already is on the grid (up to float noise).
+fn snap(value: f64, increment: f64, round_up: bool, strict: bool) -> Option<f64> {
+    if increment <= 0.0 {
+        return Some(value);
+    }
+    let units = value / increment;
+    let nearest = units.round();
+    let on_grid = (units - nearest).abs() < GRID_EPS;
+    let n = if on_grid {
+        nearest
+    } else if strict {
+        return None;
+    } else if round_up {
+        units.ceil()
+    } else {
+        units.floor()
+    };
+    // Back through the decimal representation to shed float artifacts
+    let scale = 10f64.powi(decimals(increment) as i32);
+    Some((n * increment * scale).round() / scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters(tick: f64, step: f64, min_qty: f64, min_notional: f64) -> OrderFilters {
+        OrderFilters {
+            tick_size: tick,
+            step_size: step,
+            min_qty,
+            min_notional,
+        }
+    }
+
+    #[test]
+    fn test_rounding_table() {
+        // (tick, step, is_buy, price, qty) → (price string, qty string)
+        let cases = [
+            (0.01, 0.01, true, 2299.9999999, 0.1, "2300.00", "0.10"),
+            (0.01, 0.01, false, 2300.0000001, 0.1, "2300.00", "0.10"),
+            (0.01, 0.01, true, 2300.015, 0.129, "2300.01", "0.12"),
+            (0.01, 0.01, false, 2300.011, 0.129, "2300.02", "0.12"),
+            (0.1, 0.0001, true, 97_123.47, 0.00019, "97123.4", "0.0001"),
+            (
+                0.1,
+                0.0001,
+                false,
+                97_123.41,
+                0.3 * 3.0,
+                "97123.5",
+                "0.9000",
+            ),
+            (
+                0.5,
+                0.001,
+                true,
+                123_456_789.74,
+                1.0005,
+                "123456789.5",
+                "1.000",
+            ),
+            (1.0, 1.0, false, 0.1 + 0.2 + 41.7, 3.0, "42", "3"),
+            (0.25, 0.1, false, 1000.3, 0.7, "1000.50", "0.7"),
+        ];
+        for (tick, step, is_buy, price, qty, want_price, want_qty) in cases {
+            let order = OrderBuilder::new(filters(tick, step, 0.0, 0.0), is_buy, qty)
+                .limit(price)
+                .post_only()
+                .build()
+                .unwrap();
+            let got = (order.price_str().unwrap(), order.qty_str());
+            let want = (want_price.to_string(), want_qty.to_string());
+            assert_eq!(got, want, "{price} {qty}");
+        }
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        use OrderValidationError::*;
+        let f = filters(0.01, 0.01, 0.05, 10.0);
+        let build =
+            |is_buy, price: f64, qty| OrderBuilder::new(f, is_buy, qty).limit(price).build();
+
+        assert!(matches!(
+            build(true, 2000.0, 0.004),
+            Err(BelowMinQty { .. })
+        ));
+        assert!(matches!(
+            build(true, 100.0, 0.05),
+            Err(BelowMinNotional { .. })
+        ));
+        assert!(build(true, 200.0, 0.05).is_ok());
+        assert!(matches!(build(true, f64::NAN, 0.1), Err(InvalidPrice(_))));
+        assert_eq!(build(true, -1.0, 0.1), Err(InvalidPrice(-1.0)));
+        assert_eq!(build(true, 0.001, 0.1), Err(InvalidPrice(0.0)));
+        assert_eq!(build(true, 2000.0, 0.0), Err(InvalidQuantity(0.0)));
+
+        let strict = |price, qty| {
+            OrderBuilder::new(f, false, qty)
+                .limit(price)
+                .strict()
+                .build()
+        };
+        assert!(matches!(strict(2000.005, 0.1), Err(PriceNotOnTick { .. })));
+        assert!(matches!(strict(2000.0, 0.105), Err(QtyNotOnStep { .. })));
+        assert_eq!(strict(2299.9999999, 0.1).unwrap().price, Some(2300.0));
+
+        // Market orders check the notional against the reference price
+        let market = OrderBuilder::new(f, true, 0.1).reduce_only();
+        assert_eq!(market.clone().build(), Err(MissingPrice));
+        let order = market.reference_price(2000.0).build().unwrap();
+        assert_eq!(
+            (order.price_str(), order.qty_str()),
+            (None, "0.10".to_string())
+        );
+        assert!(order.reduce_only);
+    }
+}
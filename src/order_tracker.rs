@@ -553,20 +553,20 @@ impl OrderTracker {
                         exchange_bindings.push((exchange_order_id, *coi));
                     }
                 }
-                (OrderLifecycle::PendingCreate, None) => {
-                    if order.created_at.elapsed() >= PENDING_CREATE_RECONCILE_GRACE {
-                        stale_ids.push((*coi, OrderLifecycle::Rejected));
-                    }
+                (OrderLifecycle::PendingCreate, None)
+                    if order.created_at.elapsed() >= PENDING_CREATE_RECONCILE_GRACE =>
+                {
+                    stale_ids.push((*coi, OrderLifecycle::Rejected));
                 }
-                (OrderLifecycle::PendingCancel, Some(_)) => {
-                    if order.last_update.elapsed() >= PENDING_CANCEL_RECONCILE_GRACE {
-                        order.lifecycle = if order.filled_size > 1e-12 {
-                            OrderLifecycle::PartiallyFilled
-                        } else {
-                            OrderLifecycle::Open
-                        };
-                        order.last_update = now;
-                    }
+                (OrderLifecycle::PendingCancel, Some(_))
+                    if order.last_update.elapsed() >= PENDING_CANCEL_RECONCILE_GRACE =>
+                {
+                    order.lifecycle = if order.filled_size > 1e-12 {
+                        OrderLifecycle::PartiallyFilled
+                    } else {
+                        OrderLifecycle::Open
+                    };
+                    order.last_update = now;
                 }
                 (
                     OrderLifecycle::Open
@@ -1,60 +1,13 @@
 use super::*;
-use crate::exchange::{
-    BatchAction, BatchOrderParams, BatchOrderResult, BatchResult, Exchange, OrderInfo, OrderResult,
-    OrderType, Side,
-};
-use async_trait::async_trait;
-
-#[derive(Clone)]
-struct MockExchange {
-    active_orders: Result<Vec<OrderInfo>, String>,
-}
-
-#[async_trait]
-impl Exchange for MockExchange {
-    async fn buy(&self, _size: f64, _price: f64) -> anyhow::Result<OrderResult> {
-        unreachable!("buy not used in order_tracker tests")
-    }
-
-    async fn sell(&self, _size: f64, _price: f64) -> anyhow::Result<OrderResult> {
-        unreachable!("sell not used in order_tracker tests")
-    }
-
-    async fn place_batch(&self, _params: BatchOrderParams) -> anyhow::Result<BatchOrderResult> {
-        unreachable!("place_batch not used in order_tracker tests")
-    }
-
-    async fn cancel_order(&self, _order_id: i64) -> anyhow::Result<()> {
-        unreachable!("cancel_order not used in order_tracker tests")
-    }
-
-    async fn cancel_all(&self) -> anyhow::Result<u32> {
-        unreachable!("cancel_all not used in order_tracker tests")
-    }
-
-    async fn get_active_orders(&self) -> anyhow::Result<Vec<OrderInfo>> {
-        self.active_orders
-            .clone()
-            .map_err(anyhow::Error::msg)
-    }
-
-    async fn close_all_positions(&self, _current_price: f64) -> anyhow::Result<()> {
-        unreachable!("close_all_positions not used in order_tracker tests")
-    }
-
-    async fn execute_batch(&self, _actions: Vec<BatchAction>) -> anyhow::Result<BatchResult> {
-        unreachable!("execute_batch not used in order_tracker tests")
-    }
-
-    async fn get_account_stats(
-        &self,
-    ) -> anyhow::Result<crate::strategy::inventory_neutral_mm::AccountStats> {
-        unreachable!("get_account_stats not used in order_tracker tests")
-    }
+use crate::exchange::{Exchange, OrderInfo, Side};
+use crate::exchanges::mock::{MockExchange, MockResponse};
 
-    fn limit_order_type(&self) -> OrderType {
-        OrderType::PostOnly
+fn mock_with_orders(orders: Vec<OrderInfo>) -> MockExchange {
+    let exchange = MockExchange::new();
+    for order in orders {
+        exchange.seed_open_order(order);
     }
+    exchange
 }
 
 fn make_tracker() -> OrderTracker {
@@ -597,16 +550,14 @@ async fn test_reconcile_with_exchange_removes_stale_open_orders_and_exposure() {
     let _ = tracker.apply_event(&created_bid);
     let _ = tracker.apply_event(&created_ask);
 
-    let exchange = MockExchange {
-        active_orders: Ok(vec![OrderInfo {
-            order_id: "9702".to_string(),
-            client_order_index: 7002,
-            side: Side::Sell,
-            price: 3010.0,
-            size: 0.04,
-            filled: 0.0,
-        }]),
-    };
+    let exchange = mock_with_orders(vec![OrderInfo {
+        order_id: "9702".to_string(),
+        client_order_index: 7002,
+        side: Side::Sell,
+        price: 3010.0,
+        size: 0.04,
+        filled: 0.0,
+    }]);
 
     let stale_count = tracker.reconcile_with_exchange(&exchange).await.unwrap();
 
@@ -628,9 +579,8 @@ async fn test_reconcile_with_exchange_propagates_exchange_errors() {
     let tracker = make_tracker();
     tracker.start_tracking(8001, OrderSide::Buy, 3000.0, 0.05);
 
-    let exchange = MockExchange {
-        active_orders: Err("exchange unavailable".to_string()),
-    };
+    let exchange = MockExchange::new();
+    exchange.set_query_error(Some("exchange unavailable"));
 
     let err = tracker
         .reconcile_with_exchange(&exchange)
@@ -657,9 +607,7 @@ async fn test_reconcile_with_exchange_removes_stale_pending_create_orders() {
         order.last_update = order.created_at;
     }
 
-    let exchange = MockExchange {
-        active_orders: Ok(vec![]),
-    };
+    let exchange = mock_with_orders(vec![]);
 
     let stale_count = tracker.reconcile_with_exchange(&exchange).await.unwrap();
 
@@ -695,26 +643,24 @@ async fn test_reconcile_with_exchange_rebinds_pending_create_and_reverts_stuck_p
         order.last_update = std::time::Instant::now() - Duration::from_secs(5);
     }
 
-    let exchange = MockExchange {
-        active_orders: Ok(vec![
-            OrderInfo {
-                order_id: "9821".to_string(),
-                client_order_index: 8201,
-                side: Side::Buy,
-                price: 3000.0,
-                size: 0.05,
-                filled: 0.0,
-            },
-            OrderInfo {
-                order_id: "9822".to_string(),
-                client_order_index: 8202,
-                side: Side::Sell,
-                price: 3010.0,
-                size: 0.04,
-                filled: 0.0,
-            },
-        ]),
-    };
+    let exchange = mock_with_orders(vec![
+        OrderInfo {
+            order_id: "9821".to_string(),
+            client_order_index: 8201,
+            side: Side::Buy,
+            price: 3000.0,
+            size: 0.05,
+            filled: 0.0,
+        },
+        OrderInfo {
+            order_id: "9822".to_string(),
+            client_order_index: 8202,
+            side: Side::Sell,
+            price: 3010.0,
+            size: 0.04,
+            filled: 0.0,
+        },
+    ]);
 
     let stale_count = tracker.reconcile_with_exchange(&exchange).await.unwrap();
 
@@ -735,6 +681,52 @@ async fn test_reconcile_with_exchange_rebinds_pending_create_and_reverts_stuck_p
     assert_eq!(pending_cancel.lifecycle, OrderLifecycle::Open);
 }
 
+#[tokio::test]
+async fn test_reconcile_with_mock_exchange_place_partial_fill_then_cancel() {
+    let tracker = make_tracker();
+    let exchange = MockExchange::new().with_balance(1_000.0);
+    exchange.push_response(MockResponse::PartialFill {
+        fraction: 0.4,
+        after_polls: 0,
+    });
+
+    let placed = exchange.buy(0.05, 3000.0).await.unwrap();
+    let coi = placed.client_order_index;
+    tracker.start_tracking(coi, OrderSide::Buy, 3000.0, 0.05);
+
+    // First poll fills 40% on the exchange; the order is still resting, so the
+    // pending-create order binds to its exchange id and becomes Open.
+    let stale_count = tracker.reconcile_with_exchange(&exchange).await.unwrap();
+    assert_eq!(stale_count, 0);
+    assert!((exchange.position() - 0.02).abs() < 1e-12);
+    {
+        let state = tracker.state.read();
+        let order = state
+            .active_orders
+            .get(&coi)
+            .expect("order should be active");
+        assert_eq!(order.lifecycle, OrderLifecycle::Open);
+        assert_eq!(order.exchange_order_id, Some(coi as u64));
+    }
+
+    tracker.mark_pending_cancel(coi);
+    exchange.cancel_order(coi).await.unwrap();
+
+    let stale_count = tracker.reconcile_with_exchange(&exchange).await.unwrap();
+    assert_eq!(stale_count, 1);
+    assert_eq!(tracker.active_order_count(), 0);
+    assert!((tracker.net_pending_exposure() - 0.0).abs() < 1e-10);
+    assert_eq!(
+        tracker
+            .state
+            .read()
+            .completed_orders
+            .get(&coi)
+            .map(|o| o.lifecycle),
+        Some(OrderLifecycle::Canceled)
+    );
+}
+
 #[test]
 fn test_startup_grace_ignores_untracked_open_events() {
     let tracker = make_tracker();
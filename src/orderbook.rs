@@ -0,0 +1,281 @@
+//! Local L2 orderbook maintained from a REST snapshot plus sequenced diffs.
+//!
+//! Protocol: seed with a snapshot tagged with its sequence, then apply diffs
+//! strictly in order (`seq == last_seq + 1`). Diffs at or below the current
+//! sequence are stale and dropped; a jump ahead means we missed updates, so the
+//! book is marked out of sync until it is re-seeded from a fresh snapshot.
+
+use crate::types::{Orderbook, OrderbookUpdate, PriceLevel, Side, Symbol};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::future::Future;
+
+/// Result of applying a diff to the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOutcome {
+    /// Diff applied, book advanced to its sequence.
+    Applied,
+    /// Diff at or below the current sequence (replay/out-of-order) — dropped.
+    Stale,
+    /// Sequence jumped ahead (or no snapshot yet) — book needs a resync.
+    Gap { expected: u64, received: u64 },
+}
+
+pub struct LocalOrderbook {
+    symbol: Symbol,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    last_seq: u64,
+    in_sync: bool,
+}
+
+impl LocalOrderbook {
+    /// Empty, unsynced book. Call `apply_snapshot` or `resync` before use.
+    pub fn new(symbol: Symbol) -> Self {
+        Self {
+            symbol,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_seq: 0,
+            in_sync: false,
+        }
+    }
+
+    pub fn from_snapshot(snapshot: &Orderbook, seq: u64) -> Self {
+        let mut book = Self::new(snapshot.symbol.clone());
+        book.apply_snapshot(snapshot, seq);
+        book
+    }
+
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.in_sync
+    }
+
+    /// Replace the whole book with a snapshot taken at `seq`.
+    pub fn apply_snapshot(&mut self, snapshot: &Orderbook, seq: u64) {
+        self.bids.clear();
+        self.asks.clear();
+        Self::apply_levels(&mut self.bids, &snapshot.bids);
+        Self::apply_levels(&mut self.asks, &snapshot.asks);
+        self.last_seq = seq;
+        self.in_sync = true;
+    }
+
+    /// Apply a sequenced diff. Levels with zero quantity are removed.
+    pub fn apply_diff(&mut self, seq: u64, update: &OrderbookUpdate) -> DiffOutcome {
+        if self.in_sync && seq <= self.last_seq {
+            return DiffOutcome::Stale;
+        }
+        let expected = self.last_seq + 1;
+        if !self.in_sync || seq != expected {
+            self.in_sync = false;
+            return DiffOutcome::Gap {
+                expected,
+                received: seq,
+            };
+        }
+
+        Self::apply_levels(&mut self.bids, &update.bids);
+        Self::apply_levels(&mut self.asks, &update.asks);
+        self.last_seq = seq;
+        DiffOutcome::Applied
+    }
+
+    /// Re-seed from a fresh snapshot. `fetch_orderbook` returns the snapshot and its sequence.
+    pub async fn resync<F, Fut>(&mut self, fetch_orderbook: F) -> anyhow::Result<()>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<(Orderbook, u64)>>,
+    {
+        let (snapshot, seq) = fetch_orderbook().await?;
+        self.apply_snapshot(&snapshot, seq);
+        Ok(())
+    }
+
+    fn apply_levels(side: &mut BTreeMap<Decimal, Decimal>, levels: &[PriceLevel]) {
+        for level in levels {
+            if level.quantity.is_zero() {
+                side.remove(&level.price);
+            } else {
+                side.insert(level.price, level.quantity);
+            }
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids.iter().next_back().map(|(p, q)| PriceLevel {
+            price: *p,
+            quantity: *q,
+        })
+    }
+
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks.iter().next().map(|(p, q)| PriceLevel {
+            price: *p,
+            quantity: *q,
+        })
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        let bid = self.best_bid()?.price;
+        let ask = self.best_ask()?.price;
+        Some((bid + ask) / Decimal::TWO)
+    }
+
+    /// Total (bid, ask) quantity resting within `bps` of mid.
+    pub fn depth_within_bps(&self, bps: Decimal) -> (Decimal, Decimal) {
+        let Some(mid) = self.mid() else {
+            return (Decimal::ZERO, Decimal::ZERO);
+        };
+        let band = mid * bps / Decimal::from(10_000);
+        let bid_depth = self.bids.range(mid - band..).map(|(_, q)| *q).sum();
+        let ask_depth = self.asks.range(..=mid + band).map(|(_, q)| *q).sum();
+        (bid_depth, ask_depth)
+    }
+
+    /// Average fill price for a taker order of `qty` (Buy walks asks, Sell walks bids).
+    /// `None` if the book is too thin to fill the full quantity.
+    pub fn vwap_for_size(&self, qty: Decimal, side: Side) -> Option<Decimal> {
+        if qty <= Decimal::ZERO {
+            return None;
+        }
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Decimal)>> = match side {
+            Side::Buy => Box::new(self.asks.iter()),
+            Side::Sell => Box::new(self.bids.iter().rev()),
+        };
+
+        let mut remaining = qty;
+        let mut notional = Decimal::ZERO;
+        for (price, level_qty) in levels {
+            let take = remaining.min(*level_qty);
+            notional += take * price;
+            remaining -= take;
+            if remaining.is_zero() {
+                return Some(notional / qty);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn levels(raw: &[(&str, &str)]) -> Vec<PriceLevel> {
+        raw.iter()
+            .map(|(p, q)| PriceLevel {
+                price: d(p),
+                quantity: d(q),
+            })
+            .collect()
+    }
+
+    fn snapshot(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> Orderbook {
+        Orderbook {
+            symbol: Symbol::new("ETH"),
+            bids: levels(bids),
+            asks: levels(asks),
+            timestamp: 0,
+        }
+    }
+
+    fn diff(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> OrderbookUpdate {
+        OrderbookUpdate {
+            symbol: Symbol::new("ETH"),
+            bids: levels(bids),
+            asks: levels(asks),
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_diff_replay_with_gap_resync() {
+        let mut book = LocalOrderbook::from_snapshot(
+            &snapshot(
+                &[("3000.0", "1.0"), ("2999.5", "2.0")],
+                &[("3000.5", "1.5"), ("3001.0", "3.0")],
+            ),
+            100,
+        );
+
+        // Recorded stream: 101 in order, 100 replayed, 102 removes a level, 104 skips 103
+        assert_eq!(
+            book.apply_diff(101, &diff(&[("3000.2", "0.5")], &[])),
+            DiffOutcome::Applied
+        );
+        assert_eq!(
+            book.apply_diff(100, &diff(&[("2990.0", "9.0")], &[])),
+            DiffOutcome::Stale
+        );
+        assert_eq!(
+            book.apply_diff(102, &diff(&[], &[("3000.5", "0")])),
+            DiffOutcome::Applied
+        );
+        assert_eq!(book.best_bid().unwrap().price, d("3000.2"));
+        assert_eq!(book.best_ask().unwrap().price, d("3001.0"));
+        assert_eq!(book.mid(), Some(d("3000.6")));
+
+        assert_eq!(
+            book.apply_diff(104, &diff(&[("3000.4", "1.0")], &[])),
+            DiffOutcome::Gap {
+                expected: 103,
+                received: 104
+            }
+        );
+        assert!(!book.is_synced());
+        // Further diffs are rejected until resynced
+        assert!(matches!(
+            book.apply_diff(105, &diff(&[], &[])),
+            DiffOutcome::Gap { .. }
+        ));
+
+        book.resync(|| async { Ok((snapshot(&[("3000.4", "1.0")], &[("3000.8", "2.0")]), 105)) })
+            .await
+            .unwrap();
+        assert!(book.is_synced());
+        assert_eq!(book.last_seq(), 105);
+        assert_eq!(
+            book.apply_diff(106, &diff(&[("3000.6", "0.3")], &[])),
+            DiffOutcome::Applied
+        );
+        assert_eq!(book.best_bid().unwrap().price, d("3000.6"));
+    }
+
+    #[test]
+    fn test_depth_and_vwap() {
+        let book = LocalOrderbook::from_snapshot(
+            &snapshot(
+                &[("100.0", "1"), ("99.9", "2"), ("99.0", "5")],
+                &[("100.2", "1"), ("100.3", "3"), ("101.0", "5")],
+            ),
+            1,
+        );
+
+        // mid = 100.1, 10 bps band = ±0.1001
+        assert_eq!(book.depth_within_bps(d("10")), (d("1"), d("1")));
+        assert_eq!(book.depth_within_bps(d("25")), (d("3"), d("4")));
+
+        // Buy 2: 1 @ 100.2 + 1 @ 100.3
+        assert_eq!(book.vwap_for_size(d("2"), Side::Buy), Some(d("100.25")));
+        // Sell 3: 1 @ 100.0 + 2 @ 99.9
+        assert_eq!(
+            book.vwap_for_size(d("3"), Side::Sell)
+                .map(|p| p.round_dp(4)),
+            Some(d("99.9333"))
+        );
+        assert_eq!(book.vwap_for_size(d("100"), Side::Buy), None);
+    }
+}
@@ -0,0 +1,322 @@
+//! BBO capture & replay for post-mortem debugging of strategy decisions.
+//!
+//! File layout: a 16-byte header (`ALTXRPL1` magic + first timestamp_ns, LE)
+//! followed by 32-byte little-endian records:
+//!
+//! | off | size | field                                           |
+//! |-----|------|-------------------------------------------------|
+//! | 0   | 2    | symbol_id (u16)                                 |
+//! | 2   | 1    | exchange_id (u8)                                |
+//! | 3   | 1    | msg_type (u8)                                   |
+//! | 4   | 4    | µs since previous record (u32, saturating)      |
+//! | 8   | 8    | bid_price (f64)                                 |
+//! | 16  | 8    | ask_price (f64)                                 |
+//! | 24  | 4    | bid_size (f32)                                  |
+//! | 28  | 4    | ask_size (f32)                                  |
+//!
+//! Sizes are narrowed to f32 and timestamps to µs deltas to fit 32 bytes.
+
+use crate::data_plane::BboUpdate;
+use crate::shm_reader::ShmBboMessage;
+use crate::strategy::Strategy;
+use flume::{Receiver, RecvTimeoutError};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{error, info};
+
+pub const RECORD_SIZE: usize = 32;
+const MAGIC: &[u8; 8] = b"ALTXRPL1";
+const HEADER_SIZE: usize = 16;
+/// Gap between consecutive records that counts as an idle period (main loop: 1ms)
+const IDLE_GAP_NS: u64 = 1_000_000;
+
+/// Appends every BBO seen by the main loop to a capture file.
+pub struct ReplayCapture {
+    out: BufWriter<File>,
+    last_ts_ns: Option<u64>,
+    records: u64,
+}
+
+impl ReplayCapture {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            last_ts_ns: None,
+            records: 0,
+        })
+    }
+
+    pub fn record(
+        &mut self,
+        symbol_id: u16,
+        exchange_id: u8,
+        bbo: &ShmBboMessage,
+    ) -> io::Result<()> {
+        let delta_us = match self.last_ts_ns {
+            None => {
+                self.out.write_all(MAGIC)?;
+                self.out.write_all(&bbo.timestamp_ns.to_le_bytes())?;
+                self.last_ts_ns = Some(bbo.timestamp_ns);
+                0
+            }
+            Some(last) => {
+                let delta = bbo.timestamp_ns.saturating_sub(last) / 1_000;
+                // Advance by what was encoded so rounding never accumulates
+                self.last_ts_ns = Some(last + delta.min(u32::MAX as u64) * 1_000);
+                delta.min(u32::MAX as u64) as u32
+            }
+        };
+
+        let mut rec = [0u8; RECORD_SIZE];
+        rec[0..2].copy_from_slice(&symbol_id.to_le_bytes());
+        rec[2] = exchange_id;
+        rec[3] = bbo.msg_type;
+        rec[4..8].copy_from_slice(&delta_us.to_le_bytes());
+        rec[8..16].copy_from_slice(&bbo.bid_price.to_le_bytes());
+        rec[16..24].copy_from_slice(&bbo.ask_price.to_le_bytes());
+        rec[24..28].copy_from_slice(&(bbo.bid_size as f32).to_le_bytes());
+        rec[28..32].copy_from_slice(&(bbo.ask_size as f32).to_le_bytes());
+        self.out.write_all(&rec)?;
+        self.records += 1;
+        Ok(())
+    }
+
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl Drop for ReplayCapture {
+    fn drop(&mut self) {
+        let _ = self.out.flush();
+    }
+}
+
+/// Drain `updates` into a capture file on its own thread (disk I/O stays off
+/// the data plane). Flushes when idle and exits once `stop` is set.
+pub fn spawn_capture_thread(
+    path: &Path,
+    updates: Receiver<BboUpdate>,
+    stop: Arc<AtomicBool>,
+) -> io::Result<JoinHandle<()>> {
+    let mut capture = ReplayCapture::new(path)?;
+    let path = path.display().to_string();
+    info!("🎞️ Capturing BBO stream to {}", path);
+    std::thread::Builder::new()
+        .name("replay-capture".to_string())
+        .spawn(move || {
+            let mut write = |u: BboUpdate| {
+                if let Err(e) = capture.record(u.symbol_id, u.exchange_id, &u.bbo) {
+                    error!("❌ Capture write failed ({}): {}", path, e);
+                }
+            };
+            while !stop.load(Ordering::Acquire) {
+                match updates.recv_timeout(Duration::from_millis(200)) {
+                    Ok(update) => write(update),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            updates.try_iter().for_each(&mut write);
+            let _ = capture.flush();
+            info!("🎞️ Capture closed: {} records", capture.records());
+        })
+}
+
+/// Reads a capture back as `(symbol_id, exchange_id, ShmBboMessage)`.
+pub struct ReplayReader {
+    input: BufReader<File>,
+    ts_ns: u64,
+}
+
+impl ReplayReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+        let mut header = [0u8; HEADER_SIZE];
+        match input.read_exact(&mut header) {
+            Ok(()) if &header[..8] == MAGIC => {}
+            Ok(()) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "not a replay capture",
+                ));
+            }
+            // Empty capture: nothing was recorded
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+            Err(e) => return Err(e),
+        }
+        let ts_ns = u64::from_le_bytes(header[8..16].try_into().unwrap_or_default());
+        Ok(Self { input, ts_ns })
+    }
+
+    pub fn next_record(&mut self) -> io::Result<Option<(u16, u8, ShmBboMessage)>> {
+        let mut rec = [0u8; RECORD_SIZE];
+        match self.input.read_exact(&mut rec) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let u32_at = |i: usize| u32::from_le_bytes([rec[i], rec[i + 1], rec[i + 2], rec[i + 3]]);
+        let f64_at = |i: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&rec[i..i + 8]);
+            f64::from_le_bytes(b)
+        };
+
+        let symbol_id = u16::from_le_bytes([rec[0], rec[1]]);
+        let exchange_id = rec[2];
+        self.ts_ns += u32_at(4) as u64 * 1_000;
+        let bbo = ShmBboMessage {
+            msg_type: rec[3],
+            exchange_id,
+            symbol_id,
+            timestamp_ns: self.ts_ns,
+            bid_price: f64_at(8),
+            ask_price: f64_at(16),
+            bid_size: f32::from_bits(u32_at(24)) as f64,
+            ask_size: f32::from_bits(u32_at(28)) as f64,
+            ..Default::default()
+        };
+        Ok(Some((symbol_id, exchange_id, bbo)))
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub updates: u64,
+    pub idles: u64,
+}
+
+pub struct ReplayEngine;
+
+impl ReplayEngine {
+    /// Feed a capture through `strategies` in recorded order.
+    ///
+    /// `on_idle` is called wherever the recording shows a quiet gap of at least
+    /// 1ms (the main loop's idle timeout) and once at the end.
+    pub fn play(path: &Path, strategies: &mut [Box<dyn Strategy>]) -> io::Result<ReplayStats> {
+        let mut reader = ReplayReader::open(path)?;
+        let mut stats = ReplayStats::default();
+        let mut last_ts: Option<u64> = None;
+
+        while let Some((symbol_id, exchange_id, bbo)) = reader.next_record()? {
+            if last_ts.is_some_and(|t| bbo.timestamp_ns.saturating_sub(t) >= IDLE_GAP_NS) {
+                strategies.iter_mut().for_each(|s| s.on_idle());
+                stats.idles += 1;
+            }
+            last_ts = Some(bbo.timestamp_ns);
+            if bbo.bid_price > 0.0 && bbo.ask_price > 0.0 {
+                for strategy in strategies.iter_mut() {
+                    strategy.on_bbo_update(symbol_id, exchange_id, &bbo);
+                }
+                stats.updates += 1;
+            }
+        }
+        if last_ts.is_some() {
+            strategies.iter_mut().for_each(|s| s.on_idle());
+            stats.idles += 1;
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    enum Call {
+        Bbo(u8, f64, u64),
+        Idle,
+    }
+
+    struct Recorder(Arc<Mutex<Vec<Call>>>);
+
+    impl Strategy for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+        fn on_bbo_update(&mut self, _symbol_id: u16, exchange_id: u8, bbo: &ShmBboMessage) {
+            self.0
+                .lock()
+                .push(Call::Bbo(exchange_id, bbo.bid_price, bbo.timestamp_ns));
+        }
+        fn on_idle(&mut self) {
+            self.0.lock().push(Call::Idle);
+        }
+    }
+
+    fn bbo(ts_ns: u64, bid: f64) -> ShmBboMessage {
+        ShmBboMessage {
+            msg_type: 1,
+            timestamp_ns: ts_ns,
+            bid_price: bid,
+            bid_size: 1.5,
+            ask_price: bid + 0.5,
+            ask_size: 0.25,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_capture_roundtrip_and_replay_order() {
+        let path = std::env::temp_dir().join(format!("aleph-replay-{}.bin", std::process::id()));
+        let t0 = 1_700_000_000_000_000_000u64;
+        {
+            let mut cap = ReplayCapture::new(&path).unwrap();
+            cap.record(1002, 3, &bbo(t0, 2000.0)).unwrap();
+            cap.record(1002, 4, &bbo(t0 + 200_000, 2000.25)).unwrap();
+            // 5ms gap → idle before this one
+            cap.record(1002, 3, &bbo(t0 + 5_200_000, 2001.0)).unwrap();
+            assert_eq!(cap.records(), 3);
+        }
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            (HEADER_SIZE + 3 * RECORD_SIZE) as u64
+        );
+
+        let mut reader = ReplayReader::open(&path).unwrap();
+        let (sym, exch, first) = reader.next_record().unwrap().unwrap();
+        assert_eq!((sym, exch, first.msg_type), (1002, 3, 1));
+        assert_eq!((first.bid_size, first.ask_size), (1.5, 0.25));
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Recorder(calls.clone()))];
+        let stats = ReplayEngine::play(&path, &mut strategies).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(
+            stats,
+            ReplayStats {
+                updates: 3,
+                idles: 2
+            }
+        );
+        assert_eq!(
+            *calls.lock(),
+            vec![
+                Call::Bbo(3, 2000.0, t0),
+                Call::Bbo(4, 2000.25, t0 + 200_000),
+                Call::Idle,
+                Call::Bbo(3, 2001.0, t0 + 5_200_000),
+                Call::Idle,
+            ]
+        );
+    }
+}
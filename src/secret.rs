@@ -0,0 +1,80 @@
+//! Redacted wrapper for API secrets and private keys.
+//!
+//! `Debug` and `Display` never print the value, so a stray `{:?}` on a
+//! credential-bearing struct or an error chain cannot leak it into logs, and
+//! the memory is wiped on drop. Read it through `expose` right where it is
+//! handed to a signer.
+
+use std::fmt;
+use zeroize::Zeroize;
+
+const REDACTED: &str = "***REDACTED***";
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl From<String> for Secret<String> {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::{BackpackCredentials, Credentials, EdgeXCredentials};
+
+    #[test]
+    fn test_credentials_debug_is_redacted() {
+        let creds = Credentials {
+            backpack: Some(BackpackCredentials {
+                api_key: "bp-public".to_string(),
+                api_secret: "bp-secret-b64==".to_string().into(),
+            }),
+            edgex: Some(EdgeXCredentials {
+                account_id: 42,
+                stark_private_key: "0xdeadbeefcafe".to_string().into(),
+            }),
+        };
+        let debug = format!("{:?} {:#?}", creds, creds);
+        for secret in ["bp-secret-b64==", "0xdeadbeefcafe"] {
+            assert!(!debug.contains(secret), "{secret} leaked: {debug}");
+        }
+        assert!(debug.contains("bp-public") && debug.contains(REDACTED));
+
+        let key = Secret::new("hunter2".to_string());
+        assert_eq!(
+            format!("{} {:?}", key, key),
+            "***REDACTED*** ***REDACTED***"
+        );
+        assert_eq!(key.expose(), "hunter2");
+    }
+}
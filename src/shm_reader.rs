@@ -1,10 +1,20 @@
 // src/shm_reader.rs - Lock-free Shared Matrix for HFT
-use std::sync::atomic::{Ordering, compiler_fence};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering, compiler_fence};
+use thiserror::Error;
 
 pub const NUM_SYMBOLS: usize = 2048;
 pub const NUM_EXCHANGES: usize = 7; // Padding, HL, Lighter, EdgeX, 01, Backpack, Binance
 const SLOT_SIZE: usize = 64;
 const VERSION_SIZE: usize = 8;
+/// Trailing global tick counter, placed after the BBO matrix so older offsets are unchanged.
+const GLOBAL_SEQ_OFFSET: usize =
+    NUM_SYMBOLS * VERSION_SIZE + NUM_SYMBOLS * NUM_EXCHANGES * SLOT_SIZE;
+const SYMBOLS_PER_BLOCK: usize = 64;
+const NUM_BLOCKS: usize = NUM_SYMBOLS / SYMBOLS_PER_BLOCK;
+/// Per-block write counters after the global tick (feeders that predate them
+/// leave the mapping short and `fast_poll` falls back to the full scan).
+const BLOCK_VERSIONS_OFFSET: usize = GLOBAL_SEQ_OFFSET + 8;
 
 #[repr(C, align(64))]
 #[derive(Clone, Copy, Debug, Default)]
@@ -23,31 +33,80 @@ pub struct ShmBboMessage {
 
 const _: () = assert!(std::mem::size_of::<ShmBboMessage>() == SLOT_SIZE);
 
+impl ShmBboMessage {
+    /// Quoted spread relative to the mid (negative if crossed).
+    #[inline(always)]
+    pub fn spread_bps(&self) -> f64 {
+        let mid = (self.bid_price + self.ask_price) * 0.5;
+        (self.ask_price - self.bid_price) / mid * 10_000.0
+    }
+
+    /// Both sides quoted, not crossed, and spread below `max_spread_bps`.
+    #[inline(always)]
+    pub fn is_sane(&self, max_spread_bps: f64) -> bool {
+        self.bid_price > 0.0
+            && self.ask_price >= self.bid_price
+            && self.spread_bps() < max_spread_bps
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ShmError {
+    #[error("shared memory file not found: {}", .0.display())]
+    FileNotFound(PathBuf),
+    /// Shorter than the version table, which is read without bounds checks
+    #[error("shared memory file too small: {actual} bytes, need at least {expected}")]
+    FileTooSmall { actual: usize, expected: usize },
+    /// Opening or mapping the file failed
+    #[error("shared memory mmap failed: {0}")]
+    MmapFailed(std::io::Error),
+    #[error("shared memory mapping is not aligned for atomic access")]
+    InvalidAlignment,
+}
+
 pub struct ShmReader {
     // Must keep mmap alive - without it, data pointer is invalid!
     _mmap: memmap2::Mmap,
     data: *const u8,
     local_versions: [u64; NUM_SYMBOLS],
+    local_block_versions: [u64; NUM_BLOCKS],
     max_symbols: usize,
 }
 
 impl ShmReader {
-    pub fn open(path: &str, num_symbols: usize) -> anyhow::Result<Self> {
-        let file = std::fs::File::open(path)?;
-        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    pub fn open(path: &str, num_symbols: usize) -> Result<Self, ShmError> {
+        let file = std::fs::File::open(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ShmError::FileNotFound(PathBuf::from(path)),
+            _ => ShmError::MmapFailed(e),
+        })?;
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(ShmError::MmapFailed)?;
 
+        let expected = NUM_SYMBOLS * VERSION_SIZE;
+        if mmap.len() < expected {
+            return Err(ShmError::FileTooSmall {
+                actual: mmap.len(),
+                expected,
+            });
+        }
         let data = mmap.as_ptr();
+        if !(data as usize).is_multiple_of(std::mem::align_of::<AtomicU64>()) {
+            return Err(ShmError::InvalidAlignment);
+        }
 
         Ok(Self {
             _mmap: mmap,
             data,
             local_versions: [0u64; NUM_SYMBOLS],
+            local_block_versions: [0u64; NUM_BLOCKS],
             max_symbols: num_symbols.min(NUM_SYMBOLS),
         })
     }
 
     #[inline(always)]
     fn load_version(&self, symbol_id: u16) -> u64 {
+        if symbol_id as usize >= NUM_SYMBOLS {
+            return 0;
+        }
         let offset = (symbol_id as usize) * VERSION_SIZE;
         unsafe {
             let ptr = self.data.add(offset) as *const std::sync::atomic::AtomicU64;
@@ -55,6 +114,19 @@ impl ShmReader {
         }
     }
 
+    /// Global feeder tick (incremented once per feeder loop iteration, every 1 ms).
+    /// Returns 0 if the feeder predates the counter and the mapping is too short.
+    #[inline(always)]
+    pub fn read_global_sequence(&self) -> u64 {
+        if self._mmap.len() < GLOBAL_SEQ_OFFSET + 8 {
+            return 0;
+        }
+        unsafe {
+            let ptr = self.data.add(GLOBAL_SEQ_OFFSET) as *const std::sync::atomic::AtomicU64;
+            (*ptr).load(Ordering::Acquire)
+        }
+    }
+
     #[inline(always)]
     pub fn try_poll(&mut self) -> Option<u16> {
         for sym in 0..self.max_symbols {
@@ -70,78 +142,316 @@ impl ShmReader {
     }
 
     #[inline(always)]
-    pub fn read_all_exchanges(&mut self, symbol_id: u16) -> [(u8, ShmBboMessage); NUM_EXCHANGES] {
-        let version = self.load_version(symbol_id);
-        self.local_versions[symbol_id as usize] = version;
+    fn has_block_versions(&self) -> bool {
+        self._mmap.len() >= BLOCK_VERSIONS_OFFSET + NUM_BLOCKS * 8
+    }
 
-        let mut result = [(0u8, ShmBboMessage::default()); NUM_EXCHANGES];
-        for (exch, item) in result.iter_mut().enumerate().take(NUM_EXCHANGES) {
-            let base = NUM_SYMBOLS * VERSION_SIZE;
-            let offset = base + (symbol_id as usize * NUM_EXCHANGES + exch) * SLOT_SIZE;
-            let ptr = unsafe { self.data.add(offset) };
-            let seq_ptr = ptr as *const std::sync::atomic::AtomicU32;
-
-            let mut msg;
-            let mut spin_count: u32 = 0;
-            const MAX_SPINS: u32 = 10_000;
-
-            loop {
-                // 1. Read Lock (Acquire)
-                let seq1 = unsafe { (*seq_ptr).load(Ordering::Acquire) };
-                if seq1 & 1 != 0 {
-                    spin_count += 1;
-                    if spin_count > MAX_SPINS {
-                        tracing::error!(
-                            "Seqlock stuck (writer dead?): symbol={} exch={} seq={} after {} spins",
-                            symbol_id,
-                            exch,
-                            seq1,
-                            spin_count
-                        );
-                        // Return stale data rather than hang forever
-                        msg = ShmBboMessage::default();
-                        break;
-                    }
-                    std::hint::spin_loop();
-                    continue; // Writer is active, wait
-                }
+    #[inline(always)]
+    fn load_block_version(&self, block: usize) -> u64 {
+        unsafe {
+            let ptr = self.data.add(BLOCK_VERSIONS_OFFSET + block * 8) as *const AtomicU64;
+            (*ptr).load(Ordering::Acquire)
+        }
+    }
 
-                compiler_fence(Ordering::Acquire);
+    /// Like `try_poll`, but checks the 32 per-block counters first and only
+    /// scans the versions of blocks written since the last poll. Cheaper when
+    /// few symbols change between polls.
+    #[inline(always)]
+    pub fn fast_poll(&mut self) -> Option<u16> {
+        if !self.has_block_versions() {
+            return self.try_poll();
+        }
+        for block in 0..self.max_symbols.div_ceil(SYMBOLS_PER_BLOCK) {
+            let block_version = self.load_block_version(block);
+            if block_version == self.local_block_versions[block] {
+                continue;
+            }
+            let start = block * SYMBOLS_PER_BLOCK;
+            for sym in start..(start + SYMBOLS_PER_BLOCK).min(self.max_symbols) {
+                let version = self.load_version(sym as u16);
+                if version > self.local_versions[sym] {
+                    // Block stays dirty until a scan finds nothing new in it
+                    self.local_versions[sym] = version;
+                    return Some(sym as u16);
+                }
+            }
+            // The feeder bumps the block after the symbol version, so every
+            // write counted in `block_version` has been seen
+            self.local_block_versions[block] = block_version;
+        }
+        None
+    }
 
-                // 2. Copy payload
-                msg = unsafe { core::ptr::read_volatile(ptr as *const ShmBboMessage) };
+    /// Checked pointer to the (symbol, exchange) BBO slot.
+    /// `None` if either index is out of range or the slot lies past the mapping.
+    #[inline(always)]
+    fn bbo_at(&self, symbol_id: u16, exchange_id: u8) -> Option<*const ShmBboMessage> {
+        let (sym, exch) = (symbol_id as usize, exchange_id as usize);
+        if sym >= NUM_SYMBOLS || exch >= NUM_EXCHANGES {
+            warn_out_of_bounds(symbol_id, exchange_id);
+            return None;
+        }
+        let offset = NUM_SYMBOLS * VERSION_SIZE + (sym * NUM_EXCHANGES + exch) * SLOT_SIZE;
+        if offset + SLOT_SIZE > self._mmap.len() {
+            warn_out_of_bounds(symbol_id, exchange_id);
+            return None;
+        }
+        Some(unsafe { self.data.add(offset) } as *const ShmBboMessage)
+    }
 
-                compiler_fence(Ordering::Acquire);
+    /// Seqlock read of a single (symbol, exchange) slot.
+    /// `None` on out-of-bounds indices; a default message if the writer appears stuck.
+    #[inline(always)]
+    pub fn read_bbo_strict(&self, symbol_id: u16, exchange_id: u8) -> Option<ShmBboMessage> {
+        let ptr = self.bbo_at(symbol_id, exchange_id)?;
+        let seq_ptr = ptr as *const std::sync::atomic::AtomicU32;
 
-                // 3. Validate lock
-                let seq2 = unsafe { (*seq_ptr).load(Ordering::Acquire) };
-                if seq1 == seq2 {
-                    break; // Data is clean, break spin loop
-                }
+        let mut spin_count: u32 = 0;
+        const MAX_SPINS: u32 = 10_000;
 
+        loop {
+            // 1. Read Lock (Acquire)
+            let seq1 = unsafe { (*seq_ptr).load(Ordering::Acquire) };
+            if seq1 & 1 != 0 {
                 spin_count += 1;
                 if spin_count > MAX_SPINS {
                     tracing::error!(
-                        "Seqlock torn read limit: symbol={} exch={} after {} spins",
+                        "Seqlock stuck (writer dead?): symbol={} exch={} seq={} after {} spins",
                         symbol_id,
-                        exch,
+                        exchange_id,
+                        seq1,
                         spin_count
                     );
-                    msg = ShmBboMessage::default();
-                    break;
+                    // Return stale data rather than hang forever
+                    return Some(ShmBboMessage::default());
                 }
+                std::hint::spin_loop();
+                continue; // Writer is active, wait
             }
 
+            compiler_fence(Ordering::Acquire);
+
+            // 2. Copy payload
+            let msg = unsafe { core::ptr::read_volatile(ptr) };
+
+            compiler_fence(Ordering::Acquire);
+
+            // 3. Validate lock
+            let seq2 = unsafe { (*seq_ptr).load(Ordering::Acquire) };
+            if seq1 == seq2 {
+                return Some(msg); // Data is clean
+            }
+
+            spin_count += 1;
+            if spin_count > MAX_SPINS {
+                tracing::error!(
+                    "Seqlock torn read limit: symbol={} exch={} after {} spins",
+                    symbol_id,
+                    exchange_id,
+                    spin_count
+                );
+                return Some(ShmBboMessage::default());
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn read_all_exchanges(&mut self, symbol_id: u16) -> [(u8, ShmBboMessage); NUM_EXCHANGES] {
+        let mut result = [(0u8, ShmBboMessage::default()); NUM_EXCHANGES];
+        if symbol_id as usize >= NUM_SYMBOLS {
+            warn_out_of_bounds(symbol_id, 0);
+            return result;
+        }
+
+        let version = self.load_version(symbol_id);
+        self.local_versions[symbol_id as usize] = version;
+
+        for (exch, item) in result.iter_mut().enumerate() {
+            let msg = self
+                .read_bbo_strict(symbol_id, exch as u8)
+                .unwrap_or_default();
             *item = (exch as u8, msg);
         }
         result
     }
 
     pub fn local_version(&self, symbol_id: u16) -> u64 {
-        self.local_versions[symbol_id as usize]
+        self.local_versions
+            .get(symbol_id as usize)
+            .copied()
+            .unwrap_or(0)
     }
 
     pub fn shared_version(&self, symbol_id: u16) -> u64 {
         self.load_version(symbol_id)
     }
 }
+
+// Read-only mapping; every access goes through atomics / the seqlock
+unsafe impl Send for ShmReader {}
+unsafe impl Sync for ShmReader {}
+
+/// Out-of-range (symbol, exchange) from the feeder — logged at most once per second.
+#[cold]
+fn warn_out_of_bounds(symbol_id: u16, exchange_id: u8) {
+    static LAST_WARN_SECS: AtomicU64 = AtomicU64::new(0);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let last = LAST_WARN_SECS.load(Ordering::Relaxed);
+    if now > last
+        && LAST_WARN_SECS
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    {
+        tracing::warn!(
+            "⚠️ [SHM] BBO slot out of bounds: symbol={} (max {}) exch={} (max {})",
+            symbol_id,
+            NUM_SYMBOLS - 1,
+            exchange_id,
+            NUM_EXCHANGES - 1
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_matrix(name: &str, len: usize, global_seq: Option<u64>) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let mut buf = vec![0u8; len];
+        if let Some(seq) = global_seq {
+            buf[GLOBAL_SEQ_OFFSET..GLOBAL_SEQ_OFFSET + 8].copy_from_slice(&seq.to_le_bytes());
+        }
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(&buf)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_global_sequence() {
+        let path = temp_matrix("aleph-matrix-seq", GLOBAL_SEQ_OFFSET + 8, Some(42));
+        let reader = ShmReader::open(path.to_str().unwrap(), 16).unwrap();
+        assert_eq!(reader.read_global_sequence(), 42);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_read_global_sequence_legacy_layout() {
+        let path = temp_matrix("aleph-matrix-legacy", GLOBAL_SEQ_OFFSET, None);
+        let reader = ShmReader::open(path.to_str().unwrap(), 16).unwrap();
+        assert_eq!(reader.read_global_sequence(), 0);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_bbo_bounds_checked() {
+        let path = temp_matrix("aleph-matrix-bounds", GLOBAL_SEQ_OFFSET, None);
+        let mut reader = ShmReader::open(path.to_str().unwrap(), 16).unwrap();
+
+        assert!(reader.read_bbo_strict(5, 3).is_some());
+        assert!(reader.read_bbo_strict(NUM_SYMBOLS as u16, 3).is_none());
+        assert!(reader.read_bbo_strict(5, NUM_EXCHANGES as u8).is_none());
+
+        // Out-of-range symbol from the feeder yields defaults instead of UB
+        let all = reader.read_all_exchanges(u16::MAX);
+        assert!(all.iter().all(|(_, m)| m.bid_price == 0.0));
+        assert_eq!(reader.local_version(u16::MAX), 0);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_bbo_sanity() {
+        let bbo = |bid_price, ask_price| ShmBboMessage {
+            bid_price,
+            ask_price,
+            ..Default::default()
+        };
+        assert!((bbo(1999.0, 2001.0).spread_bps() - 10.0).abs() < 1e-9);
+        assert!(bbo(1999.0, 2001.0).is_sane(500.0));
+        assert!(!bbo(1999.0, 2001.0).is_sane(10.0));
+        // One-sided, crossed and fat-finger quotes are all rejected
+        assert!(!bbo(0.0, 2001.0).is_sane(500.0));
+        assert!(!bbo(2001.0, 1999.0).is_sane(500.0));
+        assert!(!bbo(1000.0, 3000.0).is_sane(500.0));
+    }
+
+    #[test]
+    fn test_bbo_past_mapping_end() {
+        // Truncated mapping: only the version table plus one symbol's row
+        let len = NUM_SYMBOLS * VERSION_SIZE + NUM_EXCHANGES * SLOT_SIZE;
+        let path = temp_matrix("aleph-matrix-short", len, None);
+        let reader = ShmReader::open(path.to_str().unwrap(), 16).unwrap();
+        assert!(reader.read_bbo_strict(0, 6).is_some());
+        assert!(reader.read_bbo_strict(1, 0).is_none());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_fast_poll_scans_only_written_blocks() {
+        use std::os::unix::fs::FileExt;
+        let len = BLOCK_VERSIONS_OFFSET + NUM_BLOCKS * 8;
+        let path = temp_matrix("aleph-matrix-blocks", len, None);
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        // What the feeder does per WriteBBO: symbol version, then block counter
+        let write = |sym: usize, version: u64, block_version: u64| {
+            file.write_at(&version.to_le_bytes(), (sym * VERSION_SIZE) as u64)
+                .unwrap();
+            let block_offset = BLOCK_VERSIONS_OFFSET + sym / SYMBOLS_PER_BLOCK * 8;
+            file.write_at(&block_version.to_le_bytes(), block_offset as u64)
+                .unwrap();
+        };
+        let mut reader = ShmReader::open(path.to_str().unwrap(), 2048).unwrap();
+        assert_eq!(reader.fast_poll(), None);
+
+        write(3, 1, 1);
+        write(10, 1, 2);
+        write(130, 1, 1);
+        assert_eq!(reader.fast_poll(), Some(3));
+        assert_eq!(reader.fast_poll(), Some(10));
+        assert_eq!(reader.fast_poll(), Some(130));
+        assert_eq!(reader.fast_poll(), None);
+        assert_eq!(reader.local_block_versions[0], 2);
+
+        // A version bump without its block counter is not picked up (by design)
+        file.write_at(&5u64.to_le_bytes(), 700 * VERSION_SIZE as u64)
+            .unwrap();
+        assert_eq!(reader.fast_poll(), None);
+        assert_eq!(reader.try_poll(), Some(700));
+
+        write(2047, 1, 1);
+        assert_eq!(reader.fast_poll(), Some(2047));
+        std::fs::remove_file(path).ok();
+
+        // Older feeder layout: full scan
+        let path = temp_matrix("aleph-matrix-noblocks", GLOBAL_SEQ_OFFSET + 8, None);
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.write_at(&1u64.to_le_bytes(), 42 * VERSION_SIZE as u64)
+            .unwrap();
+        let mut reader = ShmReader::open(path.to_str().unwrap(), 2048).unwrap();
+        assert_eq!(reader.fast_poll(), Some(42));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_open_errors() {
+        let missing = std::env::temp_dir().join("aleph-matrix-missing");
+        assert!(matches!(
+            ShmReader::open(missing.to_str().unwrap(), 16),
+            Err(ShmError::FileNotFound(p)) if p == missing
+        ));
+
+        let path = temp_matrix("aleph-matrix-tiny", 64, None);
+        assert!(matches!(
+            ShmReader::open(path.to_str().unwrap(), 16),
+            Err(ShmError::FileTooSmall { actual: 64, expected }) if expected == NUM_SYMBOLS * 8
+        ));
+        std::fs::remove_file(path).ok();
+    }
+}
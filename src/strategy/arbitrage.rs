@@ -2,11 +2,31 @@
 //! O(1) Scalable Arbitrage Engine
 //!
 //! Scans all exchanges to find the Global Best Bid (GBB) and Global Best Ask (GBA) per symbol.
+//!
+//! With an `ExchangeRegistry` attached (`with_execution`), a cross on the
+//! traded symbol is executed as two concurrent IOC legs. Whatever one leg
+//! filled beyond the other (all of it, if the other failed) is flattened with
+//! a reduce-only IOC, as the funding arb does.
 
+use crate::error::TradingError;
+use crate::exchange::{OrderResult, Side};
 use crate::shm_reader::ShmBboMessage;
 use crate::strategy::Strategy;
+use crate::strategy::funding_arb::{FundingVenue, TakeFill};
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::runtime::Handle;
 
 pub const NUM_EXCHANGES: usize = 5;
+/// Max global-sequence distance between the two legs before the comparison is flagged as
+/// stale; the feeder ticks once per millisecond, so this is one second
+pub const DEFAULT_MAX_SEQUENCE_GAP: u64 = 1_000;
+/// Price concession when flattening a leg its partner did not match
+const UNWIND_SLIPPAGE_BPS: f64 = 20.0;
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct BboSnapshot {
@@ -35,12 +55,320 @@ impl BboSnapshot {
     }
 }
 
+/// Best bid and best ask of one symbol across exchanges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalBest {
+    pub bid_price: f64,
+    pub bid_size: f64,
+    pub bid_exchange: u8,
+    pub ask_price: f64,
+    pub ask_size: f64,
+    pub ask_exchange: u8,
+    /// Newest update among the exchanges with a valid quote
+    pub timestamp_ns: u64,
+}
+
+/// `GlobalBest` plus when each exchange last quoted (0 = no valid quote).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalBestWithMeta {
+    pub best: GlobalBest,
+    pub per_exchange_ts: [u64; NUM_EXCHANGES],
+}
+
+impl GlobalBestWithMeta {
+    /// Exchanges whose quote is more than `max_age_ns` older than `now_ns`.
+    pub fn stale_exchanges(&self, max_age_ns: u64, now_ns: u64) -> SmallVec<[u8; NUM_EXCHANGES]> {
+        self.per_exchange_ts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &ts)| ts > 0 && now_ns.saturating_sub(ts) > max_age_ns)
+            .map(|(exch, _)| exch as u8)
+            .collect()
+    }
+}
+
+/// Best bid / ask over the valid quotes in `bbos`; `None` if there are none.
+pub fn find_global_best(bbos: &[ShmBboMessage; NUM_EXCHANGES]) -> Option<GlobalBestWithMeta> {
+    let mut best = GlobalBest {
+        bid_price: 0.0,
+        bid_size: 0.0,
+        bid_exchange: 0,
+        ask_price: f64::MAX,
+        ask_size: 0.0,
+        ask_exchange: 0,
+        timestamp_ns: 0,
+    };
+    let mut per_exchange_ts = [0; NUM_EXCHANGES];
+    for (exch_idx, msg) in bbos.iter().enumerate() {
+        let snap = BboSnapshot::from_shm(msg);
+        if !snap.is_valid() {
+            continue;
+        }
+        per_exchange_ts[exch_idx] = snap.timestamp_ns;
+        best.timestamp_ns = best.timestamp_ns.max(snap.timestamp_ns);
+
+        if snap.bid_price > best.bid_price {
+            best.bid_price = snap.bid_price;
+            best.bid_size = snap.bid_size;
+            best.bid_exchange = exch_idx as u8;
+        }
+
+        if snap.ask_price < best.ask_price {
+            best.ask_price = snap.ask_price;
+            best.ask_size = snap.ask_size;
+            best.ask_exchange = exch_idx as u8;
+        }
+    }
+    (best.bid_price > 0.0).then_some(GlobalBestWithMeta {
+        best,
+        per_exchange_ts,
+    })
+}
+
+/// A detected cross: buy at `buy_exchange`'s ask, sell at `sell_exchange`'s bid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArbSignal {
+    pub symbol_id: u16,
+    pub buy_exchange: u8,
+    pub sell_exchange: u8,
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub size: f64,
+}
+
+/// Taker venues by exchange id, all trading the same instrument.
+#[derive(Default)]
+pub struct ExchangeRegistry {
+    adapters: HashMap<u8, Arc<dyn FundingVenue>>,
+}
+
+impl ExchangeRegistry {
+    pub fn register(&mut self, id: u8, adapter: Arc<dyn FundingVenue>) {
+        self.adapters.insert(id, adapter);
+    }
+
+    pub fn get(&self, id: u8) -> Option<&Arc<dyn FundingVenue>> {
+        self.adapters.get(&id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LegFill {
+    pub exchange_id: u8,
+    /// Average fill price (0 when nothing filled)
+    pub price: f64,
+    /// Executed quantity, before any flattening of the unmatched part
+    pub size: f64,
+    pub order: OrderResult,
+}
+
+impl LegFill {
+    fn new(exchange_id: u8, fill: TakeFill) -> Self {
+        Self {
+            exchange_id,
+            price: fill.avg_price,
+            size: fill.filled,
+            order: fill.order,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArbExecutionResult {
+    pub buy_fill: LegFill,
+    pub sell_fill: LegFill,
+    /// Quantity filled on both legs; any excess was flattened
+    pub matched_size: f64,
+    /// On `matched_size` at the actual fill prices, before fees
+    pub net_pnl_usd: f64,
+    pub latency_us: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum ArbExecutionError {
+    #[error("no venue registered for exchange {0}")]
+    UnknownExchange(u8),
+    /// The other leg (if it went through) has been flattened.
+    #[error("{leg} leg failed on exchange {exchange_id}: {error}")]
+    LegFailed {
+        leg: &'static str,
+        exchange_id: u8,
+        error: String,
+    },
+    /// The leg may have filled: the other leg is left open, not flattened.
+    #[error("{leg} leg outcome unknown on exchange {exchange_id}: {error}")]
+    Unresolved {
+        leg: &'static str,
+        exchange_id: u8,
+        error: String,
+    },
+}
+
+fn is_unknown_outcome(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<TradingError>(),
+        Some(TradingError::UnknownOutcome(_))
+    )
+}
+
+/// Submit both legs of `signal` concurrently as IOC limits at the signal
+/// prices. Both are awaited (not `try_join!`, which would drop the other
+/// request mid-flight) so a one-legged fill is known and can be flattened.
+/// The result reports what the venues actually executed.
+pub async fn execute_arbitrage(
+    registry: &ExchangeRegistry,
+    signal: &ArbSignal,
+) -> Result<ArbExecutionResult, ArbExecutionError> {
+    let venue = |id| {
+        registry
+            .get(id)
+            .ok_or(ArbExecutionError::UnknownExchange(id))
+    };
+    let (buy_venue, sell_venue) = (venue(signal.buy_exchange)?, venue(signal.sell_exchange)?);
+
+    let started = Instant::now();
+    let size = signal.size;
+    let (buy_res, sell_res) = futures::future::join(
+        buy_venue.take(Side::Buy, size, signal.buy_price, false),
+        sell_venue.take(Side::Sell, size, signal.sell_price, false),
+    )
+    .await;
+    let latency_us = started.elapsed().as_micros() as u64;
+
+    let concession = UNWIND_SLIPPAGE_BPS / 10_000.0;
+    let (buy_unwind_px, sell_unwind_px) = (
+        signal.buy_price * (1.0 - concession),
+        signal.sell_price * (1.0 + concession),
+    );
+    let unwind_buy = |qty| flatten(buy_venue, Side::Sell, qty, buy_unwind_px);
+    let unwind_sell = |qty| flatten(sell_venue, Side::Buy, qty, sell_unwind_px);
+    let hold = |leg, exchange_id, e: anyhow::Error| {
+        tracing::error!(
+            "🚨 ARB {} leg outcome unknown on x{}, other leg kept open for manual check: {}",
+            leg,
+            exchange_id,
+            e
+        );
+        ArbExecutionError::Unresolved {
+            leg,
+            exchange_id,
+            error: e.to_string(),
+        }
+    };
+    let (buy, sell) = match (buy_res, sell_res) {
+        (Ok(buy), Ok(sell)) => (buy, sell),
+        // Flattening the partner of a leg that may have filled would leave that leg naked
+        (Err(e), _) if is_unknown_outcome(&e) => {
+            return Err(hold("buy", signal.buy_exchange, e));
+        }
+        (_, Err(e)) if is_unknown_outcome(&e) => {
+            return Err(hold("sell", signal.sell_exchange, e));
+        }
+        (Ok(buy), Err(e)) => {
+            unwind_buy(buy.filled).await;
+            return Err(ArbExecutionError::LegFailed {
+                leg: "sell",
+                exchange_id: signal.sell_exchange,
+                error: e.to_string(),
+            });
+        }
+        (Err(e), sell_res) => {
+            if let Ok(sell) = sell_res {
+                unwind_sell(sell.filled).await;
+            }
+            return Err(ArbExecutionError::LegFailed {
+                leg: "buy",
+                exchange_id: signal.buy_exchange,
+                error: e.to_string(),
+            });
+        }
+    };
+
+    // IOCs can expire partly or wholly unfilled: only the common part is hedged
+    let matched = buy.filled.min(sell.filled);
+    unwind_buy(buy.filled - matched).await;
+    unwind_sell(sell.filled - matched).await;
+
+    let result = ArbExecutionResult {
+        net_pnl_usd: (sell.avg_price - buy.avg_price) * matched,
+        buy_fill: LegFill::new(signal.buy_exchange, buy),
+        sell_fill: LegFill::new(signal.sell_exchange, sell),
+        matched_size: matched,
+        latency_us,
+    };
+    if matched > 0.0 {
+        tracing::info!(
+            "💱 ARB executed sym={} {}/{} buy @{:.2} on {} / sell @{:.2} on {} | ${:+.4} | {}µs",
+            signal.symbol_id,
+            matched,
+            signal.size,
+            result.buy_fill.price,
+            buy_venue.name(),
+            result.sell_fill.price,
+            sell_venue.name(),
+            result.net_pnl_usd,
+            latency_us
+        );
+    } else {
+        tracing::info!(
+            "💨 ARB missed sym={}: filled {} on {} / {} on {} | {}µs",
+            signal.symbol_id,
+            result.buy_fill.size,
+            buy_venue.name(),
+            result.sell_fill.size,
+            sell_venue.name(),
+            latency_us
+        );
+    }
+    Ok(result)
+}
+
+/// Close `qty` of an unhedged leg with a reduce-only IOC; nothing to do when
+/// the leg executed nothing.
+async fn flatten(venue: &Arc<dyn FundingVenue>, side: Side, qty: f64, price: f64) {
+    if qty <= 0.0 {
+        return;
+    }
+    match venue.take(side, qty, price, true).await {
+        Ok(fill) if fill.filled < qty => tracing::error!(
+            "🚨 ARB unwind on {} closed {} of {}, rest still open",
+            venue.name(),
+            fill.filled,
+            qty
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::error!("🚨 ARB unwind of {} on {} failed: {}", qty, venue.name(), e),
+    }
+}
+
+/// Live execution of crosses on one symbol.
+struct Execution {
+    registry: Arc<ExchangeRegistry>,
+    symbol_id: u16,
+    max_size: f64,
+    /// One arb at a time; set while both legs (and any unwind) are in flight
+    in_flight: Arc<AtomicBool>,
+}
+
 pub struct ArbitrageEngine {
     _min_spread_bps: f64,
     min_spread_ratio: f64,
 
     // symbol_id -> [ShmBboMessage; 5 exchanges]
     bbo_state: std::collections::HashMap<u16, [ShmBboMessage; NUM_EXCHANGES]>,
+
+    // symbol_id -> feeder global sequence at which each exchange's BBO was read
+    seq_state: std::collections::HashMap<u16, [u64; NUM_EXCHANGES]>,
+    current_seq: u64,
+    max_sequence_gap: u64,
+    /// Quotes this much older than the symbol's newest are stale (0 = off)
+    max_quote_age_ns: u64,
+
+    // Symbols to scan; empty = all
+    allowed_symbols: HashSet<u16>,
+
+    // None = scan and log only
+    execution: Option<Execution>,
 }
 
 impl ArbitrageEngine {
@@ -49,9 +377,108 @@ impl ArbitrageEngine {
             _min_spread_bps: min_spread_bps,
             min_spread_ratio: min_spread_bps / 10_000.0,
             bbo_state: std::collections::HashMap::new(),
+            seq_state: std::collections::HashMap::new(),
+            current_seq: 0,
+            max_sequence_gap: DEFAULT_MAX_SEQUENCE_GAP,
+            max_quote_age_ns: 0,
+            allowed_symbols: HashSet::new(),
+            execution: None,
+        }
+    }
+
+    /// Only scan `allowed_symbols` (an empty list allows every symbol).
+    pub fn new_with_whitelist(min_spread_bps: f64, allowed_symbols: Vec<u16>) -> Self {
+        Self {
+            allowed_symbols: allowed_symbols.into_iter().collect(),
+            ..Self::new(min_spread_bps)
         }
     }
 
+    pub fn add_symbol(&mut self, id: u16) {
+        self.allowed_symbols.insert(id);
+    }
+
+    /// Removing the last whitelisted symbol reverts to scanning everything.
+    pub fn remove_symbol(&mut self, id: u16) {
+        self.allowed_symbols.remove(&id);
+        self.bbo_state.remove(&id);
+        self.seq_state.remove(&id);
+    }
+
+    #[inline(always)]
+    pub fn is_allowed(&self, symbol_id: u16) -> bool {
+        self.allowed_symbols.is_empty() || self.allowed_symbols.contains(&symbol_id)
+    }
+
+    pub fn with_max_sequence_gap(mut self, max_sequence_gap: u64) -> Self {
+        self.max_sequence_gap = max_sequence_gap;
+        self
+    }
+
+    /// Never trade a cross whose legs include a quote older than `max_age`
+    /// relative to the symbol's freshest exchange.
+    pub fn with_max_quote_age(mut self, max_age: Duration) -> Self {
+        self.max_quote_age_ns = max_age.as_nanos() as u64;
+        self
+    }
+
+    /// Execute crosses on `symbol_id` through `registry`, at most `max_size` per arb.
+    pub fn with_execution(
+        mut self,
+        registry: Arc<ExchangeRegistry>,
+        symbol_id: u16,
+        max_size: f64,
+    ) -> Self {
+        self.execution = Some(Execution {
+            registry,
+            symbol_id,
+            max_size,
+            in_flight: Arc::new(AtomicBool::new(false)),
+        });
+        self
+    }
+
+    /// Spawn `signal` onto the runtime unless another arb is still in flight.
+    fn dispatch(&self, signal: ArbSignal) {
+        let Some(exec) = &self.execution else {
+            return;
+        };
+        if signal.symbol_id != exec.symbol_id || signal.size <= 0.0 {
+            return;
+        }
+        // Venues we cannot trade on (e.g. feed-only exchanges) never claim the slot
+        let tradable = |id| exec.registry.get(id).is_some();
+        if !tradable(signal.buy_exchange) || !tradable(signal.sell_exchange) {
+            return;
+        }
+        let Ok(handle) = Handle::try_current() else {
+            return;
+        };
+        if exec.in_flight.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let registry = exec.registry.clone();
+        let in_flight = exec.in_flight.clone();
+        handle.spawn(async move {
+            if let Err(e) = execute_arbitrage(&registry, &signal).await {
+                tracing::warn!("⚠️ ARB execution failed: {}", e);
+            }
+            in_flight.store(false, Ordering::Release);
+        });
+    }
+
+    /// Global-sequence distance between two exchanges' last BBO for a symbol.
+    /// `None` if either side has no sequence (feeder without the counter).
+    pub fn sequence_gap(&self, symbol_id: u16, exch_a: u8, exch_b: u8) -> Option<u64> {
+        let seqs = self.seq_state.get(&symbol_id)?;
+        let a = *seqs.get(exch_a as usize)?;
+        let b = *seqs.get(exch_b as usize)?;
+        if a == 0 || b == 0 {
+            return None;
+        }
+        Some(a.abs_diff(b))
+    }
+
     fn sym_name(&self, symbol_id: u16) -> &'static str {
         match symbol_id {
             1001 => "BTC",
@@ -67,6 +494,9 @@ impl Strategy for ArbitrageEngine {
     }
 
     fn on_bbo_update(&mut self, symbol_id: u16, exchange_id: u8, bbo: &ShmBboMessage) {
+        if !self.is_allowed(symbol_id) {
+            return;
+        }
         let exchange_bbos = self
             .bbo_state
             .entry(symbol_id)
@@ -74,44 +504,56 @@ impl Strategy for ArbitrageEngine {
 
         if (exchange_id as usize) < NUM_EXCHANGES {
             exchange_bbos[exchange_id as usize] = *bbo;
+            self.seq_state
+                .entry(symbol_id)
+                .or_insert([0; NUM_EXCHANGES])[exchange_id as usize] = self.current_seq;
 
             // Re-evaluate global best
-            let mut best_bid_price = 0.0_f64;
-            let mut best_bid_size = 0.0_f64;
-            let mut best_bid_exchange = 0u8;
-            let mut best_ask_price = f64::MAX;
-            let mut best_ask_size = 0.0_f64;
-            let mut best_ask_exchange = 0u8;
-
-            for (exch_idx, msg) in exchange_bbos.iter().enumerate() {
-                let snap = BboSnapshot::from_shm(msg);
-                if !snap.is_valid() {
-                    continue;
-                }
+            let Some(meta) = find_global_best(exchange_bbos) else {
+                return;
+            };
+            let GlobalBest {
+                bid_price: best_bid_price,
+                bid_size: best_bid_size,
+                bid_exchange: best_bid_exchange,
+                ask_price: best_ask_price,
+                ask_size: best_ask_size,
+                ask_exchange: best_ask_exchange,
+                timestamp_ns: newest_ns,
+            } = meta.best;
 
-                if snap.bid_price > best_bid_price {
-                    best_bid_price = snap.bid_price;
-                    best_bid_size = snap.bid_size;
-                    best_bid_exchange = exch_idx as u8;
-                }
-
-                if snap.ask_price < best_ask_price {
-                    best_ask_price = snap.ask_price;
-                    best_ask_size = snap.ask_size;
-                    best_ask_exchange = exch_idx as u8;
-                }
-            }
-
-            if best_bid_price > 0.0
-                && best_ask_price < f64::MAX
-                && best_bid_exchange != best_ask_exchange
-                && best_bid_price > best_ask_price
-            {
+            if best_bid_exchange != best_ask_exchange && best_bid_price > best_ask_price {
                 let spread = best_bid_price - best_ask_price;
                 let mid = (best_bid_price + best_ask_price) * 0.5;
 
                 let spread_bps = (spread / mid) * 10_000.0;
 
+                let gap = self.sequence_gap(symbol_id, best_bid_exchange, best_ask_exchange);
+                let mut stale = gap.is_some_and(|gap| gap > self.max_sequence_gap);
+                if let Some(gap) = gap
+                    && stale
+                {
+                    tracing::warn!(
+                        "⚠️ {} stale cross-exchange comparison: x{} vs x{} are {} sequences apart (max {})",
+                        self.sym_name(symbol_id),
+                        best_bid_exchange,
+                        best_ask_exchange,
+                        gap,
+                        self.max_sequence_gap
+                    );
+                }
+                if self.max_quote_age_ns > 0 {
+                    let aged = meta.stale_exchanges(self.max_quote_age_ns, newest_ns);
+                    if aged.contains(&best_bid_exchange) || aged.contains(&best_ask_exchange) {
+                        tracing::warn!(
+                            "⚠️ {} cross involves stale quotes from x{:?}",
+                            self.sym_name(symbol_id),
+                            aged.as_slice()
+                        );
+                        stale = true;
+                    }
+                }
+
                 tracing::info!(
                     "📊 {} GBB={:.2}@x{} GBA={:.2}@x{} spread={:.2}bps",
                     self.sym_name(symbol_id),
@@ -134,12 +576,415 @@ impl Strategy for ArbitrageEngine {
                         exec_size,
                         spread_bps
                     );
+                    // Never trade a comparison between quotes read far apart
+                    if !stale && let Some(exec) = &self.execution {
+                        self.dispatch(ArbSignal {
+                            symbol_id,
+                            buy_exchange: best_ask_exchange,
+                            sell_exchange: best_bid_exchange,
+                            buy_price: best_ask_price,
+                            sell_price: best_bid_price,
+                            size: exec_size.min(exec.max_size),
+                        });
+                    }
                 }
             }
         }
     }
 
+    fn on_global_sequence(&mut self, seq: u64) {
+        self.current_seq = seq;
+    }
+
     fn on_idle(&mut self) {
         // No-op
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FundingRate;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+
+    /// Records (side, size, price, reduce_only) of every taker order.
+    /// Opening orders execute `fill` (default all of it) at `fill_price`
+    /// (default the limit); unwinds always fill in full.
+    #[derive(Default)]
+    struct TestVenue {
+        reject: bool,
+        unknown: bool,
+        fill: Option<f64>,
+        fill_price: Option<f64>,
+        orders: Mutex<Vec<(Side, f64, f64, bool)>>,
+    }
+
+    #[async_trait]
+    impl FundingVenue for TestVenue {
+        fn name(&self) -> &str {
+            "test"
+        }
+        async fn funding_rate(&self) -> anyhow::Result<FundingRate> {
+            unreachable!()
+        }
+        async fn equity(&self) -> anyhow::Result<f64> {
+            unreachable!()
+        }
+        async fn take(
+            &self,
+            side: Side,
+            size: f64,
+            price: f64,
+            reduce_only: bool,
+        ) -> anyhow::Result<TakeFill> {
+            if self.reject && !reduce_only {
+                anyhow::bail!("rejected");
+            }
+            if self.unknown && !reduce_only {
+                self.orders.lock().push((side, size, price, reduce_only));
+                return Err(TradingError::UnknownOutcome("no status".to_string()).into());
+            }
+            self.orders.lock().push((side, size, price, reduce_only));
+            let (filled, avg_price) = match reduce_only {
+                true => (size, price),
+                false => (
+                    self.fill.unwrap_or(size).min(size),
+                    self.fill_price.unwrap_or(price),
+                ),
+            };
+            Ok(TakeFill {
+                order: OrderResult {
+                    tx_hash: "t".to_string(),
+                    client_order_index: 0,
+                },
+                filled,
+                avg_price: if filled > 0.0 { avg_price } else { 0.0 },
+            })
+        }
+    }
+
+    fn registry(a: &Arc<TestVenue>, b: &Arc<TestVenue>) -> ExchangeRegistry {
+        let mut registry = ExchangeRegistry::default();
+        registry.register(3, a.clone());
+        registry.register(4, b.clone());
+        registry
+    }
+
+    const SIGNAL: ArbSignal = ArbSignal {
+        symbol_id: 1002,
+        buy_exchange: 3,
+        sell_exchange: 4,
+        buy_price: 3000.0,
+        sell_price: 3012.0,
+        size: 0.5,
+    };
+
+    fn bbo(bid: f64, ask: f64) -> ShmBboMessage {
+        ShmBboMessage {
+            bid_price: bid,
+            bid_size: 1.0,
+            ask_price: ask,
+            ask_size: 1.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sequence_gap_tracks_read_sequence_per_exchange() {
+        let mut engine = ArbitrageEngine::new(25.0).with_max_sequence_gap(10);
+
+        engine.on_global_sequence(100);
+        engine.on_bbo_update(1002, 1, &bbo(3000.0, 3001.0));
+        engine.on_global_sequence(250);
+        engine.on_bbo_update(1002, 3, &bbo(3002.0, 3003.0));
+
+        assert_eq!(engine.sequence_gap(1002, 1, 3), Some(150));
+        assert_eq!(engine.sequence_gap(1002, 1, 2), None);
+        assert_eq!(engine.sequence_gap(1001, 1, 3), None);
+    }
+
+    #[test]
+    fn test_global_best_reports_stale_exchanges() {
+        let mut bbos = [ShmBboMessage::default(); NUM_EXCHANGES];
+        assert!(find_global_best(&bbos).is_none());
+
+        let quote = |bid, ask, timestamp_ns| ShmBboMessage {
+            timestamp_ns,
+            ..bbo(bid, ask)
+        };
+        bbos[1] = quote(3000.0, 3001.0, 40_000_000_000);
+        bbos[3] = quote(3005.0, 3006.0, 10_000_000_000);
+        bbos[4] = quote(2998.0, 2999.0, 39_000_000_000);
+        bbos[2] = quote(3001.0, 3000.0, 40_000_000_000); // crossed: ignored
+        let meta = find_global_best(&bbos).unwrap();
+        assert_eq!((meta.best.bid_exchange, meta.best.ask_exchange), (3, 4));
+        assert_eq!(meta.best.timestamp_ns, 40_000_000_000);
+        assert_eq!(
+            meta.per_exchange_ts,
+            [0, 40_000_000_000, 0, 10_000_000_000, 39_000_000_000]
+        );
+
+        let now = meta.best.timestamp_ns;
+        assert_eq!(meta.stale_exchanges(5_000_000_000, now).as_slice(), &[3]);
+        assert_eq!(meta.stale_exchanges(500_000_000, now).as_slice(), &[3, 4]);
+        assert!(meta.stale_exchanges(60_000_000_000, now).is_empty());
+    }
+
+    #[test]
+    fn test_whitelist_skips_other_symbols() {
+        let mut engine = ArbitrageEngine::new_with_whitelist(25.0, vec![1002]);
+        engine.on_global_sequence(1);
+        engine.on_bbo_update(1001, 1, &bbo(60000.0, 60001.0));
+        engine.on_bbo_update(1002, 1, &bbo(3000.0, 3001.0));
+        assert!(!engine.bbo_state.contains_key(&1001));
+        assert!(engine.bbo_state.contains_key(&1002));
+
+        engine.add_symbol(1001);
+        engine.on_bbo_update(1001, 1, &bbo(60000.0, 60001.0));
+        assert!(engine.bbo_state.contains_key(&1001));
+
+        // Empty whitelist allows everything
+        engine.remove_symbol(1001);
+        engine.remove_symbol(1002);
+        assert!(engine.is_allowed(4242));
+        assert!(ArbitrageEngine::new(25.0).is_allowed(4242));
+    }
+
+    #[tokio::test]
+    async fn test_execute_both_legs_and_flatten_on_one_sided_fill() {
+        let (a, b) = (
+            Arc::new(TestVenue::default()),
+            Arc::new(TestVenue::default()),
+        );
+        let result = execute_arbitrage(&registry(&a, &b), &SIGNAL).await.unwrap();
+        assert_eq!(result.net_pnl_usd, 6.0);
+        assert_eq!(
+            (result.buy_fill.exchange_id, result.sell_fill.exchange_id),
+            (3, 4)
+        );
+        assert_eq!(*a.orders.lock(), vec![(Side::Buy, 0.5, 3000.0, false)]);
+        assert_eq!(*b.orders.lock(), vec![(Side::Sell, 0.5, 3012.0, false)]);
+
+        // Sell leg rejected: the bought leg is sold back reduce-only
+        let a = Arc::new(TestVenue::default());
+        let b = Arc::new(TestVenue {
+            reject: true,
+            ..Default::default()
+        });
+        let err = execute_arbitrage(&registry(&a, &b), &SIGNAL)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ArbExecutionError::LegFailed {
+                leg: "sell",
+                exchange_id: 4,
+                ..
+            }
+        ));
+        let orders = a.orders.lock().clone();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(
+            (orders[1].0, orders[1].1, orders[1].3),
+            (Side::Sell, 0.5, true)
+        );
+        assert!(orders[1].2 < 3000.0);
+
+        let unknown = ArbSignal {
+            sell_exchange: 7,
+            ..SIGNAL
+        };
+        let err = execute_arbitrage(&registry(&a, &b), &unknown)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ArbExecutionError::UnknownExchange(7)));
+
+        // Sell leg rejected and the buy IOC expired unfilled: nothing to unwind
+        let a = Arc::new(TestVenue {
+            fill: Some(0.0),
+            ..Default::default()
+        });
+        let b = Arc::new(TestVenue {
+            reject: true,
+            ..Default::default()
+        });
+        execute_arbitrage(&registry(&a, &b), &SIGNAL)
+            .await
+            .unwrap_err();
+        assert_eq!(*a.orders.lock(), vec![(Side::Buy, 0.5, 3000.0, false)]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_holds_partner_when_a_leg_outcome_is_unknown() {
+        let a = Arc::new(TestVenue {
+            unknown: true,
+            ..Default::default()
+        });
+        let b = Arc::new(TestVenue::default());
+        let err = execute_arbitrage(&registry(&a, &b), &SIGNAL)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ArbExecutionError::Unresolved {
+                leg: "buy",
+                exchange_id: 3,
+                ..
+            }
+        ));
+        // The filled sell leg is not bought back
+        assert_eq!(*b.orders.lock(), vec![(Side::Sell, 0.5, 3012.0, false)]);
+
+        // Also when the partner failed outright: the unknown leg is the one reported
+        let a = Arc::new(TestVenue {
+            reject: true,
+            ..Default::default()
+        });
+        let b = Arc::new(TestVenue {
+            unknown: true,
+            ..Default::default()
+        });
+        let err = execute_arbitrage(&registry(&a, &b), &SIGNAL)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ArbExecutionError::Unresolved { leg: "sell", .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_actual_fills_and_flattens_the_excess() {
+        // Buy leg fills 0.2 of 0.5 with price improvement, sell leg in full
+        let a = Arc::new(TestVenue {
+            fill: Some(0.2),
+            fill_price: Some(2999.0),
+            ..Default::default()
+        });
+        let b = Arc::new(TestVenue::default());
+        let result = execute_arbitrage(&registry(&a, &b), &SIGNAL).await.unwrap();
+        assert_eq!((result.buy_fill.size, result.buy_fill.price), (0.2, 2999.0));
+        assert_eq!(
+            (result.sell_fill.size, result.sell_fill.price),
+            (0.5, 3012.0)
+        );
+        assert_eq!(result.matched_size, 0.2);
+        assert!((result.net_pnl_usd - 13.0 * 0.2).abs() < 1e-9);
+
+        // Only the 0.3 the buy leg did not match is bought back
+        assert_eq!(a.orders.lock().len(), 1);
+        let orders = b.orders.lock().clone();
+        assert_eq!(orders.len(), 2);
+        assert_eq!((orders[1].0, orders[1].3), (Side::Buy, true));
+        assert!((orders[1].1 - 0.3).abs() < 1e-9);
+        assert!(orders[1].2 > 3012.0);
+
+        // Both IOCs expired: a miss, no PnL and no unwinds
+        let a = Arc::new(TestVenue {
+            fill: Some(0.0),
+            ..Default::default()
+        });
+        let b = Arc::new(TestVenue {
+            fill: Some(0.0),
+            ..Default::default()
+        });
+        let result = execute_arbitrage(&registry(&a, &b), &SIGNAL).await.unwrap();
+        assert_eq!((result.matched_size, result.net_pnl_usd), (0.0, 0.0));
+        assert_eq!(a.orders.lock().len() + b.orders.lock().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_engine_skips_cross_with_stale_exchange() {
+        let (a, b) = (
+            Arc::new(TestVenue::default()),
+            Arc::new(TestVenue::default()),
+        );
+        let mut engine = ArbitrageEngine::new(25.0)
+            .with_max_quote_age(Duration::from_secs(1))
+            .with_execution(Arc::new(registry(&a, &b)), 1002, 0.3);
+        let quote = |bid, ask, secs: u64| ShmBboMessage {
+            timestamp_ns: secs * 1_000_000_000,
+            ..bbo(bid, ask)
+        };
+
+        // EdgeX last quoted 29s before the crossing update
+        engine.on_bbo_update(1002, 3, &quote(2999.0, 3000.0, 1));
+        engine.on_bbo_update(1002, 4, &quote(3012.0, 3013.0, 30));
+        tokio::task::yield_now().await;
+        assert!(a.orders.lock().is_empty() && b.orders.lock().is_empty());
+
+        engine.on_bbo_update(1002, 3, &quote(2999.0, 3000.0, 30));
+        for _ in 0..100 {
+            if !b.orders.lock().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(*a.orders.lock(), vec![(Side::Buy, 0.3, 3000.0, false)]);
+        assert_eq!(*b.orders.lock(), vec![(Side::Sell, 0.3, 3012.0, false)]);
+    }
+
+    #[tokio::test]
+    async fn test_engine_executes_fresh_cross_on_traded_symbol() {
+        let (a, b) = (
+            Arc::new(TestVenue::default()),
+            Arc::new(TestVenue::default()),
+        );
+        let mut engine = ArbitrageEngine::new(25.0)
+            .with_max_sequence_gap(10)
+            .with_execution(Arc::new(registry(&a, &b)), 1002, 0.3);
+
+        // Stale: the two quotes were read 50 sequences apart
+        engine.on_global_sequence(100);
+        engine.on_bbo_update(1002, 3, &bbo(2999.0, 3000.0));
+        engine.on_global_sequence(150);
+        engine.on_bbo_update(1002, 4, &bbo(3012.0, 3013.0));
+        // Other symbol
+        engine.on_bbo_update(1001, 3, &bbo(59_999.0, 60_000.0));
+        engine.on_bbo_update(1001, 4, &bbo(60_300.0, 60_301.0));
+        tokio::task::yield_now().await;
+        assert!(a.orders.lock().is_empty() && b.orders.lock().is_empty());
+
+        engine.on_global_sequence(152);
+        engine.on_bbo_update(1002, 3, &bbo(2999.0, 3000.0));
+        for _ in 0..100 {
+            if !b.orders.lock().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+        // Capped at max_size
+        assert_eq!(*a.orders.lock(), vec![(Side::Buy, 0.3, 3000.0, false)]);
+        assert_eq!(*b.orders.lock(), vec![(Side::Sell, 0.3, 3012.0, false)]);
+    }
+
+    #[tokio::test]
+    async fn test_engine_ignores_cross_on_unregistered_venue() {
+        let (a, b) = (
+            Arc::new(TestVenue::default()),
+            Arc::new(TestVenue::default()),
+        );
+        let mut engine =
+            ArbitrageEngine::new(25.0).with_execution(Arc::new(registry(&a, &b)), 1002, 0.3);
+
+        // Best ask on exchange 1, which has no venue
+        engine.on_bbo_update(1002, 1, &bbo(2999.0, 3000.0));
+        engine.on_bbo_update(1002, 4, &bbo(3012.0, 3013.0));
+        tokio::task::yield_now().await;
+        let in_flight = &engine.execution.as_ref().unwrap().in_flight;
+        assert!(!in_flight.load(Ordering::Acquire));
+        assert!(b.orders.lock().is_empty());
+
+        // A tradable cross still goes through
+        engine.on_bbo_update(1002, 3, &bbo(2998.0, 2999.0));
+        for _ in 0..100 {
+            if !b.orders.lock().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(*a.orders.lock(), vec![(Side::Buy, 0.3, 2999.0, false)]);
+        assert_eq!(*b.orders.lock(), vec![(Side::Sell, 0.3, 3012.0, false)]);
+    }
+}
@@ -1,18 +1,51 @@
-use crate::backpack_api::client::BackpackClient;
+use crate::backpack_api::client::{BackpackClient, BackpackError};
 use crate::backpack_api::model::*;
-use crate::config::ExchangeConfig;
+use crate::config::{ExchangeConfig, format_price, format_size};
+use crate::exchange_state::ExchangeStateCache;
+use crate::order_builder::{OrderBuilder, OrderFilters};
+use crate::shm_depth_reader::ShmDepthReader;
 use crate::shm_reader::ShmBboMessage;
 use crate::strategy::Strategy;
+use crate::strategy::dry_run::DryRun;
+use crate::strategy::execution_metrics::{ExecutionMetrics, ExecutionTracker, RestingQuote};
+use crate::strategy::markout::{Defense, MarkoutTracker};
+use crate::strategy::order_age::OrderAges;
+use crate::strategy::protective_stop::{ProtectiveStop, plan_stop, sync_protective_stop};
+use crate::strategy::quote_board::{QuoteBoard, StpDecision};
+use crate::strategy::quoting::{
+    QuoteInputs, QuoteLevel, compute_quotes, depth_imbalance, quote_levels,
+};
+use crate::strategy::reprice::{LiveBbo, REPRICE_LOG_INTERVAL, RepriceCounter};
+use crate::strategy::state::MmState;
+use crate::strategy::volatility::Volatility;
+use crate::types::MarginSummary;
+use parking_lot::Mutex;
 use std::collections::VecDeque;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::pin::Pin;
 use tokio::runtime::Handle;
 use tracing::{error, info, warn};
 
+/// Window of order history summarized by the periodic health log
+const ORDER_HEALTH_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Owner tag of our quotes on the shared `QuoteBoard`
+const STP_OWNER: &str = "backpack_mm";
+
+/// Order IDs of the quotes currently resting on the book (set by the quote task)
+#[derive(Debug, Default)]
+struct LiveQuotes {
+    bid: Option<String>,
+    ask: Option<String>,
+    // Every level, amended or not, for cfg.max_order_age_secs
+    ages: OrderAges<String>,
+}
+
 pub struct BackpackMMStrategy {
     exchange_id: u8,
     symbol_id: u16,
+    symbol_name: String,
     cfg: ExchangeConfig,
     api_client: Option<Arc<BackpackClient>>,
 
@@ -23,93 +56,137 @@ pub struct BackpackMMStrategy {
 
     // Volatility ring buffer
     mid_history: VecDeque<f64>,
+    vol: Volatility,
+
+    // Resting quote IDs, amended in place when the mid barely moved
+    live_quotes: Arc<Mutex<LiveQuotes>>,
+
+    // Simulated order routing (cfg.dry_run)
+    dry_run: Option<Arc<DryRun>>,
+
+    // Equity / funding shared by every strategy on this Backpack account
+    account: Arc<ExchangeStateCache>,
+    account_version: u64,
+
+    // Post-fill markout (adverse selection defense), clocked from `clock`
+    markout: Arc<Mutex<MarkoutTracker>>,
+    clock: Instant,
+    quoting_paused: bool,
+
+    // L2 depth for book-imbalance shading (only opened when imbalance_weight > 0)
+    depth_reader: Option<ShmDepthReader>,
+
+    // Last post-only rejection health log
+    last_order_health: Instant,
+
+    // Fresh BBO for repricing post-only rejects, and how often that happens
+    live_bbo: Option<Arc<LiveBbo>>,
+    reprices: Arc<RepriceCounter>,
+
+    // Fill rate / slippage, updated by the quote task; snapshot copied each on_idle
+    execution: Arc<Mutex<ExecutionTracker>>,
+    exec_metrics: ExecutionMetrics,
+
+    // Venue-side reduce-only stop behind the position (cfg.protective_stop_bps)
+    protective_stop: Arc<Mutex<Option<ProtectiveStop>>>,
 
     // Dynamic balance-based limits (refreshed periodically)
     max_position: f64,
     base_size: f64,
     stop_loss_usd: f64,
-    last_balance_refresh: Option<Instant>,
     account_equity_usdc: f64,
+    // Free margin caps the exposure-adding quote (None until first fetch)
+    margin: Option<MarginSummary>,
 }
 
 impl BackpackMMStrategy {
+    /// Quotes `symbol_name` on the venue for SHM `symbol_id`. `api_client` and
+    /// `account` are shared with the rest of the process (`None` = quoting disabled).
     pub fn new(
         exchange_id: u8,
         symbol_id: u16,
+        symbol_name: String,
         _half_spread_bps: f64,
         cfg: ExchangeConfig,
+        api_client: Option<Arc<BackpackClient>>,
+        account: Arc<ExchangeStateCache>,
     ) -> Self {
-        let env_path = std::env::var("BACKPACK_ENV_PATH").unwrap_or_else(|_| {
-            "/home/metaverse/.openclaw/workspace/aleph-tx/.env.backpack".to_string()
-        });
-        let env_str = std::fs::read_to_string(&env_path).unwrap_or_default();
-        let mut api_key = String::new();
-        let mut api_secret = String::new();
-
-        for line in env_str.lines() {
-            if let Some(rest) = line.strip_prefix("BACKPACK_PUBLIC_KEY=") {
-                api_key = rest.trim().to_string();
-            }
-            if let Some(rest) = line.strip_prefix("BACKPACK_SECRET_KEY=") {
-                api_secret = rest.trim().to_string();
-            }
+        if let (Some(leverage), Some(client)) = (cfg.target_leverage, api_client.clone())
+            && let Ok(handle) = Handle::try_current()
+        {
+            let symbol = symbol_name.clone();
+            handle.spawn(async move {
+                let result = match client.get_contract_spec(&symbol).await {
+                    Ok(spec) => client.set_leverage(&symbol, leverage, &spec).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => info!("⚙️ [BP] Leverage set to {}x on {}", leverage, symbol),
+                    Err(e) => error!("⚙️ [BP] set_leverage({}x) failed: {:?}", leverage, e),
+                }
+            });
         }
 
-        let api_client = if !api_key.is_empty() && !api_secret.is_empty() {
-            match BackpackClient::new(&api_key, &api_secret, "https://api.backpack.exchange") {
-                Ok(client) => {
-                    info!("🎒 Loaded Backpack API Client (v3 — dynamic allocation)");
-                    Some(Arc::new(client))
-                }
-                Err(e) => {
-                    warn!("Failed to init Backpack Client: {}", e);
-                    None
-                }
+        let depth_reader = if cfg.imbalance_weight > 0.0 {
+            let reader = ShmDepthReader::open("/dev/shm/aleph-depth", 2048).ok();
+            if reader.is_none() {
+                warn!("📊 [BP] imbalance_weight set but depth reader not available");
             }
+            reader
         } else {
             None
         };
 
         let vol_window = cfg.vol_window;
+        let vol = Volatility::from_config(&cfg);
+        let markout = Arc::new(Mutex::new(MarkoutTracker::from_config(&cfg)));
+        let dry_run = DryRun::from_config("BP", &cfg);
+        if dry_run.is_some() {
+            warn!("🧪 [BP] DRY RUN — orders are logged and simulated, nothing is sent");
+        }
+        account.watch(
+            &symbol_name,
+            cfg.funding_skew_weight > 0.0 || cfg.funding_window_widen_bps > 0.0,
+        );
         Self {
             exchange_id,
             symbol_id,
+            symbol_name,
             cfg,
             api_client,
             last_mid: 0.0,
             last_quoted_mid: 0.0,
             last_update: None,
             mid_history: VecDeque::with_capacity(vol_window + 1),
+            vol,
+            live_quotes: Arc::new(Mutex::new(LiveQuotes::default())),
+            dry_run,
+            account,
+            account_version: 0,
+            markout,
+            clock: Instant::now(),
+            quoting_paused: false,
+            depth_reader,
+            last_order_health: Instant::now(),
+            live_bbo: LiveBbo::open(symbol_id, exchange_id),
+            reprices: Arc::new(RepriceCounter::default()),
+            execution: Arc::new(Mutex::new(ExecutionTracker::default())),
+            exec_metrics: ExecutionMetrics::default(),
+            protective_stop: Arc::new(Mutex::new(None)),
             max_position: 0.3,  // will be overwritten by balance fetch
             base_size: 0.05,    // will be overwritten
             stop_loss_usd: 5.0, // will be overwritten
-            last_balance_refresh: None,
             account_equity_usdc: 0.0,
+            margin: None,
         }
     }
 
     fn symbol_name(&self) -> &str {
-        if self.symbol_id == 1001 {
-            "BTC_USDC_PERP"
-        } else {
-            "ETH_USDC_PERP"
-        }
+        &self.symbol_name
     }
 
     fn realized_vol_bps(&self) -> f64 {
-        if self.mid_history.len() < 10 {
-            return 20.0;
-        }
-        let returns: Vec<f64> = self
-            .mid_history
-            .iter()
-            .zip(self.mid_history.iter().skip(1))
-            .map(|(prev, cur)| ((cur - prev) / prev) * 10_000.0)
-            .collect();
-        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
-        let variance =
-            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
-        variance.sqrt()
+        self.vol.vol_bps().unwrap_or(20.0)
     }
 
     fn momentum_bps(&self) -> f64 {
@@ -121,51 +198,111 @@ impl BackpackMMStrategy {
         (recent - lookback) / lookback * 10_000.0
     }
 
-    /// Refresh account balance and recompute dynamic limits
-    fn maybe_refresh_balance(&mut self) {
-        let should_refresh = match self.last_balance_refresh {
-            None => true,
-            Some(last) => last.elapsed() > Duration::from_secs(self.cfg.balance_refresh_secs),
+    /// Book imbalance within `imbalance_depth_bps` of mid (0 if disabled/unavailable)
+    fn book_imbalance(&self) -> f64 {
+        self.depth_reader
+            .as_ref()
+            .and_then(|r| r.read_depth(self.symbol_id, self.exchange_id))
+            .map(|d| depth_imbalance(&d, self.cfg.imbalance_depth_bps))
+            .unwrap_or(0.0)
+    }
+
+    /// (rate, seconds to settlement) from the cached funding; unknown once settlement passed
+    fn funding_inputs(&self) -> (f64, Option<f64>) {
+        let Some(funding) = self.account.funding(self.symbol_name()) else {
+            return (0.0, None);
         };
-        if !should_refresh {
-            return;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let secs = (funding.next_funding_ms as i64 - now_ms) as f64 / 1000.0;
+        if secs < 0.0 {
+            return (0.0, None);
         }
-        if self.last_mid <= 0.0 {
+        (funding.rate, Some(secs))
+    }
+
+    /// Leverage the free-margin cap assumes (1x unless `target_leverage` is set)
+    fn leverage(&self) -> f64 {
+        self.cfg.target_leverage.unwrap_or(1) as f64
+    }
+
+    /// Largest exposure-adding quote the free margin allows (unbounded until known)
+    fn margin_cap(&self) -> f64 {
+        self.margin.map_or(f64::MAX, |m| {
+            m.max_order_size(self.last_mid, self.leverage())
+        })
+    }
+
+    /// Recompute dynamic limits when the shared cache published new margin totals
+    fn apply_balance(&mut self) {
+        let version = self.account.version();
+        if version == self.account_version || self.last_mid <= 0.0 {
             return;
         }
+        self.account_version = version;
 
-        if let Some(client) = &self.api_client {
-            let client_arc = client.clone();
-            let mid = self.last_mid;
-            let risk_fraction = self.cfg.risk_fraction;
-            let stop_pct = self.cfg.stop_loss_pct;
-
-            // Synchronous block_on for balance fetch (cold path, every 60s)
-            if let Ok(handle) = Handle::try_current() {
-                let result = tokio::task::block_in_place(|| {
-                    handle.block_on(async { client_arc.get_total_equity().await })
-                });
-                if let Ok(equity) = result {
-                    if equity > 0.0 {
-                        self.account_equity_usdc = equity;
-                        let risk_usd = equity * risk_fraction;
-                        self.max_position = risk_usd / mid;
-                        self.base_size = (self.max_position / 3.0).max(0.01);
-                        self.stop_loss_usd = equity * stop_pct * 10.0;
-                        self.last_balance_refresh = Some(Instant::now());
+        // Net equity includes unrealized PnL and collateral backing open positions
+        let margin = self.account.margin();
+        let equity = margin.net_equity;
+        self.margin = Some(margin);
+        self.account_equity_usdc = equity;
+        let risk_usd = equity * self.cfg.risk_fraction;
+        self.max_position = risk_usd / self.last_mid;
+        self.base_size = (self.max_position / 3.0).max(0.01);
+        self.stop_loss_usd = equity * self.cfg.stop_loss_pct * 10.0;
 
+        // Base asset of e.g. `ETH_USDC_PERP`
+        let base = self.symbol_name.split('_').next().unwrap_or_default();
+        info!(
+            "💰 [BP] Equity: ${:.2} (used ${:.2}, free ${:.2}) | MaxPos: {:.4} {} | \
+             BaseSize: {:.4} | MarginCap: {:.4} | StopLoss: ${:.2} | \
+             FillRate: {:.1}% ({}/{}) | AvgSlip: {:+.2}bps",
+            equity,
+            margin.used_margin,
+            margin.available_margin,
+            self.max_position,
+            base,
+            self.base_size,
+            self.margin_cap(),
+            self.stop_loss_usd,
+            self.exec_metrics.fill_rate() * 100.0,
+            self.exec_metrics.orders_filled,
+            self.exec_metrics.orders_submitted,
+            self.exec_metrics.avg_slippage_bps()
+        );
+    }
+
+    /// Every [`ORDER_HEALTH_INTERVAL`], log how often post-only quotes were
+    /// rejected for crossing (high rates mean the spread is too tight for our
+    /// quote latency).
+    fn log_order_health(&mut self) {
+        if self.dry_run.is_some() || self.last_order_health.elapsed() < ORDER_HEALTH_INTERVAL {
+            return;
+        }
+        self.last_order_health = Instant::now();
+        let (Some(client), Ok(handle)) = (self.api_client.clone(), Handle::try_current()) else {
+            return;
+        };
+        let symbol = self.symbol_name().to_string();
+        handle.spawn(async move {
+            let end_ms = chrono::Utc::now().timestamp_millis() as u64;
+            let start_ms = end_ms.saturating_sub(ORDER_HEALTH_INTERVAL.as_millis() as u64);
+            match client
+                .get_trade_history(Some(&symbol), start_ms, end_ms)
+                .await
+            {
+                Ok(orders) => {
+                    if let Some(rate) = post_only_rejection_rate(&orders) {
                         info!(
-                            "💰 [BP] Balance: ${:.2} | MaxPos: {:.4} ETH | BaseSize: {:.4} | StopLoss: ${:.2}",
-                            equity, self.max_position, self.base_size, self.stop_loss_usd
+                            "🩺 [BP] post_only_rejection_rate={:.1}% ({} orders, last {}s)",
+                            rate * 100.0,
+                            orders.len(),
+                            ORDER_HEALTH_INTERVAL.as_secs()
                         );
-                    } else {
-                        // Even with $0, set the refresh time to avoid hammering the API
-                        self.last_balance_refresh = Some(Instant::now());
-                        info!("💰 [BP] Balance: $0.00 (no collateral or spot USDC found)");
                     }
                 }
+                Err(e) => warn!("⚠️ [BP] Order history err: {}", e),
             }
-        }
+        });
     }
 }
 
@@ -180,6 +317,10 @@ impl Strategy for BackpackMMStrategy {
         }
         if bbo.bid_price > 0.0 && bbo.ask_price > 0.0 {
             self.last_mid = (bbo.bid_price + bbo.ask_price) / 2.0;
+            self.vol.update(self.last_mid, bbo.timestamp_ns);
+            self.markout
+                .lock()
+                .on_mid(self.clock.elapsed().as_secs_f64(), self.last_mid);
             self.mid_history.push_back(self.last_mid);
             if self.mid_history.len() > self.cfg.vol_window {
                 self.mid_history.pop_front();
@@ -192,65 +333,174 @@ impl Strategy for BackpackMMStrategy {
             return;
         }
 
-        // Periodically refresh balance
-        self.maybe_refresh_balance();
+        // Pick up the latest execution stats and balance
+        self.exec_metrics = self.execution.lock().metrics();
+        self.apply_balance();
+        self.log_order_health();
+        if let Some(n) = self.reprices.take_if_elapsed(REPRICE_LOG_INTERVAL) {
+            info!("🔁 [BP] Post-only reprices: {} in the last hour", n);
+        }
 
         let now = Instant::now();
-        let should_update = match self.last_update {
-            None => true,
-            Some(last) => {
-                let elapsed = now.duration_since(last);
-                if elapsed < Duration::from_millis(self.cfg.requote_interval_ms) {
-                    false
-                } else {
-                    let time_trigger = elapsed > Duration::from_secs(5);
-                    let price_trigger = if self.last_quoted_mid > 0.0 {
-                        let dev = (self.last_mid - self.last_quoted_mid).abs()
-                            / self.last_quoted_mid
-                            * 10_000.0;
-                        dev > 8.0
-                    } else {
-                        false
-                    };
-                    time_trigger || price_trigger
-                }
+        let max_age = Duration::from_secs(self.cfg.max_order_age_secs);
+        let expired = {
+            // Cleared here so the requote below fires once, not every poll until it lands
+            let mut live = self.live_quotes.lock();
+            let expired = live.ages.expired(now, max_age);
+            if expired {
+                live.ages.clear();
             }
+            expired
         };
+        if expired {
+            info!(
+                "⌛ [BP] Quotes older than {}s — cancelling and requoting",
+                max_age.as_secs()
+            );
+        }
+        let should_update = expired
+            || match self.last_update {
+                None => true,
+                Some(last) => {
+                    let elapsed = now.duration_since(last);
+                    if elapsed < Duration::from_millis(self.cfg.requote_interval_ms) {
+                        false
+                    } else {
+                        let time_trigger =
+                            elapsed > Duration::from_secs(self.cfg.max_quote_age_secs);
+                        let price_trigger = if self.last_quoted_mid > 0.0 {
+                            let dev = (self.last_mid - self.last_quoted_mid).abs()
+                                / self.last_quoted_mid
+                                * 10_000.0;
+                            dev > self.cfg.price_change_threshold_bps
+                        } else {
+                            false
+                        };
+                        time_trigger || price_trigger
+                    }
+                }
+            };
 
         if should_update {
+            let quote_drift_bps = if self.last_quoted_mid > 0.0 {
+                (self.last_mid - self.last_quoted_mid).abs() / self.last_quoted_mid * 10_000.0
+            } else {
+                f64::MAX
+            };
             self.last_update = Some(now);
             self.last_quoted_mid = self.last_mid;
 
-            if let Some(client) = &self.api_client {
+            let defense = self
+                .markout
+                .lock()
+                .defense(self.clock.elapsed().as_secs_f64());
+            if let Defense::Pause { remaining_secs } = defense {
+                if !self.quoting_paused {
+                    warn!(
+                        "☣️ [BP] Toxic flow — pausing quotes for {:.0}s | Markout {}",
+                        remaining_secs,
+                        self.markout.lock().summary()
+                    );
+                    self.quoting_paused = true;
+                    if let Some(sim) = &self.dry_run {
+                        sim.cancel_all();
+                        self.execution.lock().on_cancel_confirmed();
+                    } else if let (Some(client), Ok(handle)) =
+                        (self.api_client.clone(), Handle::try_current())
+                    {
+                        let symbol_name = self.symbol_name().to_string();
+                        let exchange_id = self.exchange_id;
+                        let live_quotes = self.live_quotes.clone();
+                        let execution = self.execution.clone();
+                        handle.spawn(async move {
+                            match client.cancel_resting_orders(&symbol_name).await {
+                                Ok(_) => execution.lock().on_cancel_confirmed(),
+                                Err(e) => warn!("⚠️ [BP-v3] Cancel error: {:?}", e),
+                            }
+                            *live_quotes.lock() = LiveQuotes::default();
+                            QuoteBoard::global().clear(exchange_id, &symbol_name, STP_OWNER);
+                        });
+                    }
+                }
+                return;
+            }
+            if self.quoting_paused {
+                info!("☣️ [BP] Markout cool-off over — resuming quotes");
+                self.quoting_paused = false;
+            }
+
+            // Dry runs go through the whole cycle without credentials; only the
+            // REST calls below need a client
+            if self.api_client.is_some() || self.dry_run.is_some() {
                 let mid_price = self.last_mid;
-                let client_arc = client.clone();
+                let client_arc = self.api_client.clone();
                 let symbol_name = self.symbol_name().to_string();
+                let exchange_id = self.exchange_id;
                 let cfg = self.cfg.clone();
 
                 let vol_bps = self.realized_vol_bps();
                 let momentum = self.momentum_bps();
+                let imbalance = self.book_imbalance();
+                let (funding_rate, secs_to_funding) = self.funding_inputs();
                 let max_position = self.max_position;
                 let base_size = self.base_size;
                 let stop_loss_usd = self.stop_loss_usd;
+                let margin_cap = self.margin_cap();
+                let live_quotes = self.live_quotes.clone();
+                let markout = self.markout.clone();
+                let clock = self.clock;
+                let dry_run = self.dry_run.clone();
+                let live_bbo = self.live_bbo.clone();
+                let reprices = self.reprices.clone();
+                let execution = self.execution.clone();
+                let protective_stop = self.protective_stop.clone();
 
                 if let Ok(handle) = Handle::try_current() {
                     handle.spawn(async move {
                         // 1. Fetch live positions (with entry price)
                         let mut live_pos: f64 = 0.0;
                         let mut entry_price: f64 = 0.0;
-                        match client_arc.get_open_positions().await {
-                            Ok(positions) => {
-                                for pos in positions {
-                                    if pos.symbol == symbol_name {
-                                        live_pos = pos.quantity.parse().unwrap_or(0.0);
-                                        entry_price = pos.average_entry_price
-                                            .as_deref()
-                                            .and_then(|s| s.parse().ok())
-                                            .unwrap_or(0.0);
+                        if let Some(sim) = &dry_run {
+                            (live_pos, entry_price) = sim.position();
+                            markout.lock().on_position(clock.elapsed().as_secs_f64(), live_pos);
+                            execution.lock().on_position(live_pos, Instant::now());
+                        } else if let Some(client_arc) = &client_arc {
+                            match client_arc.get_open_positions().await {
+                                Ok(positions) => {
+                                    for pos in positions {
+                                        if pos.symbol == symbol_name {
+                                            live_pos = pos.quantity.parse().unwrap_or(0.0);
+                                            entry_price = pos.average_entry_price
+                                                .as_deref()
+                                                .and_then(|s| s.parse().ok())
+                                                .unwrap_or(0.0);
+                                        }
+                                    }
+                                    // Position changes since the last cycle are our fills
+                                    markout.lock().on_position(clock.elapsed().as_secs_f64(), live_pos);
+                                    execution.lock().on_position(live_pos, Instant::now());
+                                    if cfg.protective_stop_bps > 0.0 {
+                                        let plan = plan_stop(
+                                            live_pos,
+                                            entry_price,
+                                            cfg.protective_stop_bps,
+                                            cfg.protective_stop_min_position,
+                                            cfg.tick_size,
+                                            cfg.step_size,
+                                        );
+                                        sync_protective_stop(
+                                            client_arc,
+                                            &symbol_name,
+                                            &protective_stop,
+                                            plan,
+                                            cfg.tick_size,
+                                            cfg.step_size,
+                                        )
+                                        .await;
                                     }
                                 }
+                                Err(e) => warn!("⚠️ [BP-v3] Position fetch err: {:?}", e),
                             }
-                            Err(e) => warn!("⚠️ [BP-v3] Position fetch err: {:?}", e),
                         }
 
                         // === STOP-LOSS CHECK ===
@@ -261,15 +511,61 @@ impl Strategy for BackpackMMStrategy {
                                     live_pos, entry_price, mid_price, unrealized, stop_loss_usd);
                                 let close_side = if live_pos > 0.0 { "Ask" } else { "Bid" };
                                 let close_price = if live_pos > 0.0 { mid_price * 0.998 } else { mid_price * 1.002 };
+                                if let Some(sim) = &dry_run {
+                                    sim.place(live_pos < 0.0, live_pos.abs(), close_price, mid_price);
+                                    return;
+                                }
+                                let Some(client_arc) = &client_arc else { return };
+                                // Don't close into our own resting quotes
+                                let close_price = match QuoteBoard::global().check(
+                                    cfg.self_trade_policy,
+                                    exchange_id,
+                                    &symbol_name,
+                                    live_pos < 0.0,
+                                    close_price,
+                                    cfg.tick_size,
+                                ) {
+                                    StpDecision::Submit(price) => price,
+                                    StpDecision::Skip => {
+                                        warn!("🚫 [BP-v3] Stop-loss would cross our own quotes, skipped");
+                                        return;
+                                    }
+                                    StpDecision::CancelFirst(ids) => {
+                                        for id in ids {
+                                            if let Err(e) = client_arc.cancel_order(&symbol_name, &id).await {
+                                                warn!("⚠️ [BP-v3] Self-trade cancel {} failed: {:?}", id, e);
+                                            }
+                                        }
+                                        close_price
+                                    }
+                                };
+                                let close = OrderBuilder::new(
+                                    OrderFilters::from_config(&cfg),
+                                    live_pos < 0.0,
+                                    live_pos.abs(),
+                                )
+                                .limit(close_price)
+                                .reduce_only()
+                                .build();
+                                let close = match close {
+                                    Ok(order) => order,
+                                    Err(e) => {
+                                        error!("🛑 [BP-v3] Stop-loss order invalid: {}", e);
+                                        return;
+                                    }
+                                };
                                 let req = BackpackOrderRequest {
                                     symbol: symbol_name.clone(),
                                     side: close_side.to_string(),
                                     order_type: "Limit".to_string(),
-                                    price: format!("{:.2}", close_price),
-                                    quantity: format!("{:.2}", live_pos.abs()),
+                                    price: close.price_str().unwrap_or_default(),
+                                    quantity: close.qty_str(),
                                     client_id: None,
                                     post_only: Some(false),
                                     time_in_force: Some("IOC".to_string()),
+                                    reduce_only: Some(true),
+                                    trigger_price: None,
+                                    trigger_quantity: None,
                                 };
                                 match client_arc.create_order(&req).await {
                                     Ok(resp) => warn!("🛑 [BP-v3] Stop-loss filled: {}", resp.id),
@@ -279,29 +575,25 @@ impl Strategy for BackpackMMStrategy {
                             }
                         }
 
-                        // 2. Cancel existing quotes
-                        if let Err(e) = client_arc.cancel_all_orders(&symbol_name).await {
-                            warn!("⚠️ [BP-v3] Cancel error: {:?}", e);
-                        }
-
                         // === DYNAMIC SPREAD ===
-                        let base_spread = f64::max(cfg.min_spread_bps, vol_bps * cfg.vol_multiplier);
-                        let mut bid_spread = base_spread;
-                        let mut ask_spread = base_spread;
-
-                        if momentum > cfg.momentum_threshold_bps {
-                            bid_spread *= cfg.momentum_spread_mult;
-                        } else if momentum < -cfg.momentum_threshold_bps {
-                            ask_spread *= cfg.momentum_spread_mult;
-                        }
-
-                        // Inventory skew
-                        let skew_factor = live_pos / max_position;
-                        let skew_shift = skew_factor * base_spread * 0.5;
-                        let skewed_mid = mid_price * (1.0 - skew_shift / 10_000.0);
-
-                        let bid_price = skewed_mid * (1.0 - bid_spread / 10_000.0);
-                        let ask_price = skewed_mid * (1.0 + ask_spread / 10_000.0);
+                        let quote = compute_quotes(&cfg, &QuoteInputs {
+                            mid: mid_price,
+                            vol_bps,
+                            momentum_bps: momentum,
+                            position: live_pos,
+                            max_position,
+                            imbalance,
+                            funding_rate,
+                            secs_to_funding,
+                        });
+                        let quote = defense.apply(mid_price, quote);
+                        let (bid_price, ask_price) = (quote.bid_price, quote.ask_price);
+                        let (bid_spread, ask_spread) = (quote.bid_spread_bps, quote.ask_spread_bps);
+                        let markout_summary = {
+                            let mut m = markout.lock();
+                            m.set_quotes(bid_price, ask_price);
+                            m.summary()
+                        };
 
                         // === DYNAMIC SIZING ===
                         let pos_ratio = live_pos.abs() / max_position;
@@ -310,48 +602,500 @@ impl Strategy for BackpackMMStrategy {
                         let mut ask_size = scaled;
                         if live_pos >= max_position { bid_size = 0.0; }
                         if live_pos <= -max_position { ask_size = 0.0; }
+                        // Free margin caps the side(s) adding exposure; reducing quotes need none
+                        if live_pos >= 0.0 { bid_size = bid_size.min(margin_cap); }
+                        if live_pos <= 0.0 { ask_size = ask_size.min(margin_cap); }
 
-                        info!("🎒v3 Vol={:.1} Mom={:.1} | Bid:{:.3}@{:.2}(sp={:.0}) Ask:{:.3}@{:.2}(sp={:.0}) Pos={:.3} MaxPos={:.3}",
-                            vol_bps, momentum, bid_size, bid_price, bid_spread, ask_size, ask_price, ask_spread, live_pos, max_position);
+                        info!("🎒v3 Vol={:.1} Mom={:.1} Imb={:+.2} | Bid:{:.3}@{:.2}(sp={:.0}) Ask:{:.3}@{:.2}(sp={:.0}) Pos={:.3} MaxPos={:.3}",
+                            vol_bps, momentum, imbalance, bid_size, bid_price, bid_spread, ask_size, ask_price, ask_spread, live_pos, max_position);
+                        info!("🧪 [BP] Markout {} | Defense={}", markout_summary, defense);
 
-                        let mut futures = Vec::new();
-                        for &(is_buy, price, size) in &[(true, bid_price, bid_size), (false, ask_price, ask_size)] {
-                            if size < 0.01 { continue; }
-                            let client_arc = client_arc.clone();
-                            let symbol_name = symbol_name.clone();
-                            let req_future = async move {
-                                let req = BackpackOrderRequest {
-                                    symbol: symbol_name,
-                                    side: if is_buy { "Bid".to_string() } else { "Ask".to_string() },
+                        // 2. Small drift with both quotes resting: amend in place (1 msg per side).
+                        //    Multi-level ladders always go through cancel-all + resubmit.
+                        let resting = {
+                            let q = live_quotes.lock();
+                            q.bid.clone().zip(q.ask.clone())
+                        };
+                        if let Some((bid_id, ask_id)) = resting
+                            && let Some(client_arc) = &client_arc
+                            && !expired
+                            && quote_drift_bps < cfg.requote_threshold_bps
+                            && cfg.num_quote_levels <= 1
+                            && bid_size >= 0.01
+                            && ask_size >= 0.01
+                        {
+                            let amend = |order_id: String, price: f64, size: f64| BackpackAmendOrderRequest {
+                                symbol: symbol_name.clone(),
+                                order_id,
+                                price: Some(format_price(price, cfg.tick_size)),
+                                quantity: Some(format_size(size, cfg.step_size)),
+                            };
+                            let bid_req = amend(bid_id, bid_price, bid_size);
+                            let ask_req = amend(ask_id, ask_price, ask_size);
+                            let (bid_res, ask_res) = futures::future::join(
+                                client_arc.amend_order(&bid_req),
+                                client_arc.amend_order(&ask_req),
+                            )
+                            .await;
+                            match (bid_res, ask_res) {
+                                (Ok(bid), Ok(ask)) => {
+                                    info!("✏️ [BP-v3] Amended Bid:{} Ask:{} (drift={:.1}bps)", bid.id, ask.id, quote_drift_bps);
+                                    let mut exec = execution.lock();
+                                    exec.on_amended(true, bid_price, bid_size, mid_price);
+                                    exec.on_amended(false, ask_price, ask_size, mid_price);
+                                    drop(exec);
+                                    let board = QuoteBoard::global();
+                                    board.set_quote(exchange_id, &symbol_name, STP_OWNER, true, bid.id.clone(), bid_price);
+                                    board.set_quote(exchange_id, &symbol_name, STP_OWNER, false, ask.id.clone(), ask_price);
+                                    let mut q = live_quotes.lock();
+                                    q.bid = Some(bid.id);
+                                    q.ask = Some(ask.id);
+                                    return;
+                                }
+                                (bid_res, ask_res) => {
+                                    // Likely filled/canceled underneath us — fall back to a full requote
+                                    if let Err(e) = bid_res { warn!("⚠️ [BP-v3] Amend Bid failed: {:?}", e); }
+                                    if let Err(e) = ask_res { warn!("⚠️ [BP-v3] Amend Ask failed: {:?}", e); }
+                                }
+                            }
+                        }
+
+                        // 3. Cancel existing quotes and place fresh ones
+                        if let Some(sim) = &dry_run {
+                            sim.cancel_all();
+                            execution.lock().on_cancel_confirmed();
+                        } else if let Some(client_arc) = &client_arc {
+                            match client_arc.cancel_resting_orders(&symbol_name).await {
+                                Ok(_) => execution.lock().on_cancel_confirmed(),
+                                Err(e) => warn!("⚠️ [BP-v3] Cancel error: {:?}", e),
+                            }
+                        }
+                        *live_quotes.lock() = LiveQuotes::default();
+                        QuoteBoard::global().clear(exchange_id, &symbol_name, STP_OWNER);
+
+                        let levels = quote_levels(
+                            &cfg, mid_price, &quote, bid_size, ask_size, live_pos, max_position,
+                        );
+                        let resting = |is_buy, price, size| RestingQuote {
+                            is_buy,
+                            price,
+                            size,
+                            decision_mid: mid_price,
+                            placed_at: Instant::now(),
+                        };
+                        let filters = OrderFilters::from_config(&cfg);
+                        let mut batch: Vec<QuoteLevel> = Vec::new();
+                        for level in levels {
+                            // Tick / lot rounding and venue minimums; outer levels that
+                            // decayed below them are dropped
+                            let order = OrderBuilder::new(filters, level.is_buy, level.size)
+                                .limit(level.price)
+                                .post_only()
+                                .build();
+                            let Ok(order) = order else { continue };
+                            let level = QuoteLevel {
+                                price: order.price.unwrap_or(level.price),
+                                size: order.qty,
+                                ..level
+                            };
+                            // Simulated ids are never recorded, so dry runs skip the amend path
+                            if let Some(sim) = &dry_run {
+                                sim.place(level.is_buy, level.size, level.price, mid_price);
+                                execution.lock().on_submitted(resting(level.is_buy, level.price, level.size));
+                                continue;
+                            }
+                            batch.push(level);
+                        }
+
+                        // One batch for the whole ladder; post-only rejects are repriced
+                        // off the current BBO and resubmitted once as a second batch
+                        let Some(client_arc) = &client_arc else { return };
+                        let mut repriced = false;
+                        while !batch.is_empty() {
+                            let reqs: Vec<BackpackOrderRequest> = batch
+                                .iter()
+                                .map(|l| BackpackOrderRequest {
+                                    symbol: symbol_name.clone(),
+                                    side: if l.is_buy { "Bid" } else { "Ask" }.to_string(),
                                     order_type: "Limit".to_string(),
-                                    price: format!("{:.2}", price),
-                                    quantity: format!("{:.2}", size),
+                                    price: format_price(l.price, cfg.tick_size),
+                                    quantity: format_size(l.size, cfg.step_size),
                                     client_id: None,
                                     post_only: Some(true),
                                     time_in_force: None,
-                                };
-                                match client_arc.create_order(&req).await {
-                                    Ok(resp) => info!("✅ [BP-v3] {:?}: {}", if is_buy {"Bid"} else {"Ask"}, resp.id),
-                                    Err(e) => error!("❌ [BP-v3] {:?}: {:?}", if is_buy {"Bid"} else {"Ask"}, e),
+                                    reduce_only: None,
+                                    trigger_price: None,
+                                    trigger_quantity: None,
+                                })
+                                .collect();
+                            let results = match client_arc.create_orders_batch(&reqs).await {
+                                Ok(results) => results,
+                                Err(e) => {
+                                    error!("❌ [BP-v3] Batch of {} failed: {:?}", reqs.len(), e);
+                                    break;
                                 }
                             };
-                            futures.push(req_future);
+                            let mut retry = Vec::new();
+                            for (l, res) in batch.into_iter().zip(results) {
+                                let side = if l.is_buy { "Bid" } else { "Ask" };
+                                match res {
+                                    Ok(resp) => {
+                                        info!("✅ [BP-v3] {:?}[L{}]: {}", side, l.level, resp.id);
+                                        execution.lock().on_submitted(resting(l.is_buy, l.price, l.size));
+                                        let mut q = live_quotes.lock();
+                                        q.ages.on_placed(resp.id.clone(), Instant::now());
+                                        if l.level == 0 {
+                                            QuoteBoard::global().set_quote(exchange_id, &symbol_name, STP_OWNER, l.is_buy, resp.id.clone(), l.price);
+                                            if l.is_buy { q.bid = Some(resp.id); } else { q.ask = Some(resp.id); }
+                                        }
+                                    }
+                                    Err(e) if !repriced
+                                        && e.downcast_ref::<BackpackError>()
+                                            .is_some_and(BackpackError::is_post_only_rejection) =>
+                                    {
+                                        // Book moved through us: one retry off the current BBO
+                                        match live_bbo.as_ref().and_then(|b| b.reprice(l.is_buy, l.price, cfg.tick_size)) {
+                                            Some(p) => {
+                                                info!("🔁 [BP-v3] {:?}[L{}] post-only crossed @{:.2}, repricing @{:.2}", side, l.level, l.price, p);
+                                                reprices.record();
+                                                retry.push(QuoteLevel { price: p, ..l });
+                                            }
+                                            None => warn!("🚫 [BP-v3] {:?}[L{}] post-only crossed @{:.2}, no fresh BBO", side, l.level, l.price),
+                                        }
+                                    }
+                                    Err(e) => error!("❌ [BP-v3] {:?}: {:?}", side, e),
+                                }
+                            }
+                            repriced = true;
+                            batch = retry;
                         }
-                        futures::future::join_all(futures).await;
                     });
                 }
             }
         }
     }
 
+    fn metrics(&self) -> &ExecutionMetrics {
+        &self.exec_metrics
+    }
+
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        MmState {
+            symbol_id: self.symbol_id,
+            last_mid: self.last_mid,
+            last_quoted_mid: self.last_quoted_mid,
+            mid_history: self.mid_history.iter().copied().collect(),
+            vol: self.vol.snapshot(),
+            max_position: self.max_position,
+            base_size: self.base_size,
+            stop_loss_usd: self.stop_loss_usd,
+            account_equity: self.account_equity_usdc,
+        }
+        .encode()
+    }
+
+    fn load_state(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let state = MmState::decode(bytes, self.symbol_id)?;
+        let skip = state.mid_history.len().saturating_sub(self.cfg.vol_window);
+        self.mid_history = state.mid_history.into_iter().skip(skip).collect();
+        self.vol.restore(state.vol);
+        self.last_mid = state.last_mid;
+        self.last_quoted_mid = state.last_quoted_mid;
+        self.max_position = state.max_position;
+        self.base_size = state.base_size;
+        self.stop_loss_usd = state.stop_loss_usd;
+        self.account_equity_usdc = state.account_equity;
+        Ok(())
+    }
+
     fn on_shutdown(&mut self) -> Pin<Box<dyn std::future::Future<Output = ()> + Send + '_>> {
         let client_opt = self.api_client.clone();
         let sym = self.symbol_name().to_string();
+        let exchange_id = self.exchange_id;
+        let dry_run = self.dry_run.clone();
         Box::pin(async move {
-            if let Some(client) = client_opt {
+            if let Some(sim) = dry_run {
+                sim.cancel_all();
+                info!(
+                    "🧪 [BP-v3] Dry run finished | rPnL=${:.4}",
+                    sim.realized_pnl()
+                );
+            } else if let Some(client) = client_opt {
                 info!("♻️ [BP-v3] Shutting down: Canceling all orders...");
+                // Also takes down the protective stop
                 let _ = client.cancel_all_orders(&sym).await;
+                *self.live_quotes.lock() = LiveQuotes::default();
+                *self.protective_stop.lock() = None;
+                QuoteBoard::global().clear(exchange_id, &sym, STP_OWNER);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, EXCH_BACKPACK, SYM_ETH};
+    use crate::exchanges::mock_http::MockHttpServer;
+    use base64::Engine;
+
+    /// `on_idle` runs on a current-thread runtime, where any `block_in_place`
+    /// panics, against a client whose endpoint refuses connections: it must
+    /// only read the published equity and return immediately.
+    #[tokio::test]
+    async fn test_on_idle_reads_published_balance_without_io() {
+        let secret = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let client = BackpackClient::new("test-key", &secret, "http://127.0.0.1:9").unwrap();
+        let cfg = ExchangeConfig {
+            balance_refresh_secs: 3600,
+            ..AppConfig::default().backpack
+        };
+        let risk_fraction = cfg.risk_fraction;
+        let client = Some(Arc::new(client));
+        let account = ExchangeStateCache::new("BP");
+        let symbol = cfg.symbol_name.clone();
+        let mut mm = BackpackMMStrategy::new(
+            EXCH_BACKPACK,
+            SYM_ETH,
+            symbol,
+            25.0,
+            cfg,
+            client,
+            account.clone(),
+        );
+
+        let bbo = ShmBboMessage {
+            bid_price: 1999.0,
+            ask_price: 2001.0,
+            timestamp_ns: 1,
+            ..Default::default()
+        };
+        mm.on_bbo_update(SYM_ETH, EXCH_BACKPACK, &bbo);
+        account.publish_margin(MarginSummary {
+            net_equity: 10_000.0,
+            used_margin: 0.0,
+            available_margin: 10_000.0,
+        });
+
+        let started = Instant::now();
+        mm.on_idle();
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert_eq!(mm.account_equity_usdc, 10_000.0);
+        assert!((mm.max_position - 10_000.0 * risk_fraction / 2000.0).abs() < 1e-12);
+        assert_eq!(mm.margin_cap(), 5.0);
+
+        // Unchanged cache: limits are not recomputed
+        mm.max_position = 0.0;
+        mm.on_idle();
+        assert_eq!(mm.max_position, 0.0);
+    }
+
+    /// Quotes (side, price, size) the MM sent in its order batches.
+    fn placed_quotes(server: &MockHttpServer) -> Vec<(String, f64, f64)> {
+        let batches = server.requests().into_iter().filter(|r| r.method == "POST");
+        batches
+            .flat_map(|r| serde_json::from_str::<Vec<serde_json::Value>>(&r.body).unwrap())
+            .map(|o| {
+                let field = |k: &str| o[k].as_str().unwrap().to_string();
+                let number = |k: &str| field(k).parse().unwrap();
+                (field("side"), number("price"), number("quantity"))
+            })
+            .collect()
+    }
+
+    /// Full quote cycle against a scripted venue: flat position, quotes
+    /// placed around the mock BBO, then a requote after the BBO moves.
+    #[tokio::test]
+    async fn test_on_idle_quotes_around_mock_bbo() {
+        let server = MockHttpServer::start(|req| match req.method.as_str() {
+            // Batch placements are echoed back as accepted orders
+            "POST" => {
+                let mut orders: Vec<serde_json::Value> = serde_json::from_str(&req.body).unwrap();
+                for (i, order) in orders.iter_mut().enumerate() {
+                    order["id"] = format!("{}", i + 1).into();
+                    order["status"] = "New".into();
+                }
+                (200, serde_json::to_string(&orders).unwrap())
             }
+            // No open positions, no cancelled orders, no trade history
+            _ => (200, "[]".to_string()),
         })
+        .await;
+        let secret = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let client = BackpackClient::new("test-key", &secret, &server.base_url).unwrap();
+        let cfg = ExchangeConfig {
+            requote_interval_ms: 0,
+            ..AppConfig::default().backpack
+        };
+        let account = ExchangeStateCache::new("BP");
+        account.publish_margin(MarginSummary {
+            net_equity: 10_000.0,
+            used_margin: 0.0,
+            available_margin: 10_000.0,
+        });
+        let symbol = cfg.symbol_name.clone();
+        let mut mm = BackpackMMStrategy::new(
+            EXCH_BACKPACK,
+            SYM_ETH,
+            symbol,
+            25.0,
+            cfg.clone(),
+            Some(Arc::new(client)),
+            account,
+        );
+
+        let mut sent = 0;
+        for (i, mid) in [2000.0, 2010.0].into_iter().enumerate() {
+            let bbo = ShmBboMessage {
+                bid_price: mid - 1.0,
+                ask_price: mid + 1.0,
+                timestamp_ns: (i as u64 + 1) * 1_000_000_000,
+                ..Default::default()
+            };
+            mm.on_bbo_update(SYM_ETH, EXCH_BACKPACK, &bbo);
+            mm.on_idle();
+
+            // Same pricing the MM runs, flat and undefended
+            let (funding_rate, secs_to_funding) = mm.funding_inputs();
+            let expected = compute_quotes(
+                &cfg,
+                &QuoteInputs {
+                    mid,
+                    vol_bps: mm.realized_vol_bps(),
+                    momentum_bps: mm.momentum_bps(),
+                    position: 0.0,
+                    max_position: mm.max_position,
+                    imbalance: mm.book_imbalance(),
+                    funding_rate,
+                    secs_to_funding,
+                },
+            );
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while placed_quotes(&server).len() < sent + 2 && Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            let quotes = placed_quotes(&server);
+            assert_eq!(quotes.len(), sent + 2, "mid {mid}: {quotes:?}");
+            let size = cfg.step_size..=mm.base_size;
+            for (side, price, qty) in &quotes[sent..] {
+                let target = if side == "Bid" {
+                    expected.bid_price
+                } else {
+                    expected.ask_price
+                };
+                assert!(
+                    (price - target).abs() <= cfg.tick_size,
+                    "{side} {price} vs {target}"
+                );
+                assert!(size.contains(qty), "{side} size {qty}");
+            }
+            assert!(
+                quotes[sent..]
+                    .iter()
+                    .any(|(side, p, _)| side == "Bid" && *p < mid - 1.0)
+            );
+            assert!(
+                quotes[sent..]
+                    .iter()
+                    .any(|(side, p, _)| side == "Ask" && *p > mid + 1.0)
+            );
+            sent = quotes.len();
+        }
+
+        // Each cycle read the position and swept the old quotes first
+        let requests = server.requests();
+        let count = |method: &str, path: &str| {
+            requests
+                .iter()
+                .filter(|r| r.method == method && r.path.starts_with(path))
+                .count()
+        };
+        assert_eq!(count("GET", "/api/v1/position"), 2);
+        assert_eq!(count("DELETE", "/api/v1/orders"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_quotes_without_credentials() {
+        let cfg = ExchangeConfig {
+            requote_interval_ms: 0,
+            dry_run: true,
+            ..AppConfig::default().backpack
+        };
+        let account = ExchangeStateCache::new("BP");
+        account.publish_margin(MarginSummary {
+            net_equity: 10_000.0,
+            used_margin: 0.0,
+            available_margin: 10_000.0,
+        });
+        let symbol = cfg.symbol_name.clone();
+        let mut mm =
+            BackpackMMStrategy::new(EXCH_BACKPACK, SYM_ETH, symbol, 25.0, cfg, None, account);
+
+        let bbo = ShmBboMessage {
+            bid_price: 1999.0,
+            ask_price: 2001.0,
+            timestamp_ns: 1_000_000_000,
+            ..Default::default()
+        };
+        mm.on_bbo_update(SYM_ETH, EXCH_BACKPACK, &bbo);
+        mm.on_idle();
+
+        // Both sides are routed to the simulator and filled there
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while mm.execution.lock().metrics().orders_submitted < 2 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(mm.execution.lock().metrics().orders_submitted >= 2);
+        let sim = mm.dry_run.as_ref().unwrap();
+        assert!(
+            sim.realized_pnl() < 0.0,
+            "slippage is charged on the round trip"
+        );
+    }
+
+    #[test]
+    fn test_warm_state_survives_restart() {
+        use crate::strategy::state;
+        let max_age = Duration::from_secs(300);
+        let dir = std::env::temp_dir().join(format!("aleph-state-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let new_mm = |symbol_id| {
+            let cfg = AppConfig::default().backpack;
+            let symbol = cfg.symbol_name.clone();
+            let account = ExchangeStateCache::new("BP");
+            BackpackMMStrategy::new(EXCH_BACKPACK, symbol_id, symbol, 25.0, cfg, None, account)
+        };
+
+        let mut mm = new_mm(SYM_ETH);
+        for i in 0..40u64 {
+            let mid = 2000.0 + (i % 7) as f64 * 0.5;
+            let bbo = ShmBboMessage {
+                bid_price: mid - 0.5,
+                ask_price: mid + 0.5,
+                timestamp_ns: (i + 1) * 250_000_000,
+                ..Default::default()
+            };
+            mm.on_bbo_update(SYM_ETH, EXCH_BACKPACK, &bbo);
+        }
+        mm.max_position = 1.5;
+        state::save(&mm, &dir).unwrap().unwrap();
+
+        let mut restarted = new_mm(SYM_ETH);
+        assert!(state::load(&mut restarted, &dir, max_age).unwrap());
+        assert_eq!(restarted.last_mid, mm.last_mid);
+        assert_eq!(restarted.mid_history, mm.mid_history);
+        assert_eq!(restarted.realized_vol_bps(), mm.realized_vol_bps());
+        assert_eq!(restarted.momentum_bps(), mm.momentum_bps());
+        assert_eq!(restarted.max_position, 1.5);
+        assert!(restarted.last_update.is_none());
+
+        // Another symbol's snapshot is refused; a stale one is ignored
+        let mut other = new_mm(SYM_ETH + 1);
+        assert!(state::load(&mut other, &dir, max_age).is_err());
+        std::thread::sleep(Duration::from_millis(10));
+        let mut cold = new_mm(SYM_ETH);
+        assert!(!state::load(&mut cold, &dir, Duration::ZERO).unwrap());
+        assert_eq!(cold.last_mid, 0.0);
+        std::fs::remove_dir_all(&dir).ok();
     }
 }
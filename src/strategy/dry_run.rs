@@ -0,0 +1,130 @@
+//! Dry-run order simulation for the Backpack / EdgeX market makers.
+//!
+//! With `dry_run = true` the strategies keep their full code path (market data,
+//! balance, quoting math) but route order placement and cancels here instead of
+//! the exchange API. Every order is logged and assumed filled at mid shifted by
+//! `dry_run_slippage_bps` against us, updating a simulated position that
+//! replaces the live position fetch.
+
+use crate::config::ExchangeConfig;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::info;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SimPosition {
+    qty: f64,
+    entry_price: f64,
+    realized_pnl: f64,
+}
+
+#[derive(Debug)]
+pub struct DryRun {
+    tag: &'static str,
+    slippage_bps: f64,
+    pos: Mutex<SimPosition>,
+    next_id: AtomicU64,
+}
+
+impl DryRun {
+    pub fn new(tag: &'static str, slippage_bps: f64) -> Self {
+        Self {
+            tag,
+            slippage_bps,
+            pos: Mutex::new(SimPosition::default()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// `Some` only when `cfg.dry_run` is set.
+    pub fn from_config(tag: &'static str, cfg: &ExchangeConfig) -> Option<Arc<Self>> {
+        cfg.dry_run
+            .then(|| Arc::new(Self::new(tag, cfg.dry_run_slippage_bps)))
+    }
+
+    /// Simulated (position, average entry price).
+    pub fn position(&self) -> (f64, f64) {
+        let p = self.pos.lock();
+        (p.qty, p.entry_price)
+    }
+
+    pub fn realized_pnl(&self) -> f64 {
+        self.pos.lock().realized_pnl
+    }
+
+    /// Log the order and fill it at mid ± slippage. Returns a synthetic order id.
+    pub fn place(&self, is_buy: bool, size: f64, limit_price: f64, mid: f64) -> String {
+        let slip = self.slippage_bps / 10_000.0;
+        let fill_px = if is_buy {
+            mid * (1.0 + slip)
+        } else {
+            mid * (1.0 - slip)
+        };
+        let signed = if is_buy { size } else { -size };
+
+        let mut p = self.pos.lock();
+        if p.qty == 0.0 || p.qty.signum() == signed.signum() {
+            // Opening / adding: volume-weighted entry
+            let total = p.qty + signed;
+            p.entry_price = (p.entry_price * p.qty.abs() + fill_px * size) / total.abs();
+            p.qty = total;
+        } else {
+            // Reducing / flipping: realize on the closed part
+            let closed = size.min(p.qty.abs());
+            p.realized_pnl += (fill_px - p.entry_price) * closed * p.qty.signum();
+            p.qty += signed;
+            if p.qty.abs() < 1e-12 {
+                p.qty = 0.0;
+                p.entry_price = 0.0;
+            } else if p.qty.signum() == signed.signum() {
+                p.entry_price = fill_px;
+            }
+        }
+
+        let id = format!("DRY-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        info!(
+            "🧪 [{}][DRY] {} {:.4} @ {:.2} (limit {:.2}) → {} | Pos={:.4}@{:.2} rPnL=${:.4}",
+            self.tag,
+            if is_buy { "Buy" } else { "Sell" },
+            size,
+            fill_px,
+            limit_price,
+            id,
+            p.qty,
+            p.entry_price,
+            p.realized_pnl
+        );
+        id
+    }
+
+    pub fn cancel_all(&self) {
+        info!("🧪 [{}][DRY] Would cancel all orders", self.tag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fills_track_position_entry_and_pnl_with_slippage() {
+        let sim = DryRun::new("T", 10.0);
+        sim.place(true, 1.0, 1999.0, 2000.0);
+        sim.place(true, 1.0, 2999.0, 3000.0);
+        // 2002 and 3003 averaged
+        let (qty, entry) = sim.position();
+        assert_eq!(qty, 2.0);
+        assert!((entry - 2502.5).abs() < 1e-9);
+
+        // Flip to short: close 2 at 2997, open 1 at the same price
+        sim.place(false, 3.0, 3001.0, 3000.0);
+        let (qty, entry) = sim.position();
+        assert_eq!(qty, -1.0);
+        assert!((entry - 2997.0).abs() < 1e-9);
+        assert!((sim.realized_pnl() - (2997.0 - 2502.5) * 2.0).abs() < 1e-9);
+
+        sim.place(true, 1.0, 2999.0, 3000.0);
+        assert_eq!(sim.position(), (0.0, 0.0));
+    }
+}
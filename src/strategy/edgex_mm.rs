@@ -3,17 +3,71 @@
 //! This strategy uses the low-level EdgeXClient API directly.
 //! TODO: Migrate to EdgeXGateway (unified Exchange trait) for consistency.
 
-use crate::config::{ExchangeConfig, format_price, format_size, round_to_tick};
+use crate::config::ExchangeConfig;
+use crate::edgex_api::client::{ClientError, EdgeXClient};
+use crate::edgex_api::model::CreateOrderRequest;
+use crate::edgex_api::order_builder::EdgeXOrderBuilder;
+use crate::exchange_state::ExchangeStateCache;
+use crate::order_builder::{OrderBuilder, OrderFilters};
+use crate::shm_depth_reader::ShmDepthReader;
 use crate::shm_reader::ShmBboMessage;
 use crate::strategy::Strategy;
-use crate::edgex_api::client::EdgeXClient;
-use crate::edgex_api::model::{CreateOrderRequest, OrderSide, OrderType, TimeInForce};
+use crate::strategy::dry_run::DryRun;
+use crate::strategy::execution_metrics::{ExecutionMetrics, ExecutionTracker, RestingQuote};
+use crate::strategy::markout::{Defense, MarkoutTracker};
+use crate::strategy::order_age::OrderAges;
+use crate::strategy::quoting::{
+    QuoteInputs, QuoteLevel, compute_quotes, depth_imbalance, quote_levels,
+};
+use crate::strategy::reprice::{LiveBbo, REPRICE_LOG_INTERVAL, RepriceCounter};
+use crate::strategy::state::MmState;
+use crate::strategy::volatility::Volatility;
+use crate::types::MarginSummary;
+use parking_lot::Mutex;
 use std::collections::VecDeque;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::pin::Pin;
 use tokio::runtime::Handle;
 
+/// ETH-USD perp contract quoted by this strategy (key in the shared account cache)
+pub const CONTRACT_ID: &str = "10000002";
+/// [`CONTRACT_ID`] as the numeric id order and cancel requests take
+pub const CONTRACT: u64 = match u64::from_str_radix(CONTRACT_ID, 10) {
+    Ok(id) => id,
+    Err(_) => panic!("CONTRACT_ID must be numeric"),
+};
+
+/// Lifetime of a signed quote; requotes replace it long before that
+const QUOTE_EXPIRY: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Exchange ids of the quotes placed last cycle, for targeted cancellation.
+///
+/// `complete` is false when some order's fate is unknown (lost create
+/// response, failed cancel, fresh start); the next requote then sweeps the
+/// whole contract with `cancel_all_orders` instead.
+#[derive(Debug, Default)]
+struct LiveOrders {
+    ids: Vec<u64>,
+    complete: bool,
+    // Placement times, for cfg.max_order_age_secs
+    ages: OrderAges<u64>,
+}
+
+impl LiveOrders {
+    /// Ids to cancel by id, or `None` when the contract must be swept.
+    /// Either way the caller is about to clear the book, so state resets.
+    fn take_for_cancel(&mut self) -> Option<Vec<u64>> {
+        let ids = std::mem::take(&mut self.ids);
+        self.ages.clear();
+        std::mem::replace(&mut self.complete, true).then_some(ids)
+    }
+
+    fn mark_unknown(&mut self) {
+        self.complete = false;
+    }
+}
+
 pub struct MarketMakerStrategy {
     target_exchange_id: u8,
     symbol_id: u16,
@@ -28,49 +82,76 @@ pub struct MarketMakerStrategy {
 
     // Volatility
     mid_history: VecDeque<f64>,
+    vol: Volatility,
+
+    // Simulated order routing (cfg.dry_run)
+    dry_run: Option<Arc<DryRun>>,
+
+    // Ids of our resting quotes (set by the quote task)
+    live_orders: Arc<Mutex<LiveOrders>>,
+
+    // Equity / funding shared by every strategy on this EdgeX account
+    account: Arc<ExchangeStateCache>,
+    account_version: u64,
+
+    // Post-fill markout (adverse selection defense), clocked from `clock`
+    markout: Arc<Mutex<MarkoutTracker>>,
+    clock: Instant,
+    quoting_paused: bool,
+
+    // L2 depth for book-imbalance shading (only opened when imbalance_weight > 0)
+    depth_reader: Option<ShmDepthReader>,
+
+    // Fresh BBO for repricing post-only rejects, and how often that happens
+    live_bbo: Option<Arc<LiveBbo>>,
+    reprices: Arc<RepriceCounter>,
+
+    // Fill rate / slippage, updated by the quote task; snapshot copied each on_idle
+    execution: Arc<Mutex<ExecutionTracker>>,
+    exec_metrics: ExecutionMetrics,
 
     // Dynamic limits
     max_position: f64,
     base_size: f64,
     stop_loss_usd: f64,
-    last_balance_refresh: Option<Instant>,
     account_equity_usd: f64,
+    // Free margin caps the exposure-adding quote (None until first fetch)
+    margin: Option<MarginSummary>,
 }
 
 impl MarketMakerStrategy {
+    /// `edgex_client` / `account_id` / `account` are shared with the rest of the
+    /// process (`None` = quoting disabled).
     pub fn new(
         target_exchange_id: u8,
         symbol_id: u16,
         _half_spread_bps: f64,
         cfg: ExchangeConfig,
+        edgex_client: Option<Arc<EdgeXClient>>,
+        account_id: u64,
+        account: Arc<ExchangeStateCache>,
     ) -> Self {
-        let mut edgex_client = None;
-        let mut account_id = 0;
-
-        let env_path = std::env::var("EDGEX_ENV_PATH").unwrap_or_else(|_| {
-            "/home/metaverse/.openclaw/workspace/aleph-tx/.env.edgex".to_string()
-        });
-
-        if let Ok(env_str) = std::fs::read_to_string(&env_path) {
-            let mut key = String::new();
-            for line in env_str.lines() {
-                if let Some(rest) = line.strip_prefix("EDGEX_ACCOUNT_ID=") {
-                    account_id = rest.trim().parse().unwrap_or(0);
-                }
-                if let Some(rest) = line.strip_prefix("EDGEX_STARK_PRIVATE_KEY=") {
-                    key = rest.trim().to_string();
-                }
+        let depth_reader = if cfg.imbalance_weight > 0.0 {
+            let reader = ShmDepthReader::open("/dev/shm/aleph-depth", 2048).ok();
+            if reader.is_none() {
+                tracing::warn!("📊 [EX] imbalance_weight set but depth reader not available");
             }
-            if account_id > 0
-                && !key.is_empty()
-                && let Ok(client) = EdgeXClient::new(&key, None)
-            {
-                edgex_client = Some(Arc::new(client));
-                tracing::info!("✅ Loaded EdgeX API Client (v3 — dynamic allocation)");
-            }
-        }
+            reader
+        } else {
+            None
+        };
 
         let vol_window = cfg.vol_window;
+        let vol = Volatility::from_config(&cfg);
+        let markout = Arc::new(Mutex::new(MarkoutTracker::from_config(&cfg)));
+        let dry_run = DryRun::from_config("EX", &cfg);
+        if dry_run.is_some() {
+            tracing::warn!("🧪 [EX] DRY RUN — orders are logged and simulated, nothing is sent");
+        }
+        account.watch(
+            CONTRACT_ID,
+            cfg.funding_skew_weight > 0.0 || cfg.funding_window_widen_bps > 0.0,
+        );
         let min_order = cfg.min_order_size;
         Self {
             target_exchange_id,
@@ -82,28 +163,29 @@ impl MarketMakerStrategy {
             last_mid: 0.0,
             last_quoted_mid: 0.0,
             mid_history: VecDeque::with_capacity(vol_window + 1),
+            vol,
+            dry_run,
+            live_orders: Arc::new(Mutex::new(LiveOrders::default())),
+            account,
+            account_version: 0,
+            markout,
+            clock: Instant::now(),
+            quoting_paused: false,
+            depth_reader,
+            live_bbo: LiveBbo::open(symbol_id, target_exchange_id),
+            reprices: Arc::new(RepriceCounter::default()),
+            execution: Arc::new(Mutex::new(ExecutionTracker::default())),
+            exec_metrics: ExecutionMetrics::default(),
             max_position: 0.2,
             base_size: min_order.max(0.1),
             stop_loss_usd: 5.0,
-            last_balance_refresh: None,
             account_equity_usd: 0.0,
+            margin: None,
         }
     }
 
     fn realized_vol_bps(&self) -> f64 {
-        if self.mid_history.len() < 10 {
-            return 25.0;
-        }
-        let returns: Vec<f64> = self
-            .mid_history
-            .iter()
-            .zip(self.mid_history.iter().skip(1))
-            .map(|(prev, cur)| ((cur - prev) / prev) * 10_000.0)
-            .collect();
-        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
-        let variance =
-            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
-        variance.sqrt()
+        self.vol.vol_bps().unwrap_or(25.0)
     }
 
     fn momentum_bps(&self) -> f64 {
@@ -119,62 +201,145 @@ impl MarketMakerStrategy {
         (recent - lookback) / lookback * 10_000.0
     }
 
-    /// Refresh EdgeX balance and recompute limits
-    fn maybe_refresh_balance(&mut self) {
-        let should_refresh = match self.last_balance_refresh {
-            None => true,
-            Some(last) => last.elapsed() > Duration::from_secs(self.cfg.balance_refresh_secs),
+    /// Book imbalance within `imbalance_depth_bps` of mid (0 if disabled/unavailable)
+    fn book_imbalance(&self) -> f64 {
+        self.depth_reader
+            .as_ref()
+            .and_then(|r| r.read_depth(self.symbol_id, self.target_exchange_id))
+            .map(|d| depth_imbalance(&d, self.cfg.imbalance_depth_bps))
+            .unwrap_or(0.0)
+    }
+
+    /// (rate, seconds to settlement) from the cached funding; unknown once settlement passed
+    fn funding_inputs(&self) -> (f64, Option<f64>) {
+        let Some(funding) = self.account.funding(CONTRACT_ID) else {
+            return (0.0, None);
         };
-        if !should_refresh || self.last_mid <= 0.0 {
-            return;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let secs = (funding.next_funding_ms as i64 - now_ms) as f64 / 1000.0;
+        if secs < 0.0 {
+            return (0.0, None);
         }
+        (funding.rate, Some(secs))
+    }
 
-        if let Some(client) = &self.edgex_client {
-            let client_arc = client.clone();
-            let account_id = self.account_id;
-            let mid = self.last_mid;
-            let risk_fraction = self.cfg.risk_fraction;
-            let stop_pct = self.cfg.stop_loss_pct;
-            let min_order_size = self.cfg.min_order_size;
-
-            if let Ok(handle) = Handle::try_current() {
-                let result = tokio::task::block_in_place(|| {
-                    handle.block_on(async { client_arc.get_balances(account_id).await })
-                });
-                if let Ok(balances) = result {
-                    // Sum total from EdgeX balance entries
-                    let mut equity = 0.0;
-                    for b in &balances {
-                        let bal: f64 = b.balance.parse().unwrap_or(0.0);
-                        if bal > equity {
-                            equity = bal;
-                        }
-                    }
+    /// Largest exposure-adding quote the free margin allows (unbounded until known)
+    fn margin_cap(&self) -> f64 {
+        let leverage = self.cfg.target_leverage.unwrap_or(1) as f64;
+        self.margin
+            .map_or(f64::MAX, |m| m.max_order_size(self.last_mid, leverage))
+    }
 
-                    if equity > 0.0 {
-                        self.account_equity_usd = equity;
-                        let risk_usd = equity * risk_fraction;
-                        self.max_position = risk_usd / mid;
-                        self.base_size = (self.max_position / 2.0).max(min_order_size);
-                        // Round to 0.01 for EdgeX stepSize
-                        self.base_size = (self.base_size * 100.0).floor() / 100.0;
-                        if self.base_size < min_order_size {
-                            self.base_size = min_order_size;
-                        }
-                        self.stop_loss_usd = equity * stop_pct * 10.0;
-                        self.last_balance_refresh = Some(Instant::now());
-
-                        tracing::info!(
-                            "💰 [EX] Balance: ${:.2} | MaxPos: {:.4} ETH | BaseSize: {:.2} | StopLoss: ${:.2}",
-                            equity,
-                            self.max_position,
-                            self.base_size,
-                            self.stop_loss_usd
-                        );
-                    }
-                }
-            }
+    /// Recompute limits when the shared cache published new EdgeX margin totals
+    fn apply_balance(&mut self) {
+        let version = self.account.version();
+        if version == self.account_version || self.last_mid <= 0.0 {
+            return;
+        }
+        self.account_version = version;
+
+        let margin = self.account.margin();
+        let equity = margin.net_equity;
+        self.margin = Some(margin);
+        let min_order_size = self.cfg.min_order_size;
+        self.account_equity_usd = equity;
+        let risk_usd = equity * self.cfg.risk_fraction;
+        self.max_position = risk_usd / self.last_mid;
+        self.base_size = (self.max_position / 2.0).max(min_order_size);
+        // Round to 0.01 for EdgeX stepSize
+        self.base_size = (self.base_size * 100.0).floor() / 100.0;
+        if self.base_size < min_order_size {
+            self.base_size = min_order_size;
         }
+        self.stop_loss_usd = equity * self.cfg.stop_loss_pct * 10.0;
+
+        tracing::info!(
+            "💰 [EX] Equity: ${:.2} (used ${:.2}, free ${:.2}) | MaxPos: {:.4} ETH | \
+             BaseSize: {:.2} | MarginCap: {:.4} | StopLoss: ${:.2} | \
+             FillRate: {:.1}% ({}/{}) | AvgSlip: {:+.2}bps",
+            equity,
+            margin.used_margin,
+            margin.available_margin,
+            self.max_position,
+            self.base_size,
+            self.margin_cap(),
+            self.stop_loss_usd,
+            self.exec_metrics.fill_rate() * 100.0,
+            self.exec_metrics.orders_filled,
+            self.exec_metrics.orders_submitted,
+            self.exec_metrics.avg_slippage_bps()
+        );
+    }
+}
+
+/// Signed post-only limit order for one quote level (`None` if signing failed).
+///
+/// The Starknet ECDSA signature runs on the blocking pool so it cannot stall
+/// the Tokio workers (and with them the WebSocket feeds).
+async fn sign_quote(
+    client: &Arc<EdgeXClient>,
+    account_id: u64,
+    level: &QuoteLevel,
+    cfg: &ExchangeConfig,
+) -> Option<CreateOrderRequest> {
+    let client = client.clone();
+    let (is_buy, price, size_eth) = (level.is_buy, level.price, level.size);
+    let (tick_size, step_size) = (cfg.tick_size, cfg.step_size);
+    let client_order_id = format!("MM-{}", rand::random::<u32>());
+    let signed = tokio::task::spawn_blocking(move || {
+        let order = EdgeXOrderBuilder::new(&client, account_id, CONTRACT)
+            .post_only()
+            .client_order_id(client_order_id)
+            .expire_in(QUOTE_EXPIRY)
+            .increments(tick_size, step_size);
+        if is_buy {
+            order.buy(price, size_eth).build()
+        } else {
+            order.sell(price, size_eth).build()
+        }
+    })
+    .await;
+
+    match signed {
+        Ok(Ok(req)) => Some(req),
+        Ok(Err(e)) => {
+            let side = if is_buy { "Bid" } else { "Ask" };
+            tracing::error!("❌ [EX-v3] Crypto signing failed for {:?}: {}", side, e);
+            None
+        }
+        Err(e) => {
+            tracing::error!("❌ [EX-v3] Signing task failed: {}", e);
+            None
+        }
+    }
+}
+
+/// After a create whose response was lost, find the order by `clientOrderId`
+/// and pull it if it made it onto the book, so the next requote starts from a
+/// known state instead of stacking a second quote next to it.
+async fn resolve_unknown_order(client: &EdgeXClient, account_id: u64, client_order_id: &str) {
+    // Give the matching engine a moment to register the order
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    match client
+        .cancel_order_by_client_order_id(account_id, client_order_id)
+        .await
+    {
+        Ok(Some(order)) if order.status.is_live() => tracing::warn!(
+            "🧹 [EX-v3] {} was live (id {}, filled {}/{}), cancelled",
+            client_order_id,
+            order.id,
+            order.cum_fill_size,
+            order.size
+        ),
+        Ok(Some(order)) => tracing::info!(
+            "🔎 [EX-v3] {} already {:?} (filled {}/{})",
+            client_order_id,
+            order.status,
+            order.cum_fill_size,
+            order.size
+        ),
+        Ok(None) => tracing::info!("🔎 [EX-v3] {} never reached the book", client_order_id),
+        Err(e) => tracing::error!("❌ [EX-v3] Could not resolve {}: {}", client_order_id, e),
     }
 }
 
@@ -190,6 +355,10 @@ impl Strategy for MarketMakerStrategy {
         if bbo.bid_price > 0.0 && bbo.ask_price > 0.0 {
             let mid = (bbo.bid_price + bbo.ask_price) / 2.0;
             self.last_mid = mid;
+            self.vol.update(mid, bbo.timestamp_ns);
+            self.markout
+                .lock()
+                .on_mid(self.clock.elapsed().as_secs_f64(), mid);
             self.mid_history.push_back(mid);
             if self.mid_history.len() > self.cfg.vol_window {
                 self.mid_history.pop_front();
@@ -202,58 +371,146 @@ impl Strategy for MarketMakerStrategy {
             return;
         }
 
-        self.maybe_refresh_balance();
+        self.exec_metrics = self.execution.lock().metrics();
+        self.apply_balance();
+        if let Some(n) = self.reprices.take_if_elapsed(REPRICE_LOG_INTERVAL) {
+            tracing::info!("🔁 [EX] Post-only reprices: {} in the last hour", n);
+        }
 
         let now = Instant::now();
-        let should_update = match self.last_update {
-            None => true,
-            Some(last) => {
-                let elapsed = now.duration_since(last);
-                if elapsed < Duration::from_millis(self.cfg.requote_interval_ms) {
-                    false
-                } else {
-                    let time_trigger = elapsed > Duration::from_secs(5);
-                    let price_trigger = if self.last_quoted_mid > 0.0 {
-                        let dev = (self.last_mid - self.last_quoted_mid).abs()
-                            / self.last_quoted_mid
-                            * 10_000.0;
-                        dev > 10.0
-                    } else {
-                        false
-                    };
-                    time_trigger || price_trigger
-                }
+        let max_age = Duration::from_secs(self.cfg.max_order_age_secs);
+        let expired = {
+            // Cleared here so the requote below fires once, not every poll until it lands
+            let mut live = self.live_orders.lock();
+            let expired = live.ages.expired(now, max_age);
+            if expired {
+                live.ages.clear();
             }
+            expired
         };
+        if expired {
+            tracing::info!(
+                "⌛ [EX] Quotes older than {}s — cancelling and requoting",
+                max_age.as_secs()
+            );
+        }
+        let should_update = expired
+            || match self.last_update {
+                None => true,
+                Some(last) => {
+                    let elapsed = now.duration_since(last);
+                    if elapsed < Duration::from_millis(self.cfg.requote_interval_ms) {
+                        false
+                    } else {
+                        let time_trigger =
+                            elapsed > Duration::from_secs(self.cfg.max_quote_age_secs);
+                        let price_trigger = if self.last_quoted_mid > 0.0 {
+                            let dev = (self.last_mid - self.last_quoted_mid).abs()
+                                / self.last_quoted_mid
+                                * 10_000.0;
+                            dev > self.cfg.price_change_threshold_bps
+                        } else {
+                            false
+                        };
+                        time_trigger || price_trigger
+                    }
+                }
+            };
 
         if should_update {
             self.last_update = Some(now);
             self.last_quoted_mid = self.last_mid;
 
-            if let Some(client) = &self.edgex_client {
+            let defense = self
+                .markout
+                .lock()
+                .defense(self.clock.elapsed().as_secs_f64());
+            if let Defense::Pause { remaining_secs } = defense {
+                if !self.quoting_paused {
+                    tracing::warn!(
+                        "☣️ [EX-v3] Toxic flow — pausing quotes for {:.0}s | Markout {}",
+                        remaining_secs,
+                        self.markout.lock().summary()
+                    );
+                    self.quoting_paused = true;
+                    if let Some(sim) = &self.dry_run {
+                        sim.cancel_all();
+                        self.execution.lock().on_cancel_confirmed();
+                    } else if let (Some(client), Ok(handle)) =
+                        (self.edgex_client.clone(), Handle::try_current())
+                    {
+                        let account_id = self.account_id;
+                        let live_orders = self.live_orders.clone();
+                        let execution = self.execution.clone();
+                        handle.spawn(async move {
+                            use crate::edgex_api::model::CancelAllOrderRequest;
+                            let req = CancelAllOrderRequest {
+                                account_id,
+                                filter_contract_id_list: vec![CONTRACT],
+                            };
+                            live_orders.lock().take_for_cancel();
+                            match client.cancel_all_orders(&req).await {
+                                Ok(_) => execution.lock().on_cancel_confirmed(),
+                                Err(e) => {
+                                    tracing::warn!("⚠️ [EX-v3] Cancel err: {}", e);
+                                    live_orders.lock().mark_unknown();
+                                }
+                            }
+                        });
+                    }
+                }
+                return;
+            }
+            if self.quoting_paused {
+                tracing::info!("☣️ [EX-v3] Markout cool-off over — resuming quotes");
+                self.quoting_paused = false;
+            }
+
+            // Dry runs go through the whole cycle without credentials; only the REST calls
+            // below need a client
+            if self.edgex_client.is_some() || self.dry_run.is_some() {
                 let mid_price = self.last_mid;
-                let client_arc: Arc<EdgeXClient> = client.clone();
+                let client_arc: Option<Arc<EdgeXClient>> = self.edgex_client.clone();
                 let account_id = self.account_id;
                 let cfg = self.cfg.clone();
 
                 let vol_bps = self.realized_vol_bps();
                 let momentum = self.momentum_bps();
+                let imbalance = self.book_imbalance();
+                let (funding_rate, secs_to_funding) = self.funding_inputs();
                 let max_position = self.max_position;
                 let base_size = self.base_size;
+                let margin_cap = self.margin_cap();
+                let markout = self.markout.clone();
+                let clock = self.clock;
+                let dry_run = self.dry_run.clone();
+                let live_orders = self.live_orders.clone();
+                let live_bbo = self.live_bbo.clone();
+                let reprices = self.reprices.clone();
+                let execution = self.execution.clone();
 
                 if let Ok(handle) = Handle::try_current() {
                     handle.spawn(async move {
                         // 1. Fetch live positions
                         let mut live_pos = 0.0;
-                        match client_arc.get_positions(account_id).await {
-                            Ok(positions) => {
-                                for p in positions {
-                                    if p.contract_id == "10000002" {
-                                        live_pos += p.open_size.parse::<f64>().unwrap_or(0.0);
+                        if let Some(sim) = &dry_run {
+                            live_pos = sim.position().0;
+                            markout.lock().on_position(clock.elapsed().as_secs_f64(), live_pos);
+                            execution.lock().on_position(live_pos, Instant::now());
+                        } else if let Some(client_arc) = &client_arc {
+                            match client_arc.get_positions(account_id).await {
+                                Ok(positions) => {
+                                    for p in positions {
+                                        if p.contract_id == CONTRACT_ID {
+                                            live_pos += p.open_size.parse::<f64>().unwrap_or(0.0);
+                                        }
                                     }
+                                    // Position changes since the last cycle are our fills
+                                    markout.lock().on_position(clock.elapsed().as_secs_f64(), live_pos);
+                                    execution.lock().on_position(live_pos, Instant::now());
                                 }
+                                Err(e) => tracing::warn!("⚠️ [EX-v3] Position err: {}", e),
                             }
-                            Err(e) => tracing::warn!("⚠️ [EX-v3] Position err: {:?}", e),
                         }
 
                         // === STOP-LOSS (over-exposure guard) ===
@@ -262,144 +519,289 @@ impl Strategy for MarketMakerStrategy {
                         if live_pos.abs() > max_position * 3.0 && max_position > 0.0 {
                             tracing::warn!("🛑 [EX-v3] OVER-EXPOSED! Pos={:.4} MaxPos={:.4} — cancelling all orders",
                                 live_pos, max_position);
+                            if let Some(sim) = &dry_run {
+                                sim.cancel_all();
+                                return;
+                            }
+                            let Some(client_arc) = &client_arc else { return };
                             use crate::edgex_api::model::CancelAllOrderRequest;
                             let cancel_req = CancelAllOrderRequest {
-                                account_id, filter_contract_id_list: vec![10000002],
+                                account_id, filter_contract_id_list: vec![CONTRACT],
                             };
-                            let _ = client_arc.cancel_all_orders(&cancel_req).await;
+                            live_orders.lock().take_for_cancel();
+                            if client_arc.cancel_all_orders(&cancel_req).await.is_err() {
+                                live_orders.lock().mark_unknown();
+                            }
                             return;
                         }
 
                         // 2. Cancel existing quotes
                         use crate::edgex_api::model::CancelAllOrderRequest;
                         let cancel_req = CancelAllOrderRequest {
-                            account_id, filter_contract_id_list: vec![10000002],
+                            account_id, filter_contract_id_list: vec![CONTRACT],
                         };
-                        if let Err(e) = client_arc.cancel_all_orders(&cancel_req).await {
-                            tracing::warn!("⚠️ [EX-v3] Cancel err: {:?}", e);
-                        }
-
-                        // EdgeX 限流: 2 req/2s，在 cancel 后延迟 1.2 秒再提交新订单
-                        tokio::time::sleep(tokio::time::Duration::from_millis(1200)).await;
+                        if let Some(sim) = &dry_run {
+                            sim.cancel_all();
+                            execution.lock().on_cancel_confirmed();
+                        } else if let Some(client_arc) = &client_arc {
+                            // Cancel our own quotes by id when all are known, else sweep the contract
+                            let ids = live_orders.lock().take_for_cancel();
+                            let cancelled = match ids {
+                                Some(ids) if ids.is_empty() => None,
+                                Some(ids) => Some(client_arc.cancel_orders_by_id(account_id, &ids).await),
+                                None => Some(client_arc.cancel_all_orders(&cancel_req).await),
+                            };
+                            if let Some(res) = cancelled {
+                                match res {
+                                    Ok(_) => execution.lock().on_cancel_confirmed(),
+                                    Err(e) => {
+                                        tracing::warn!("⚠️ [EX-v3] Cancel err: {}", e);
+                                        live_orders.lock().mark_unknown();
+                                    }
+                                }
 
-                        // === DYNAMIC SPREAD ===
-                        let base_spread = f64::max(cfg.min_spread_bps, vol_bps * cfg.vol_multiplier);
-                        let mut bid_spread = base_spread;
-                        let mut ask_spread = base_spread;
-                        if momentum > cfg.momentum_threshold_bps {
-                            bid_spread *= cfg.momentum_spread_mult;
-                        } else if momentum < -cfg.momentum_threshold_bps {
-                            ask_spread *= cfg.momentum_spread_mult;
+                                // EdgeX 限流: 2 req/2s，在 cancel 后延迟 1.2 秒再提交新订单
+                                tokio::time::sleep(tokio::time::Duration::from_millis(1200)).await;
+                            }
                         }
 
-                        let skew_factor = live_pos / max_position;
-                        let skew_shift = skew_factor * base_spread * 0.5;
-                        let skewed_mid = mid_price * (1.0 - skew_shift / 10_000.0);
-                        let bid_price = skewed_mid * (1.0 - bid_spread / 10_000.0);
-                        let ask_price = skewed_mid * (1.0 + ask_spread / 10_000.0);
+                        // === DYNAMIC SPREAD ===
+                        let quote = compute_quotes(&cfg, &QuoteInputs {
+                            mid: mid_price,
+                            vol_bps,
+                            momentum_bps: momentum,
+                            position: live_pos,
+                            max_position,
+                            imbalance,
+                            funding_rate,
+                            secs_to_funding,
+                        });
+                        let quote = defense.apply(mid_price, quote);
+                        let (bid_price, ask_price) = (quote.bid_price, quote.ask_price);
+                        let (bid_spread, ask_spread) = (quote.bid_spread_bps, quote.ask_spread_bps);
+                        let markout_summary = {
+                            let mut m = markout.lock();
+                            m.set_quotes(bid_price, ask_price);
+                            m.summary()
+                        };
 
                         // === SIZING ===
                         let mut bid_size = base_size;
                         let mut ask_size = base_size;
                         if live_pos >= max_position { bid_size = 0.0; }
                         if live_pos <= -max_position { ask_size = 0.0; }
+                        // Free margin caps the side(s) adding exposure; reducing quotes need none
+                        if live_pos >= 0.0 { bid_size = bid_size.min(margin_cap); }
+                        if live_pos <= 0.0 { ask_size = ask_size.min(margin_cap); }
 
-                        tracing::info!("🔌v3 Vol={:.1} Mom={:.1} | Bid:{:.2}@{:.2}(sp={:.0}) Ask:{:.2}@{:.2}(sp={:.0}) Pos={:.3} MaxPos={:.3}",
-                            vol_bps, momentum, bid_size, bid_price, bid_spread, ask_size, ask_price, ask_spread, live_pos, max_position);
+                        tracing::info!("🔌v3 Vol={:.1} Mom={:.1} Imb={:+.2} | Bid:{:.2}@{:.2}(sp={:.0}) Ask:{:.2}@{:.2}(sp={:.0}) Pos={:.3} MaxPos={:.3}",
+                            vol_bps, momentum, imbalance, bid_size, bid_price, bid_spread, ask_size, ask_price, ask_spread, live_pos, max_position);
+                        tracing::info!("🧪 [EX-v3] Markout {} | Defense={}", markout_summary, defense);
 
                         // Submit orders
-                        let synthetic_id = "0x4554482d3900000000000000000000";
-                        let collateral_id = "0x2ce625e94458d39dd0bf3b45a843544dd4a14b8169045a3a3d15aa564b936c5";
-                        let fee_rate = 0.00034_f64;
-                        let expire_time_ms = chrono::Utc::now().timestamp_millis() as u64 + (30 * 24 * 60 * 60 * 1000);
-                        let expire_time_hours = expire_time_ms / (60 * 60 * 1000);
-
-                        let mut futures = Vec::new();
-                        for &(is_buy, price, size_eth) in &[(true, bid_price, bid_size), (false, ask_price, ask_size)] {
-                            if size_eth < cfg.min_order_size.max(0.01) { continue; }
-                            let client_arc = client_arc.clone();
-
-                            let req_future = async move {
-                                let price = round_to_tick(price, cfg.tick_size);
-                                let size_eth = round_to_tick(size_eth, cfg.step_size);
-                                let value_usd = price * size_eth;
-                                let amount_synthetic = (size_eth * 1_000_000_000.0) as u64;
-                                let amount_collateral = (value_usd * 1_000_000.0).round() as u64;
-                                let exact_fee = value_usd * fee_rate;
-                                let amount_fee_quantum = (exact_fee * 1_000_000.0).ceil();
-                                let amount_fee_str = format!("{:.6}", amount_fee_quantum / 1_000_000.0);
-                                let amount_fee = amount_fee_quantum as u64;
-                                let initial_nonce = rand::random::<u32>() as u64;
-                                let client_order_id = format!("MM-{}", initial_nonce);
-
-                                use sha2::{Sha256, Digest};
-                                let mut hasher = Sha256::new();
-                                hasher.update(client_order_id.as_bytes());
-                                let l2_nonce_hex = hex::encode(hasher.finalize());
-                                let l2_nonce = u64::from_str_radix(&l2_nonce_hex[..8], 16).unwrap();
-
-                                // === PHASE 2: CPU-BOUND CRYPTO ISOLATION ===
-                                // Move Starknet ECDSA signing to blocking thread pool to prevent
-                                // blocking Tokio worker threads and causing WebSocket disconnects
-                                let client_for_blocking = client_arc.clone();
-                                let crypto_result = tokio::task::spawn_blocking(move || {
-                                    let hash_result = client_for_blocking.signature_manager.calc_limit_order_hash(
-                                        synthetic_id, collateral_id, collateral_id,
-                                        is_buy, amount_synthetic, amount_collateral, amount_fee,
-                                        l2_nonce, account_id, expire_time_hours
-                                    );
-                                    match hash_result {
-                                        Ok(hash) => client_for_blocking.signature_manager.sign_l2_action(hash),
-                                        Err(e) => Err(e),
+                        let levels = quote_levels(
+                            &cfg, mid_price, &quote, bid_size, ask_size, live_pos, max_position,
+                        );
+                        let resting = |is_buy, price, size_eth| RestingQuote {
+                            is_buy,
+                            price,
+                            size: size_eth,
+                            decision_mid: mid_price,
+                            placed_at: Instant::now(),
+                        };
+                        let filters = OrderFilters::from_config(&cfg);
+                        let mut batch: Vec<QuoteLevel> = Vec::new();
+                        for level in levels {
+                            // Tick / lot rounding and venue minimums (min_order_size,
+                            // min_notional); outer levels that decayed below them are dropped
+                            let order = OrderBuilder::new(filters, level.is_buy, level.size)
+                                .limit(level.price)
+                                .post_only()
+                                .build();
+                            let Ok(order) = order else { continue };
+                            let level = QuoteLevel {
+                                price: order.price.unwrap_or(level.price),
+                                size: order.qty,
+                                ..level
+                            };
+                            if let Some(sim) = &dry_run {
+                                sim.place(level.is_buy, level.size, level.price, mid_price);
+                                execution.lock().on_submitted(resting(level.is_buy, level.price, level.size));
+                                continue;
+                            }
+                            batch.push(level);
+                        }
+
+                        // Sign the ladder, submit it as one batch; post-only rejects are
+                        // repriced off the current BBO and resubmitted once as a second batch
+                        let Some(client_arc) = &client_arc else { return };
+                        let mut repriced = false;
+                        while !batch.is_empty() {
+                            let signed = futures::future::join_all(batch.iter().map(|l| {
+                                sign_quote(client_arc, account_id, l, &cfg)
+                            }))
+                            .await;
+                            let (quotes, reqs): (Vec<QuoteLevel>, Vec<CreateOrderRequest>) = batch
+                                .into_iter()
+                                .zip(signed)
+                                .filter_map(|(l, req)| req.map(|req| (l, req)))
+                                .unzip();
+                            let results = client_arc.create_orders_batch(&reqs).await;
+                            let mut retry = Vec::new();
+                            for ((l, req), res) in quotes.into_iter().zip(&reqs).zip(results) {
+                                let side = if l.is_buy { "Bid" } else { "Ask" };
+                                match res {
+                                    Ok(resp) => {
+                                        tracing::info!("✅ [EX-v3] {:?}: order {} ({})", side, resp.order_id, resp.client_order_id);
+                                        let mut orders = live_orders.lock();
+                                        orders.ids.push(resp.order_id);
+                                        orders.ages.on_placed(resp.order_id, Instant::now());
+                                        drop(orders);
+                                        execution.lock().on_submitted(resting(l.is_buy, l.price, l.size));
+                                    }
+                                    Err(e) if !repriced && e.is_post_only_rejection() => {
+                                        // Book moved through us: one retry off the current BBO
+                                        match live_bbo.as_ref().and_then(|b| b.reprice(l.is_buy, l.price, cfg.tick_size)) {
+                                            Some(p) => {
+                                                tracing::info!("🔁 [EX-v3] {:?} post-only crossed @{:.2}, repricing @{:.2}", side, l.price, p);
+                                                reprices.record();
+                                                retry.push(QuoteLevel { price: p, ..l });
+                                            }
+                                            None => tracing::warn!("🚫 [EX-v3] {:?} post-only crossed @{:.2}, no fresh BBO", side, l.price),
+                                        }
+                                    }
+                                    Err(ClientError::Rejected { code, msg }) => {
+                                        tracing::warn!("🚫 [EX-v3] {:?} rejected: {} {}", side, code, msg);
                                     }
-                                }).await;
-
-                                if let Ok(Ok(l2_sig)) = crypto_result {
-                                    let req = CreateOrderRequest {
-                                        price: format_price(price, cfg.tick_size),
-                                        size: format_size(size_eth, cfg.step_size),
-                                        r#type: OrderType::Limit,
-                                        time_in_force: TimeInForce::PostOnly,
-                                        reduce_only: false,
-                                        account_id, contract_id: 10000002,
-                                        side: if is_buy { OrderSide::Buy } else { OrderSide::Sell },
-                                        client_order_id, expire_time: expire_time_ms - 864_000_000,
-                                        l2_nonce, l2_value: format!("{:.4}", value_usd),
-                                        l2_size: format_size(size_eth, cfg.step_size),
-                                        l2_limit_fee: amount_fee_str,
-                                        l2_expire_time: expire_time_ms,
-                                        l2_signature: l2_sig,
-                                    };
-                                    match client_arc.create_order(&req).await {
-                                        Ok(resp) => tracing::info!("✅ [EX-v3] {:?}: {}", if is_buy {"Bid"} else {"Ask"}, resp),
-                                        Err(e) => tracing::error!("❌ [EX-v3] {:?}: {:?}", if is_buy {"Bid"} else {"Ask"}, e),
+                                    Err(e) if e.is_unknown_outcome() => {
+                                        tracing::warn!("⚠️ [EX-v3] {} {}: outcome unknown ({}), resolving", side, req.client_order_id, e);
+                                        live_orders.lock().mark_unknown();
+                                        resolve_unknown_order(client_arc, account_id, &req.client_order_id).await;
                                     }
-                                } else {
-                                    tracing::error!("❌ [EX-v3] Crypto signing failed for {:?}", if is_buy {"Bid"} else {"Ask"});
+                                    Err(e) => tracing::error!("❌ [EX-v3] {:?} failed: {}", side, e),
                                 }
-                            };
-                            futures.push(req_future);
+                            }
+                            repriced = true;
+                            batch = retry;
                         }
-                        futures::future::join_all(futures).await;
                     });
                 }
             }
         }
     }
 
+    fn metrics(&self) -> &ExecutionMetrics {
+        &self.exec_metrics
+    }
+
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        MmState {
+            symbol_id: self.symbol_id,
+            last_mid: self.last_mid,
+            last_quoted_mid: self.last_quoted_mid,
+            mid_history: self.mid_history.iter().copied().collect(),
+            vol: self.vol.snapshot(),
+            max_position: self.max_position,
+            base_size: self.base_size,
+            stop_loss_usd: self.stop_loss_usd,
+            account_equity: self.account_equity_usd,
+        }
+        .encode()
+    }
+
+    fn load_state(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let state = MmState::decode(bytes, self.symbol_id)?;
+        let skip = state.mid_history.len().saturating_sub(self.cfg.vol_window);
+        self.mid_history = state.mid_history.into_iter().skip(skip).collect();
+        self.vol.restore(state.vol);
+        self.last_mid = state.last_mid;
+        self.last_quoted_mid = state.last_quoted_mid;
+        self.max_position = state.max_position;
+        self.base_size = state.base_size;
+        self.stop_loss_usd = state.stop_loss_usd;
+        self.account_equity_usd = state.account_equity;
+        Ok(())
+    }
+
     fn on_shutdown(&mut self) -> Pin<Box<dyn std::future::Future<Output = ()> + Send + '_>> {
         let client_opt = self.edgex_client.clone();
         let account_id = self.account_id;
+        let dry_run = self.dry_run.clone();
         Box::pin(async move {
-            if let Some(client) = client_opt {
+            if let Some(sim) = dry_run {
+                sim.cancel_all();
+                tracing::info!(
+                    "🧪 [EX-v3] Dry run finished | rPnL=${:.4}",
+                    sim.realized_pnl()
+                );
+            } else if let Some(client) = client_opt {
                 tracing::info!("♻️ [EX-v3] Shutting down: Canceling all orders...");
                 use crate::edgex_api::model::CancelAllOrderRequest;
                 let req = CancelAllOrderRequest {
                     account_id,
-                    filter_contract_id_list: vec![10000002],
+                    filter_contract_id_list: vec![CONTRACT],
                 };
                 let _ = client.cancel_all_orders(&req).await;
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, EXCH_EDGEX, SYM_ETH};
+
+    #[test]
+    fn test_live_orders_fall_back_to_sweep_when_state_unknown() {
+        let mut live = LiveOrders::default();
+        // Fresh start: leftovers from a previous run may rest on the book
+        assert_eq!(live.take_for_cancel(), None);
+
+        live.ids.extend([11, 12]);
+        assert_eq!(live.take_for_cancel(), Some(vec![11, 12]));
+        assert_eq!(live.take_for_cancel(), Some(vec![]));
+
+        live.ids.push(13);
+        live.mark_unknown();
+        assert_eq!(live.take_for_cancel(), None);
+        assert!(live.ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_quotes_without_credentials() {
+        let cfg = ExchangeConfig {
+            requote_interval_ms: 0,
+            dry_run: true,
+            ..AppConfig::default().edgex
+        };
+        let account = ExchangeStateCache::new("EX");
+        account.publish_margin(MarginSummary {
+            net_equity: 10_000.0,
+            used_margin: 0.0,
+            available_margin: 10_000.0,
+        });
+        let mut mm = MarketMakerStrategy::new(EXCH_EDGEX, SYM_ETH, 25.0, cfg, None, 0, account);
+
+        let bbo = ShmBboMessage {
+            bid_price: 1999.0,
+            ask_price: 2001.0,
+            timestamp_ns: 1_000_000_000,
+            ..Default::default()
+        };
+        mm.on_bbo_update(SYM_ETH, EXCH_EDGEX, &bbo);
+        mm.on_idle();
+
+        // Both sides are routed to the simulator and filled there
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while mm.execution.lock().metrics().orders_submitted < 2 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(mm.execution.lock().metrics().orders_submitted >= 2);
+        let sim = mm.dry_run.as_ref().unwrap();
+        assert!(
+            sim.realized_pnl() < 0.0,
+            "slippage is charged on the round trip"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Execution quality of the market makers' quotes (fill rate, slippage).
+//!
+//! The venues give us no per-order fill callback yet, so the quote task infers
+//! fills from the position change between requote cycles: a long delta fills
+//! the resting bids innermost-first, a short delta the asks. Quotes still
+//! resting when a cancel is confirmed count as cancelled unfilled.
+
+use std::time::Instant;
+
+/// Per-strategy execution counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExecutionMetrics {
+    pub orders_submitted: u64,
+    pub orders_filled: u64,
+    pub orders_cancelled_unfilled: u64,
+    /// Sum over fills of (fill − decision mid) in bps, signed so positive = cost
+    pub total_slippage_bps: f64,
+    /// Submit → fill detection (an upper bound: fills are seen once per cycle)
+    pub avg_fill_latency_ms: f64,
+}
+
+impl ExecutionMetrics {
+    pub const EMPTY: Self = Self {
+        orders_submitted: 0,
+        orders_filled: 0,
+        orders_cancelled_unfilled: 0,
+        total_slippage_bps: 0.0,
+        avg_fill_latency_ms: 0.0,
+    };
+
+    /// Filled / submitted (0 before the first order)
+    pub fn fill_rate(&self) -> f64 {
+        if self.orders_submitted == 0 {
+            return 0.0;
+        }
+        self.orders_filled as f64 / self.orders_submitted as f64
+    }
+
+    pub fn avg_slippage_bps(&self) -> f64 {
+        if self.orders_filled == 0 {
+            return 0.0;
+        }
+        self.total_slippage_bps / self.orders_filled as f64
+    }
+
+    fn record_fill(&mut self, slippage_bps: f64, latency_ms: f64) {
+        self.orders_filled += 1;
+        self.total_slippage_bps += slippage_bps;
+        self.avg_fill_latency_ms +=
+            (latency_ms - self.avg_fill_latency_ms) / self.orders_filled as f64;
+    }
+}
+
+/// A quote submitted this cycle, with the mid it was priced from.
+#[derive(Debug, Clone, Copy)]
+pub struct RestingQuote {
+    pub is_buy: bool,
+    pub price: f64,
+    pub size: f64,
+    pub decision_mid: f64,
+    pub placed_at: Instant,
+}
+
+/// Shared between a strategy and its quote tasks (`Arc<Mutex<_>>`).
+#[derive(Debug, Default)]
+pub struct ExecutionTracker {
+    metrics: ExecutionMetrics,
+    resting: Vec<RestingQuote>,
+    last_position: Option<f64>,
+}
+
+impl ExecutionTracker {
+    pub fn metrics(&self) -> ExecutionMetrics {
+        self.metrics
+    }
+
+    pub fn on_submitted(&mut self, quote: RestingQuote) {
+        self.metrics.orders_submitted += 1;
+        self.resting.push(quote);
+    }
+
+    /// Amended in place: same order, new price.
+    pub fn on_amended(&mut self, is_buy: bool, price: f64, size: f64, decision_mid: f64) {
+        if let Some(q) = self.resting.iter_mut().find(|q| q.is_buy == is_buy) {
+            q.price = price;
+            q.size = size;
+            q.decision_mid = decision_mid;
+        }
+    }
+
+    /// Attribute the position change since the last call to resting quotes.
+    pub fn on_position(&mut self, position: f64, now: Instant) {
+        let Some(last) = self.last_position.replace(position) else {
+            return;
+        };
+        let mut remaining = (position - last).abs();
+        let is_buy = position > last;
+        if remaining < 1e-9 {
+            return;
+        }
+        // Innermost quote fills first
+        self.resting.sort_by(|a, b| {
+            let (a, b) = if is_buy { (b, a) } else { (a, b) };
+            a.price.total_cmp(&b.price)
+        });
+        let mut i = 0;
+        while i < self.resting.len() && remaining > 1e-9 {
+            let q = self.resting[i];
+            if q.is_buy != is_buy {
+                i += 1;
+                continue;
+            }
+            let sign = if is_buy { 1.0 } else { -1.0 };
+            let slippage_bps = sign * (q.price - q.decision_mid) / q.decision_mid * 10_000.0;
+            let latency_ms = now.saturating_duration_since(q.placed_at).as_secs_f64() * 1000.0;
+            self.metrics.record_fill(slippage_bps, latency_ms);
+            remaining -= q.size;
+            self.resting.remove(i);
+        }
+    }
+
+    /// Cancel confirmed by the venue: whatever is still resting never filled.
+    pub fn on_cancel_confirmed(&mut self) {
+        self.metrics.orders_cancelled_unfilled += self.resting.len() as u64;
+        self.resting.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_position_delta_fills_innermost_quotes_then_cancel_counts_rest() {
+        let t0 = Instant::now();
+        let quote = |is_buy, price| RestingQuote {
+            is_buy,
+            price,
+            size: 0.1,
+            decision_mid: 2000.0,
+            placed_at: t0,
+        };
+        let mut tracker = ExecutionTracker::default();
+        tracker.on_position(0.0, t0);
+        tracker.on_submitted(quote(true, 1998.0));
+        tracker.on_submitted(quote(true, 1999.0));
+        tracker.on_submitted(quote(false, 2001.0));
+
+        // Long 0.1: the 1999 bid filled, 5 bps inside mid
+        tracker.on_position(0.1, t0 + Duration::from_millis(400));
+        let m = tracker.metrics();
+        assert_eq!((m.orders_submitted, m.orders_filled), (3, 1));
+        assert!((m.avg_slippage_bps() + 5.0).abs() < 1e-9);
+        assert!((m.avg_fill_latency_ms - 400.0).abs() < 1.0);
+
+        tracker.on_cancel_confirmed();
+        let m = tracker.metrics();
+        assert_eq!(m.orders_cancelled_unfilled, 2);
+        assert!((m.fill_rate() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(ExecutionMetrics::EMPTY.fill_rate(), 0.0);
+    }
+}
@@ -0,0 +1,788 @@
+//! Funding-rate arbitrage between two perp venues (Backpack ↔ EdgeX).
+//!
+//! Every `check_interval_secs` both predicted funding rates are fetched and
+//! annualized with each venue's settlement period. When the differential
+//! exceeds `entry_apr`, a hedged pair is opened with IOC limits: long on the
+//! venue with the lower rate (receives / pays less), short on the higher one.
+//! The pair is held while the differential persists and unwound with
+//! reduce-only IOCs once it compresses below `exit_apr` or `max_hold_secs`
+//! passes. Funding captured at each settlement is accrued per leg, and every
+//! entry/exit is appended to a JSONL journal.
+
+use crate::config::{AppConfig, EXCH_BACKPACK, EXCH_EDGEX, FundingArbConfig};
+use crate::exchange::{OrderResult, Side};
+use crate::exchanges::backpack::client::BackpackClient;
+use crate::exchanges::backpack::gateway::BackpackGateway;
+use crate::exchanges::edgex::client::EdgeXClient;
+use crate::exchanges::edgex::gateway::{EdgeXConfig, EdgeXGateway};
+use crate::shm_reader::ShmBboMessage;
+use crate::strategy::Strategy;
+use crate::types::FundingRate;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::io::Write;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+
+const SECS_PER_YEAR: f64 = 365.0 * 86_400.0;
+
+/// A perp venue as seen by the funding arb: funding, equity and taker orders.
+#[async_trait]
+pub trait FundingVenue: Send + Sync {
+    fn name(&self) -> &str;
+    /// Predicted funding for the traded contract
+    async fn funding_rate(&self) -> Result<FundingRate>;
+    /// Account equity in USD
+    async fn equity(&self) -> Result<f64>;
+    /// Marketable IOC limit order; `reduce_only` for unwinds. Resolves once
+    /// the IOC is done, with what actually executed; fails with
+    /// `TradingError::UnknownOutcome` when that cannot be confirmed.
+    async fn take(&self, side: Side, size: f64, price: f64, reduce_only: bool) -> Result<TakeFill>;
+}
+
+/// Outcome of an IOC taker order.
+#[derive(Debug, Clone)]
+pub struct TakeFill {
+    pub order: OrderResult,
+    /// Executed base quantity (0 = expired unfilled)
+    pub filled: f64,
+    /// Volume-weighted fill price (0 when nothing filled)
+    pub avg_price: f64,
+}
+
+/// One side of the arb: venue, BBO exchange id and settlement period.
+pub struct Leg {
+    pub venue: Arc<dyn FundingVenue>,
+    pub exchange_id: u8,
+    pub funding_interval_secs: u64,
+}
+
+/// Per-period rate → annualized rate.
+#[inline]
+pub fn annualize(rate: f64, interval_secs: u64) -> f64 {
+    rate * SECS_PER_YEAR / interval_secs.max(1) as f64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArbDecision {
+    Idle {
+        diff_apr: f64,
+    },
+    Enter {
+        long: usize,
+        short: usize,
+        diff_apr: f64,
+    },
+    Hold {
+        diff_apr: f64,
+    },
+    Exit {
+        reason: &'static str,
+        diff_apr: f64,
+    },
+}
+
+/// Decide the next action from the annualized rates of legs 0 and 1.
+///
+/// `held` is `(long, short, held_secs)` for an open pair.
+pub fn decide(
+    cfg: &FundingArbConfig,
+    apr: [f64; 2],
+    held: Option<(usize, usize, u64)>,
+) -> ArbDecision {
+    match held {
+        Some((long, short, held_secs)) => {
+            // Positive while the short leg still collects more than the long leg pays
+            let diff_apr = apr[short] - apr[long];
+            if held_secs >= cfg.max_hold_secs {
+                ArbDecision::Exit {
+                    reason: "max_hold",
+                    diff_apr,
+                }
+            } else if diff_apr < cfg.exit_apr {
+                ArbDecision::Exit {
+                    reason: "compressed",
+                    diff_apr,
+                }
+            } else {
+                ArbDecision::Hold { diff_apr }
+            }
+        }
+        None => {
+            let (long, short) = if apr[0] <= apr[1] { (0, 1) } else { (1, 0) };
+            let diff_apr = apr[short] - apr[long];
+            if diff_apr >= cfg.entry_apr {
+                ArbDecision::Enter {
+                    long,
+                    short,
+                    diff_apr,
+                }
+            } else {
+                ArbDecision::Idle { diff_apr }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct OpenPair {
+    long: usize,
+    short: usize,
+    size: f64,
+    entry_px: [f64; 2],
+    entry_diff_apr: f64,
+    opened_at: Instant,
+    /// Funding in force for each leg's current period (accrued when it settles)
+    period: [FundingRate; 2],
+    captured_usd: f64,
+}
+
+/// Shared state and logic; the `Strategy` wrapper drives `step` from `on_idle`.
+pub struct FundingArbCore {
+    cfg: FundingArbConfig,
+    legs: [Leg; 2],
+    pair: Mutex<Option<OpenPair>>,
+}
+
+impl FundingArbCore {
+    pub fn new(cfg: FundingArbConfig, legs: [Leg; 2]) -> Self {
+        Self {
+            cfg,
+            legs,
+            pair: Mutex::new(None),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.pair.lock().is_some()
+    }
+
+    /// Funding accrued so far on the open pair (USD, positive = received).
+    pub fn captured_funding_usd(&self) -> Option<f64> {
+        self.pair.lock().as_ref().map(|p| p.captured_usd)
+    }
+
+    /// One evaluation cycle at the given mids (indexed like `legs`).
+    pub async fn step(&self, mids: [f64; 2]) -> Result<ArbDecision> {
+        let (r0, r1) = futures::future::join(
+            self.legs[0].venue.funding_rate(),
+            self.legs[1].venue.funding_rate(),
+        )
+        .await;
+        let rates = [r0?, r1?];
+        let apr = [
+            annualize(rates[0].rate, self.legs[0].funding_interval_secs),
+            annualize(rates[1].rate, self.legs[1].funding_interval_secs),
+        ];
+
+        let held = {
+            let mut guard = self.pair.lock();
+            guard.as_mut().map(|pair| {
+                Self::accrue(pair, &rates, mids);
+                (pair.long, pair.short, pair.opened_at.elapsed().as_secs())
+            })
+        };
+
+        let decision = decide(&self.cfg, apr, held);
+        match decision {
+            ArbDecision::Enter {
+                long,
+                short,
+                diff_apr,
+            } => self.enter(long, short, diff_apr, mids, rates).await?,
+            ArbDecision::Exit { reason, diff_apr } => self.exit(reason, diff_apr, mids).await?,
+            ArbDecision::Idle { .. } | ArbDecision::Hold { .. } => {}
+        }
+        Ok(decision)
+    }
+
+    /// Book funding for every leg whose settlement passed since the last check.
+    fn accrue(pair: &mut OpenPair, rates: &[FundingRate; 2], mids: [f64; 2]) {
+        for i in 0..2 {
+            if rates[i].next_funding_ms > pair.period[i].next_funding_ms {
+                // Longs pay a positive rate, shorts receive it
+                let sign = if i == pair.long { -1.0 } else { 1.0 };
+                let notional = pair.size * mids[i];
+                pair.captured_usd += sign * pair.period[i].rate * notional;
+                pair.period[i] = rates[i];
+            }
+        }
+    }
+
+    fn price(&self, side: Side, mid: f64) -> f64 {
+        let slip = self.cfg.slippage_bps / 10_000.0;
+        match side {
+            Side::Buy => mid * (1.0 + slip),
+            Side::Sell => mid * (1.0 - slip),
+        }
+    }
+
+    async fn enter(
+        &self,
+        long: usize,
+        short: usize,
+        diff_apr: f64,
+        mids: [f64; 2],
+        rates: [FundingRate; 2],
+    ) -> Result<()> {
+        let (e0, e1) =
+            futures::future::join(self.legs[0].venue.equity(), self.legs[1].venue.equity()).await;
+        let equity = e0?.min(e1?);
+        let notional = (equity * self.cfg.risk_fraction).min(self.cfg.max_notional_usd);
+        let ref_mid = mids[long].max(mids[short]);
+        let step = self.cfg.size_step.max(f64::EPSILON);
+        let size = (notional / ref_mid / step).floor() * step;
+        if size < self.cfg.min_size {
+            info!(
+                "💤 [FUND] Diff {:.1}% APR but size {:.4} < min {} (equity ${:.2})",
+                diff_apr * 100.0,
+                size,
+                self.cfg.min_size,
+                equity
+            );
+            return Ok(());
+        }
+
+        let (long_leg, short_leg) = (&self.legs[long], &self.legs[short]);
+        let (long_res, short_res) = futures::future::join(
+            long_leg
+                .venue
+                .take(Side::Buy, size, self.price(Side::Buy, mids[long]), false),
+            short_leg
+                .venue
+                .take(Side::Sell, size, self.price(Side::Sell, mids[short]), false),
+        )
+        .await;
+
+        let long_px = self.price(Side::Sell, mids[long]);
+        let short_px = self.price(Side::Buy, mids[short]);
+        match (long_res, short_res) {
+            (Ok(long_fill), Ok(short_fill)) => {
+                // IOCs can fill partly or unevenly: only the common part is a hedge
+                let size = long_fill.filled.min(short_fill.filled);
+                self.unwind(long_leg, Side::Sell, long_fill.filled - size, long_px)
+                    .await;
+                self.unwind(short_leg, Side::Buy, short_fill.filled - size, short_px)
+                    .await;
+                if size <= 0.0 {
+                    info!(
+                        "💨 [FUND] Entry missed: filled {} on {} / {} on {}",
+                        long_fill.filled,
+                        long_leg.venue.name(),
+                        short_fill.filled,
+                        short_leg.venue.name()
+                    );
+                    return Ok(());
+                }
+                info!(
+                    "📈 [FUND] Opened {:.4}: long {} / short {} | diff {:.1}% APR",
+                    size,
+                    long_leg.venue.name(),
+                    short_leg.venue.name(),
+                    diff_apr * 100.0
+                );
+                let mut entry_px = [0.0; 2];
+                entry_px[long] = long_fill.avg_price;
+                entry_px[short] = short_fill.avg_price;
+                *self.pair.lock() = Some(OpenPair {
+                    long,
+                    short,
+                    size,
+                    entry_px,
+                    entry_diff_apr: diff_apr,
+                    opened_at: Instant::now(),
+                    period: rates,
+                    captured_usd: 0.0,
+                });
+                self.journal(serde_json::json!({
+                    "event": "entry",
+                    "long": long_leg.venue.name(),
+                    "short": short_leg.venue.name(),
+                    "size": size,
+                    "long_px": long_fill.avg_price,
+                    "short_px": short_fill.avg_price,
+                    "long_rate": rates[long].rate,
+                    "short_rate": rates[short].rate,
+                    "diff_apr": diff_apr,
+                }));
+                Ok(())
+            }
+            (long_res, short_res) => {
+                // Legged: flatten whatever the side that went through executed
+                if let Ok(fill) = &long_res {
+                    self.unwind(long_leg, Side::Sell, fill.filled, long_px)
+                        .await;
+                }
+                if let Ok(fill) = &short_res {
+                    self.unwind(short_leg, Side::Buy, fill.filled, short_px)
+                        .await;
+                }
+                let err = long_res
+                    .err()
+                    .or(short_res.err())
+                    .unwrap_or_else(|| anyhow!("unknown"));
+                Err(anyhow!("funding arb entry failed: {}", err))
+            }
+        }
+    }
+
+    /// Reduce-only IOC closing `qty` of an unhedged entry leg (no-op at 0).
+    async fn unwind(&self, leg: &Leg, side: Side, qty: f64, price: f64) {
+        if qty <= 0.0 {
+            return;
+        }
+        if let Err(e) = leg.venue.take(side, qty, price, true).await {
+            error!(
+                "🚨 [FUND] Unwind of {} on {} failed: {}",
+                qty,
+                leg.venue.name(),
+                e
+            );
+        }
+    }
+
+    async fn exit(&self, reason: &str, diff_apr: f64, mids: [f64; 2]) -> Result<()> {
+        let Some(pair) = self.pair.lock().clone() else {
+            return Ok(());
+        };
+        let (long_leg, short_leg) = (&self.legs[pair.long], &self.legs[pair.short]);
+        let (long_res, short_res) = futures::future::join(
+            long_leg.venue.take(
+                Side::Sell,
+                pair.size,
+                self.price(Side::Sell, mids[pair.long]),
+                true,
+            ),
+            short_leg.venue.take(
+                Side::Buy,
+                pair.size,
+                self.price(Side::Buy, mids[pair.short]),
+                true,
+            ),
+        )
+        .await;
+        if let Err(e) = long_res.and(short_res) {
+            // Reduce-only makes a retry of the already-closed leg harmless
+            return Err(anyhow!("funding arb unwind failed (will retry): {}", e));
+        }
+
+        let basis_pnl = (mids[pair.long] - pair.entry_px[pair.long]) * pair.size
+            + (pair.entry_px[pair.short] - mids[pair.short]) * pair.size;
+        info!(
+            "📉 [FUND] Closed ({}) after {}s | funding ${:+.4} basis ${:+.4} | diff {:.1}% (entry {:.1}%)",
+            reason,
+            pair.opened_at.elapsed().as_secs(),
+            pair.captured_usd,
+            basis_pnl,
+            diff_apr * 100.0,
+            pair.entry_diff_apr * 100.0
+        );
+        self.journal(serde_json::json!({
+            "event": "exit",
+            "reason": reason,
+            "long": long_leg.venue.name(),
+            "short": short_leg.venue.name(),
+            "size": pair.size,
+            "long_px": mids[pair.long],
+            "short_px": mids[pair.short],
+            "held_secs": pair.opened_at.elapsed().as_secs(),
+            "diff_apr": diff_apr,
+            "captured_funding_usd": pair.captured_usd,
+            "basis_pnl_usd": basis_pnl,
+        }));
+        *self.pair.lock() = None;
+        Ok(())
+    }
+
+    fn journal(&self, mut record: serde_json::Value) {
+        if self.cfg.journal_path.is_empty() {
+            return;
+        }
+        record["ts_ms"] = chrono::Utc::now().timestamp_millis().into();
+        let path = std::path::Path::new(&self.cfg.journal_path);
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut f| writeln!(f, "{}", record));
+        if let Err(e) = result {
+            warn!(
+                "⚠️ [FUND] Journal write failed ({}): {}",
+                self.cfg.journal_path, e
+            );
+        }
+    }
+}
+
+pub struct FundingArbStrategy {
+    core: Arc<FundingArbCore>,
+    symbol_id: u16,
+    check_interval: Duration,
+    mids: [f64; 2],
+    last_check: Option<Instant>,
+    busy: Arc<AtomicBool>,
+}
+
+impl FundingArbStrategy {
+    pub fn new(cfg: FundingArbConfig, legs: [Leg; 2]) -> Self {
+        Self {
+            symbol_id: cfg.symbol_id,
+            check_interval: Duration::from_secs(cfg.check_interval_secs),
+            core: Arc::new(FundingArbCore::new(cfg, legs)),
+            mids: [0.0; 2],
+            last_check: None,
+            busy: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Wrap the shared clients in gateways; EdgeX order params come from
+    /// `config.toml` + `EDGEX_ACCOUNT_ID` (see `Credentials::load`).
+    pub fn from_clients(
+        config: &AppConfig,
+        backpack_client: Arc<BackpackClient>,
+        edgex_client: Arc<EdgeXClient>,
+    ) -> Result<Self> {
+        let cfg = config.funding_arb.clone();
+        let backpack = BackpackGateway::new(backpack_client, cfg.backpack_symbol.clone())
+            .with_precision(config.backpack.tick_size, config.backpack.step_size);
+        let edgex = EdgeXGateway::new(edgex_client, EdgeXConfig::from_env()?);
+
+        info!(
+            "💸 [FUND] Funding arb armed: {} ↔ EdgeX | entry {:.0}% / exit {:.0}% APR",
+            cfg.backpack_symbol,
+            cfg.entry_apr * 100.0,
+            cfg.exit_apr * 100.0
+        );
+        Ok(Self::new(
+            cfg,
+            [
+                Leg {
+                    venue: Arc::new(backpack),
+                    exchange_id: EXCH_BACKPACK,
+                    funding_interval_secs: config.backpack.funding_interval_secs,
+                },
+                Leg {
+                    venue: Arc::new(edgex),
+                    exchange_id: EXCH_EDGEX,
+                    funding_interval_secs: config.edgex.funding_interval_secs,
+                },
+            ],
+        ))
+    }
+}
+
+impl Strategy for FundingArbStrategy {
+    fn name(&self) -> &str {
+        "FundingArb"
+    }
+
+    fn on_bbo_update(&mut self, symbol_id: u16, exchange_id: u8, bbo: &ShmBboMessage) {
+        if symbol_id != self.symbol_id || bbo.bid_price <= 0.0 || bbo.ask_price <= 0.0 {
+            return;
+        }
+        for (i, leg) in self.core.legs.iter().enumerate() {
+            if leg.exchange_id == exchange_id {
+                self.mids[i] = (bbo.bid_price + bbo.ask_price) / 2.0;
+            }
+        }
+    }
+
+    fn on_idle(&mut self) {
+        if self.mids.iter().any(|m| *m <= 0.0) {
+            return;
+        }
+        if self
+            .last_check
+            .is_some_and(|t| t.elapsed() < self.check_interval)
+        {
+            return;
+        }
+        // One cycle in flight at a time; a slow REST round-trip must not double-enter
+        if self.busy.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+
+        let Ok(handle) = Handle::try_current() else {
+            self.busy.store(false, Ordering::Release);
+            return;
+        };
+        let core = self.core.clone();
+        let busy = self.busy.clone();
+        let mids = self.mids;
+        handle.spawn(async move {
+            match core.step(mids).await {
+                Ok(ArbDecision::Hold { diff_apr }) => info!(
+                    "⏳ [FUND] Holding | diff {:.1}% APR | funding ${:+.4}",
+                    diff_apr * 100.0,
+                    core.captured_funding_usd().unwrap_or(0.0)
+                ),
+                Ok(_) => {}
+                Err(e) => warn!("⚠️ [FUND] Cycle failed: {}", e),
+            }
+            busy.store(false, Ordering::Release);
+        });
+    }
+
+    fn on_shutdown(&mut self) -> Pin<Box<dyn std::future::Future<Output = ()> + Send + '_>> {
+        let core = self.core.clone();
+        let mids = self.mids;
+        Box::pin(async move {
+            if !core.is_open() {
+                return;
+            }
+            if !core.cfg.unwind_on_shutdown {
+                warn!("♻️ [FUND] Leaving hedged pair open (unwind_on_shutdown = false)");
+                return;
+            }
+            info!("♻️ [FUND] Shutting down: unwinding hedged pair...");
+            if let Err(e) = core.exit("shutdown", 0.0, mids).await {
+                error!("🚨 [FUND] Shutdown unwind failed: {}", e);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestVenue {
+        name: &'static str,
+        rate: Mutex<FundingRate>,
+        orders: Mutex<Vec<(Side, f64, bool)>>,
+        reject: AtomicBool,
+        // Quantity opening orders execute (None = all of it)
+        fill: Mutex<Option<f64>>,
+    }
+
+    impl TestVenue {
+        fn new(name: &'static str, rate: f64) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                rate: Mutex::new(FundingRate {
+                    rate,
+                    next_funding_ms: 1_000,
+                }),
+                orders: Mutex::new(Vec::new()),
+                reject: AtomicBool::new(false),
+                fill: Mutex::new(None),
+            })
+        }
+
+        fn settle(&self, new_rate: f64) {
+            let mut r = self.rate.lock();
+            r.next_funding_ms += 1_000;
+            r.rate = new_rate;
+        }
+    }
+
+    #[async_trait]
+    impl FundingVenue for TestVenue {
+        fn name(&self) -> &str {
+            self.name
+        }
+        async fn funding_rate(&self) -> Result<FundingRate> {
+            Ok(*self.rate.lock())
+        }
+        async fn equity(&self) -> Result<f64> {
+            Ok(10_000.0)
+        }
+        async fn take(
+            &self,
+            side: Side,
+            size: f64,
+            price: f64,
+            reduce_only: bool,
+        ) -> Result<TakeFill> {
+            if self.reject.load(Ordering::Relaxed) && !reduce_only {
+                return Err(anyhow!("rejected"));
+            }
+            self.orders.lock().push((side, size, reduce_only));
+            let filled = match *self.fill.lock() {
+                Some(fill) if !reduce_only => fill.min(size),
+                _ => size,
+            };
+            Ok(TakeFill {
+                order: OrderResult {
+                    tx_hash: "t".to_string(),
+                    client_order_index: 0,
+                },
+                filled,
+                avg_price: price,
+            })
+        }
+    }
+
+    fn cfg() -> FundingArbConfig {
+        FundingArbConfig {
+            journal_path: String::new(),
+            ..Default::default()
+        }
+    }
+
+    fn core(a: &Arc<TestVenue>, b: &Arc<TestVenue>) -> FundingArbCore {
+        let leg = |v: &Arc<TestVenue>, id| Leg {
+            venue: v.clone(),
+            exchange_id: id,
+            funding_interval_secs: 3600,
+        };
+        FundingArbCore::new(cfg(), [leg(a, 5), leg(b, 3)])
+    }
+
+    #[test]
+    fn test_decide_directions_and_exits() {
+        let cfg = cfg();
+        // 8760 periods/yr at 1h: 1 bp/h = 87.6% APR
+        assert!((annualize(0.0001, 3600) - 0.876).abs() < 1e-12);
+        assert!(matches!(
+            decide(&cfg, [0.30, 0.0], None),
+            ArbDecision::Enter {
+                long: 1,
+                short: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            decide(&cfg, [-0.10, 0.10], None),
+            ArbDecision::Enter {
+                long: 0,
+                short: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            decide(&cfg, [0.05, 0.0], None),
+            ArbDecision::Idle { .. }
+        ));
+        assert!(matches!(
+            decide(&cfg, [0.0, 0.10], Some((0, 1, 60))),
+            ArbDecision::Hold { .. }
+        ));
+        assert!(matches!(
+            decide(&cfg, [0.0, 0.01], Some((0, 1, 60))),
+            ArbDecision::Exit {
+                reason: "compressed",
+                ..
+            }
+        ));
+        assert!(matches!(
+            decide(&cfg, [0.0, 0.50], Some((0, 1, cfg.max_hold_secs))),
+            ArbDecision::Exit {
+                reason: "max_hold",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_enter_accrue_and_reduce_only_exit() {
+        let (bp, ex) = (TestVenue::new("bp", 0.0001), TestVenue::new("ex", 0.0));
+        let core = core(&bp, &ex);
+
+        // 87.6% APR differential: short bp (paying side for longs), long ex
+        let d = core.step([2000.0, 2000.0]).await.unwrap();
+        assert!(matches!(
+            d,
+            ArbDecision::Enter {
+                long: 1,
+                short: 0,
+                ..
+            }
+        ));
+        // min(equity) 10k × 10% = $1000 → 0.5 ETH
+        assert_eq!(*bp.orders.lock(), vec![(Side::Sell, 0.5, false)]);
+        assert_eq!(*ex.orders.lock(), vec![(Side::Buy, 0.5, false)]);
+
+        // bp settles at 1 bp: short 0.5 × $2000 receives $0.10
+        // (the rate in force when the pair opened, not the new one)
+        bp.settle(0.00005);
+        assert!(matches!(
+            core.step([2000.0, 2000.0]).await.unwrap(),
+            ArbDecision::Hold { .. }
+        ));
+        assert!((core.captured_funding_usd().unwrap() - 0.1).abs() < 1e-12);
+
+        // Differential compresses → reduce-only unwind of both legs
+        bp.settle(0.0);
+        let d = core.step([2000.0, 2000.0]).await.unwrap();
+        assert!(matches!(
+            d,
+            ArbDecision::Exit {
+                reason: "compressed",
+                ..
+            }
+        ));
+        assert_eq!(bp.orders.lock()[1], (Side::Buy, 0.5, true));
+        assert_eq!(ex.orders.lock()[1], (Side::Sell, 0.5, true));
+        assert!(!core.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_legged_entry_is_flattened() {
+        let (bp, ex) = (TestVenue::new("bp", 0.0001), TestVenue::new("ex", 0.0));
+        ex.reject.store(true, Ordering::Relaxed);
+        let arb = core(&bp, &ex);
+
+        assert!(arb.step([2000.0, 2000.0]).await.is_err());
+        assert_eq!(
+            *bp.orders.lock(),
+            vec![(Side::Sell, 0.5, false), (Side::Buy, 0.5, true)]
+        );
+        assert!(!arb.is_open());
+
+        // Rejected leg after a partial fill: only the executed 0.2 is bought back
+        let (bp, ex) = (TestVenue::new("bp", 0.0001), TestVenue::new("ex", 0.0));
+        ex.reject.store(true, Ordering::Relaxed);
+        *bp.fill.lock() = Some(0.2);
+        assert!(core(&bp, &ex).step([2000.0, 2000.0]).await.is_err());
+        assert_eq!(bp.orders.lock()[1], (Side::Buy, 0.2, true));
+    }
+
+    #[tokio::test]
+    async fn test_partial_entry_holds_matched_size_and_flattens_excess() {
+        let (bp, ex) = (TestVenue::new("bp", 0.0001), TestVenue::new("ex", 0.0));
+        *ex.fill.lock() = Some(0.3);
+        let arb = core(&bp, &ex);
+
+        // Short bp fills 0.5, long ex only 0.3: 0.2 of the short is bought back
+        assert!(matches!(
+            arb.step([2000.0, 2000.0]).await.unwrap(),
+            ArbDecision::Enter { .. }
+        ));
+        assert_eq!(
+            *bp.orders.lock(),
+            vec![(Side::Sell, 0.5, false), (Side::Buy, 0.2, true)]
+        );
+        assert_eq!(*ex.orders.lock(), vec![(Side::Buy, 0.5, false)]);
+        let pair = arb.pair.lock().clone().unwrap();
+        assert_eq!(pair.size, 0.3);
+        // Entry at the fill prices (the IOC limits here), not the mids
+        assert!(pair.entry_px[1] > 2000.0 && pair.entry_px[0] < 2000.0);
+
+        // Exit closes the hedged 0.3 on both venues
+        bp.settle(0.0);
+        arb.step([2000.0, 2000.0]).await.unwrap();
+        assert_eq!(bp.orders.lock()[2], (Side::Buy, 0.3, true));
+        assert_eq!(ex.orders.lock()[1], (Side::Sell, 0.3, true));
+
+        // Neither IOC fills: no pair, no unwinds
+        let (bp, ex) = (TestVenue::new("bp", 0.0001), TestVenue::new("ex", 0.0));
+        *bp.fill.lock() = Some(0.0);
+        *ex.fill.lock() = Some(0.0);
+        let missed = core(&bp, &ex);
+        missed.step([2000.0, 2000.0]).await.unwrap();
+        assert!(!missed.is_open());
+        assert_eq!(bp.orders.lock().len() + ex.orders.lock().len(), 2);
+    }
+}
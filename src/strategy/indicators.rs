@@ -0,0 +1,348 @@
+//! Incremental technical indicators: SMA, EMA, RSI, ATR and Bollinger bands.
+//!
+//! Each is O(1) per update. `update` returns `None` until `warm_up()` values
+//! have been seen, then the current reading.
+
+use std::collections::VecDeque;
+
+/// Simple moving average over the last `period` values.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        }
+    }
+
+    pub fn warm_up(&self) -> usize {
+        self.period
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+/// Exponential moving average, `alpha = 2 / (period + 1)`, seeded with the
+/// SMA of the first `period` values.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    count: usize,
+    value: f64,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            count: 0,
+            value: 0.0,
+        }
+    }
+
+    pub fn warm_up(&self) -> usize {
+        self.period
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.count += 1;
+        if self.count <= self.period {
+            // Running mean of the seed window
+            self.value += (value - self.value) / self.count as f64;
+        } else {
+            self.value += self.alpha * (value - self.value);
+        }
+        (self.count >= self.period).then_some(self.value)
+    }
+}
+
+/// Wilder's running average: plain mean of the first `period` samples, then
+/// `avg = (avg × (period − 1) + x) / period`.
+#[derive(Debug, Clone)]
+struct Wilder {
+    period: usize,
+    count: usize,
+    value: f64,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            value: 0.0,
+        }
+    }
+
+    fn update(&mut self, x: f64) -> Option<f64> {
+        self.count += 1;
+        if self.count <= self.period {
+            self.value += (x - self.value) / self.count as f64;
+        } else {
+            self.value += (x - self.value) / self.period as f64;
+        }
+        (self.count >= self.period).then_some(self.value)
+    }
+}
+
+/// Relative strength index (0–100) with Wilder smoothing of gains and losses.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    prev: Option<f64>,
+    gains: Wilder,
+    losses: Wilder,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev: None,
+            gains: Wilder::new(period),
+            losses: Wilder::new(period),
+        }
+    }
+
+    /// `period` changes, so one more price.
+    pub fn warm_up(&self) -> usize {
+        self.gains.period + 1
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        let prev = self.prev.replace(value)?;
+        let change = value - prev;
+        let gain = self.gains.update(change.max(0.0));
+        let loss = self.losses.update((-change).max(0.0));
+        let (gain, loss) = gain.zip(loss)?;
+        if loss == 0.0 {
+            // No losses: fully overbought (or neutral on a flat series)
+            return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+        }
+        Some(100.0 - 100.0 / (1.0 + gain / loss))
+    }
+}
+
+/// Average true range with Wilder smoothing. The first bar's true range is
+/// its high − low.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    prev_close: Option<f64>,
+    avg: Wilder,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_close: None,
+            avg: Wilder::new(period),
+        }
+    }
+
+    pub fn warm_up(&self) -> usize {
+        self.avg.period
+    }
+
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<f64> {
+        let range = high - low;
+        let true_range = match self.prev_close.replace(close) {
+            Some(prev) => range.max((high - prev).abs()).max((low - prev).abs()),
+            None => range,
+        };
+        self.avg.update(true_range)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+/// Bollinger bands: SMA ± `k` population standard deviations over `period`.
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    k: f64,
+    sma: Sma,
+    sum_sq: f64,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, k: f64) -> Self {
+        Self {
+            k,
+            sma: Sma::new(period),
+            sum_sq: 0.0,
+        }
+    }
+
+    pub fn warm_up(&self) -> usize {
+        self.sma.period
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<Bands> {
+        self.sum_sq += value * value;
+        if self.sma.window.len() == self.sma.period
+            && let Some(&oldest) = self.sma.window.front()
+        {
+            self.sum_sq -= oldest * oldest;
+        }
+        let middle = self.sma.update(value)?;
+        let n = self.sma.period as f64;
+        // Rounding can leave a hair below zero on a flat window
+        let std = (self.sum_sq / n - middle * middle).max(0.0).sqrt();
+        Some(Bands {
+            lower: middle - self.k * std,
+            middle,
+            upper: middle + self.k * std,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic random walk (LCG) with bars built around each close.
+    fn series(seed: u64, n: usize) -> Vec<(f64, f64, f64)> {
+        let mut state = seed;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let mut close = 2000.0;
+        (0..n)
+            .map(|_| {
+                close += (uniform() - 0.5) * 10.0;
+                let high = close + uniform() * 3.0;
+                let low = close - uniform() * 3.0;
+                (high, low, close)
+            })
+            .collect()
+    }
+
+    fn mean(xs: &[f64]) -> f64 {
+        xs.iter().sum::<f64>() / xs.len() as f64
+    }
+
+    /// Wilder average of `xs` recomputed from scratch.
+    fn wilder(xs: &[f64], period: usize) -> f64 {
+        let mut avg = mean(&xs[..period]);
+        for x in &xs[period..] {
+            avg = (avg * (period - 1) as f64 + x) / period as f64;
+        }
+        avg
+    }
+
+    fn close_enough(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * a.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_incremental_matches_batch_recomputation() {
+        for (seed, period) in [(1, 14), (7, 5), (42, 20), (99, 1)] {
+            let bars = series(seed, 300);
+            let closes: Vec<f64> = bars.iter().map(|b| b.2).collect();
+            let (mut sma, mut ema) = (Sma::new(period), Ema::new(period));
+            let (mut rsi, mut atr) = (Rsi::new(period), Atr::new(period));
+            let mut bollinger = Bollinger::new(period, 2.0);
+
+            for (i, &(high, low, close)) in bars.iter().enumerate() {
+                let seen = &closes[..=i];
+                let n = i + 1;
+
+                let got = sma.update(close);
+                assert_eq!(got.is_some(), n >= sma.warm_up());
+                if let Some(got) = got {
+                    assert!(
+                        close_enough(got, mean(&seen[n - period..])),
+                        "SMA {seed}/{i}"
+                    );
+                }
+
+                let got = ema.update(close);
+                assert_eq!(got.is_some(), n >= ema.warm_up());
+                if let Some(got) = got {
+                    let alpha = 2.0 / (period as f64 + 1.0);
+                    let want = seen[period..]
+                        .iter()
+                        .fold(mean(&seen[..period]), |e, x| e + alpha * (x - e));
+                    assert!(close_enough(got, want), "EMA {seed}/{i}");
+                }
+
+                let got = rsi.update(close);
+                assert_eq!(got.is_some(), n >= rsi.warm_up());
+                if let Some(got) = got {
+                    let changes: Vec<f64> = seen.windows(2).map(|w| w[1] - w[0]).collect();
+                    let gains: Vec<f64> = changes.iter().map(|c| c.max(0.0)).collect();
+                    let losses: Vec<f64> = changes.iter().map(|c| (-c).max(0.0)).collect();
+                    let rs = wilder(&gains, period) / wilder(&losses, period);
+                    assert!(
+                        close_enough(got, 100.0 - 100.0 / (1.0 + rs)),
+                        "RSI {seed}/{i}"
+                    );
+                }
+
+                let got = atr.update(high, low, close);
+                assert_eq!(got.is_some(), n >= atr.warm_up());
+                if let Some(got) = got {
+                    let ranges: Vec<f64> = (0..n)
+                        .map(|j| {
+                            let (h, l, _) = bars[j];
+                            match j.checked_sub(1).map(|p| bars[p].2) {
+                                Some(prev) => (h - l).max((h - prev).abs()).max((l - prev).abs()),
+                                None => h - l,
+                            }
+                        })
+                        .collect();
+                    assert!(close_enough(got, wilder(&ranges, period)), "ATR {seed}/{i}");
+                }
+
+                let got = bollinger.update(close);
+                assert_eq!(got.is_some(), n >= bollinger.warm_up());
+                if let Some(bands) = got {
+                    let window = &seen[n - period..];
+                    let mid = mean(window);
+                    let var = window.iter().map(|x| (x - mid).powi(2)).sum::<f64>() / period as f64;
+                    let want = mid + 2.0 * var.sqrt();
+                    // sum / sum-of-squares variance loses a few digits at price ≈ 2000
+                    assert!((bands.upper - want).abs() < 1e-6, "Bollinger {seed}/{i}");
+                    assert!(close_enough(bands.middle, mid));
+                    assert!(close_enough(
+                        bands.upper - bands.middle,
+                        bands.middle - bands.lower
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rsi_extremes() {
+        let mut rsi = Rsi::new(3);
+        let rising: Vec<_> = (0..5).map(|i| rsi.update(100.0 + i as f64)).collect();
+        assert_eq!(rising, vec![None, None, None, Some(100.0), Some(100.0)]);
+
+        let mut flat = Rsi::new(3);
+        assert_eq!((0..5).filter_map(|_| flat.update(100.0)).last(), Some(50.0));
+    }
+}
@@ -0,0 +1,364 @@
+//! Post-fill markout tracker (adverse selection / toxic flow defense).
+//!
+//! Each of our fills is marked against the mid 1s / 5s / 30s later:
+//! buy markout = (mid_later − fill) / fill, sell = (fill − mid_later) / fill, in bps.
+//! Negative means we were picked off. When the short-horizon average on a side
+//! stays below `-markout_threshold_bps`, the MM either widens that side by
+//! `markout_widen_mult` or, with `markout_pause_secs > 0`, stops quoting for a
+//! cool-off period.
+//!
+//! Time is passed in as seconds on the caller's clock so the tracker stays pure.
+
+use crate::config::ExchangeConfig;
+use crate::strategy::quoting::Quote;
+use crate::types::Side;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Markout horizons in seconds.
+pub const HORIZONS_SECS: [f64; 3] = [1.0, 5.0, 30.0];
+/// Completed markouts kept per side per horizon.
+const MAX_SAMPLES: usize = 20;
+/// Position changes below this are treated as noise, not fills.
+const MIN_FILL_QTY: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Defense {
+    None,
+    /// Multiply each side's distance from mid
+    Widen {
+        bid_mult: f64,
+        ask_mult: f64,
+    },
+    /// Quoting paused until the cool-off ends
+    Pause {
+        remaining_secs: f64,
+    },
+}
+
+impl Defense {
+    /// Widen the quote away from `mid` (no-op unless `Widen`).
+    pub fn apply(&self, mid: f64, quote: Quote) -> Quote {
+        let Defense::Widen { bid_mult, ask_mult } = *self else {
+            return quote;
+        };
+        let bid_price = mid - (mid - quote.bid_price) * bid_mult;
+        let ask_price = mid + (quote.ask_price - mid) * ask_mult;
+        Quote {
+            bid_price,
+            ask_price,
+            bid_spread_bps: (mid - bid_price) / mid * 10_000.0,
+            ask_spread_bps: (ask_price - mid) / mid * 10_000.0,
+        }
+    }
+}
+
+impl fmt::Display for Defense {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Defense::None => write!(f, "none"),
+            Defense::Widen { bid_mult, ask_mult } => {
+                write!(f, "widen(bid×{:.2}, ask×{:.2})", bid_mult, ask_mult)
+            }
+            Defense::Pause { remaining_secs } => write!(f, "pause({:.0}s left)", remaining_secs),
+        }
+    }
+}
+
+struct PendingFill {
+    side: Side,
+    price: f64,
+    time: f64,
+    /// Bitmask of horizons already marked
+    marked: u8,
+}
+
+pub struct MarkoutTracker {
+    threshold_bps: f64,
+    min_fills: usize,
+    widen_mult: f64,
+    pause_secs: f64,
+
+    /// Time-indexed mids (t, mid), trimmed to the longest horizon
+    mids: VecDeque<(f64, f64)>,
+    pending: Vec<PendingFill>,
+    /// [side][horizon] → recent markouts in bps (side 0 = buy, 1 = sell)
+    samples: [[VecDeque<f64>; HORIZONS_SECS.len()]; 2],
+
+    last_position: Option<f64>,
+    quoted_bid: f64,
+    quoted_ask: f64,
+    paused_until: f64,
+}
+
+impl MarkoutTracker {
+    /// `threshold_bps <= 0` disables the defense (markouts are still tracked).
+    pub fn new(threshold_bps: f64, min_fills: usize, widen_mult: f64, pause_secs: f64) -> Self {
+        Self {
+            threshold_bps,
+            min_fills: min_fills.max(1),
+            widen_mult,
+            pause_secs,
+            mids: VecDeque::new(),
+            pending: Vec::new(),
+            samples: Default::default(),
+            last_position: None,
+            quoted_bid: 0.0,
+            quoted_ask: 0.0,
+            paused_until: 0.0,
+        }
+    }
+
+    pub fn from_config(cfg: &ExchangeConfig) -> Self {
+        Self::new(
+            cfg.markout_threshold_bps,
+            cfg.markout_min_fills,
+            cfg.markout_widen_mult,
+            cfg.markout_pause_secs as f64,
+        )
+    }
+
+    fn side_idx(side: Side) -> usize {
+        match side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        }
+    }
+
+    /// Record a mid and resolve any fill markouts whose horizon has elapsed.
+    pub fn on_mid(&mut self, t: f64, mid: f64) {
+        if mid <= 0.0 {
+            return;
+        }
+        self.mids.push_back((t, mid));
+        let max_horizon = HORIZONS_SECS[HORIZONS_SECS.len() - 1];
+
+        let mut i = 0;
+        while i < self.pending.len() {
+            let fill = &mut self.pending[i];
+            for (h, horizon) in HORIZONS_SECS.iter().enumerate() {
+                if fill.marked & (1 << h) != 0 {
+                    continue;
+                }
+                // First mid at or after fill time + horizon
+                let Some(&(_, mark)) = self.mids.iter().find(|(ts, _)| *ts >= fill.time + horizon)
+                else {
+                    continue;
+                };
+                let bps = match fill.side {
+                    Side::Buy => (mark - fill.price) / fill.price * 10_000.0,
+                    Side::Sell => (fill.price - mark) / fill.price * 10_000.0,
+                };
+                let buf = &mut self.samples[Self::side_idx(fill.side)][h];
+                buf.push_back(bps);
+                if buf.len() > MAX_SAMPLES {
+                    buf.pop_front();
+                }
+                fill.marked |= 1 << h;
+            }
+            if fill.marked.count_ones() as usize == HORIZONS_SECS.len() {
+                self.pending.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        // Keep enough history to mark fills recorded with a past timestamp
+        let oldest_pending = self
+            .pending
+            .iter()
+            .map(|f| f.time)
+            .fold(f64::INFINITY, f64::min);
+        let keep_from = (t - max_horizon).min(oldest_pending);
+        while self.mids.front().is_some_and(|(ts, _)| *ts < keep_from) {
+            self.mids.pop_front();
+        }
+    }
+
+    /// Record one of our fills (e.g. from the fills API).
+    pub fn on_fill(&mut self, t: f64, side: Side, price: f64) {
+        if price > 0.0 {
+            self.pending.push(PendingFill {
+                side,
+                price,
+                time: t,
+                marked: 0,
+            });
+        }
+    }
+
+    /// Prices of the quotes just placed; used to price fills inferred from position changes.
+    pub fn set_quotes(&mut self, bid_price: f64, ask_price: f64) {
+        self.quoted_bid = bid_price;
+        self.quoted_ask = ask_price;
+    }
+
+    /// Infer fills from a polled position: an increase means our bid was hit,
+    /// a decrease means our ask was lifted. The first call only sets the baseline.
+    pub fn on_position(&mut self, t: f64, position: f64) {
+        let Some(prev) = self.last_position.replace(position) else {
+            return;
+        };
+        let delta = position - prev;
+        if delta > MIN_FILL_QTY {
+            self.on_fill(t, Side::Buy, self.quoted_bid);
+        } else if delta < -MIN_FILL_QTY {
+            self.on_fill(t, Side::Sell, self.quoted_ask);
+        }
+    }
+
+    /// Mean markout (bps) for a side at horizon index `h`, if any samples exist.
+    pub fn avg_markout(&self, side: Side, h: usize) -> Option<f64> {
+        let buf = &self.samples[Self::side_idx(side)][h];
+        if buf.is_empty() {
+            return None;
+        }
+        Some(buf.iter().sum::<f64>() / buf.len() as f64)
+    }
+
+    /// Short-horizon side is toxic once it has `min_fills` samples averaging below −threshold.
+    fn is_toxic(&self, side: Side) -> bool {
+        let buf = &self.samples[Self::side_idx(side)][0];
+        buf.len() >= self.min_fills
+            && self
+                .avg_markout(side, 0)
+                .is_some_and(|avg| avg < -self.threshold_bps)
+    }
+
+    /// Defense to apply to the next quote cycle at time `t`.
+    pub fn defense(&mut self, t: f64) -> Defense {
+        if t < self.paused_until {
+            return Defense::Pause {
+                remaining_secs: self.paused_until - t,
+            };
+        }
+        if self.threshold_bps <= 0.0 {
+            return Defense::None;
+        }
+
+        let (bid_toxic, ask_toxic) = (self.is_toxic(Side::Buy), self.is_toxic(Side::Sell));
+        if !bid_toxic && !ask_toxic {
+            return Defense::None;
+        }
+        if self.pause_secs > 0.0 {
+            self.paused_until = t + self.pause_secs;
+            // Require fresh evidence after the cool-off
+            for side in &mut self.samples {
+                side[0].clear();
+            }
+            return Defense::Pause {
+                remaining_secs: self.pause_secs,
+            };
+        }
+        Defense::Widen {
+            bid_mult: if bid_toxic { self.widen_mult } else { 1.0 },
+            ask_mult: if ask_toxic { self.widen_mult } else { 1.0 },
+        }
+    }
+
+    /// One-line summary: "1s B:-1.2/A:+0.3 5s ... 30s ...".
+    pub fn summary(&self) -> String {
+        let fmt = |v: Option<f64>| v.map_or("n/a".to_string(), |v| format!("{:+.1}", v));
+        HORIZONS_SECS
+            .iter()
+            .enumerate()
+            .map(|(h, secs)| {
+                format!(
+                    "{}s B:{}/A:{}",
+                    secs,
+                    fmt(self.avg_markout(Side::Buy, h)),
+                    fmt(self.avg_markout(Side::Sell, h))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bid hit at 100, then the mid keeps falling: 1s markout −10 bps each time.
+    fn picked_off(tracker: &mut MarkoutTracker, t0: f64, position: f64) {
+        tracker.set_quotes(100.0, 100.2);
+        tracker.on_position(t0, position);
+        tracker.on_mid(t0 + 0.5, 99.95);
+        tracker.on_mid(t0 + 1.2, 99.9);
+    }
+
+    #[test]
+    fn test_markouts_per_side_and_horizon() {
+        let mut tracker = MarkoutTracker::new(0.0, 1, 1.5, 0.0);
+        tracker.on_mid(0.0, 100.1);
+        tracker.on_position(0.0, 0.0);
+
+        // Bid filled at 100.0, ask filled at 100.2
+        tracker.set_quotes(100.0, 100.2);
+        tracker.on_position(1.0, 0.1);
+        tracker.on_position(1.0, 0.0);
+
+        tracker.on_mid(2.5, 100.05); // ≥1s: buy +5 bps, sell ≈ +14.97 bps
+        tracker.on_mid(7.0, 99.9); // ≥5s: buy −10 bps, sell ≈ +29.94 bps
+        assert!((tracker.avg_markout(Side::Buy, 0).unwrap() - 5.0).abs() < 1e-9);
+        assert!((tracker.avg_markout(Side::Sell, 0).unwrap() - 14.970_059_880).abs() < 1e-6);
+        assert!((tracker.avg_markout(Side::Buy, 1).unwrap() + 10.0).abs() < 1e-9);
+        assert_eq!(tracker.avg_markout(Side::Buy, 2), None);
+
+        tracker.on_mid(31.0, 100.0);
+        assert_eq!(tracker.avg_markout(Side::Buy, 2), Some(0.0));
+        assert!(tracker.pending.is_empty());
+        assert_eq!(tracker.defense(31.0), Defense::None);
+    }
+
+    #[test]
+    fn test_persistent_negative_markout_widens_toxic_side() {
+        let mut tracker = MarkoutTracker::new(5.0, 3, 2.0, 0.0);
+        tracker.on_position(0.0, 0.0);
+        for i in 1..=2 {
+            picked_off(&mut tracker, i as f64 * 10.0, i as f64 * 0.1);
+        }
+        // Only two samples: not yet persistent
+        assert_eq!(tracker.defense(25.0), Defense::None);
+
+        picked_off(&mut tracker, 30.0, 0.3);
+        let defense = tracker.defense(35.0);
+        assert_eq!(
+            defense,
+            Defense::Widen {
+                bid_mult: 2.0,
+                ask_mult: 1.0
+            }
+        );
+
+        let quote = Quote {
+            bid_price: 99.9,
+            ask_price: 100.1,
+            bid_spread_bps: 10.0,
+            ask_spread_bps: 10.0,
+        };
+        let widened = defense.apply(100.0, quote);
+        assert!((widened.bid_price - 99.8).abs() < 1e-9);
+        assert_eq!(widened.ask_price, 100.1);
+    }
+
+    #[test]
+    fn test_pause_cool_off_then_requires_fresh_evidence() {
+        let mut tracker = MarkoutTracker::new(5.0, 2, 2.0, 60.0);
+        tracker.on_position(0.0, 0.0);
+        picked_off(&mut tracker, 10.0, 0.1);
+        picked_off(&mut tracker, 20.0, 0.2);
+
+        assert_eq!(
+            tracker.defense(22.0),
+            Defense::Pause {
+                remaining_secs: 60.0
+            }
+        );
+        assert!(matches!(
+            tracker.defense(50.0),
+            Defense::Pause { remaining_secs } if (remaining_secs - 32.0).abs() < 1e-9
+        ));
+        assert_eq!(tracker.defense(82.0), Defense::None);
+    }
+}
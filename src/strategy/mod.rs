@@ -1,10 +1,24 @@
-pub mod lighter_adaptive_mm;
 pub mod arbitrage;
 pub mod backpack_mm;
-pub mod inventory_neutral_mm;
+pub mod dry_run;
 pub mod edgex_mm;
+pub mod execution_metrics;
+pub mod funding_arb;
+pub mod indicators;
+pub mod inventory_neutral_mm;
+pub mod lighter_adaptive_mm;
+pub mod markout;
+pub mod order_age;
+pub mod protective_stop;
+pub mod quote_board;
+pub mod quoting;
+pub mod reprice;
+pub mod runner;
+pub mod state;
+pub mod volatility;
 
 use crate::shm_reader::ShmBboMessage;
+use crate::strategy::execution_metrics::ExecutionMetrics;
 use std::future::Future;
 use std::pin::Pin;
 
@@ -19,10 +33,29 @@ pub trait Strategy {
     /// for a specific symbol on a specific exchange.
     fn on_bbo_update(&mut self, symbol_id: u16, exchange_id: u8, bbo: &ShmBboMessage);
 
+    /// Called with the feeder's global sequence just before the BBO update(s)
+    /// read at that sequence are dispatched. Default: ignored.
+    fn on_global_sequence(&mut self, _seq: u64) {}
+
     /// Called at the end of every poll cycle when no new data is present.
     /// Used for periodic tasks like order lifecycle management.
     fn on_idle(&mut self);
 
+    /// Execution quality so far (fill rate, slippage). Default: nothing tracked.
+    fn metrics(&self) -> &ExecutionMetrics {
+        &ExecutionMetrics::EMPTY
+    }
+
+    /// Warm-up state to carry across a restart (see `state`). Default: none.
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    /// Restore what `save_state` wrote before the restart.
+    fn load_state(&mut self, _bytes: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     /// Called during graceful shutdown to cancel all orders
     fn on_shutdown(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
         Box::pin(async {})
@@ -0,0 +1,69 @@
+//! Placement time of each resting quote, for max-lifetime housekeeping.
+//!
+//! Quiet markets trip neither the price nor (with in-place amends) the
+//! cancel path, so a quote can outlive the volatility regime it was priced
+//! for. Once the oldest tracked order passes `max_order_age_secs` the MM
+//! cancels everything and quotes afresh.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Order id → placement time; cleared whenever the book is swept.
+#[derive(Debug)]
+pub struct OrderAges<K> {
+    placed: HashMap<K, Instant>,
+}
+
+impl<K> Default for OrderAges<K> {
+    fn default() -> Self {
+        Self {
+            placed: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> OrderAges<K> {
+    pub fn on_placed(&mut self, id: K, at: Instant) {
+        self.placed.insert(id, at);
+    }
+
+    pub fn clear(&mut self) {
+        self.placed.clear();
+    }
+
+    pub fn oldest(&self) -> Option<Instant> {
+        self.placed.values().min().copied()
+    }
+
+    /// True once any tracked order has rested longer than `max_age` (zero = never).
+    pub fn expired(&self, now: Instant, max_age: Duration) -> bool {
+        !max_age.is_zero()
+            && self
+                .oldest()
+                .is_some_and(|t| now.saturating_duration_since(t) > max_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expires_on_oldest_order_and_resets_on_clear() {
+        let t0 = Instant::now();
+        let max_age = Duration::from_secs(30);
+        let mut ages = OrderAges::default();
+        assert!(!ages.expired(t0 + Duration::from_secs(60), max_age));
+
+        ages.on_placed("a", t0);
+        ages.on_placed("b", t0 + Duration::from_secs(20));
+        assert_eq!(ages.oldest(), Some(t0));
+        assert!(!ages.expired(t0 + Duration::from_secs(30), max_age));
+        assert!(ages.expired(t0 + Duration::from_secs(31), max_age));
+        assert!(!ages.expired(t0 + Duration::from_secs(31), Duration::ZERO));
+
+        ages.clear();
+        assert!(!ages.expired(t0 + Duration::from_secs(31), max_age));
+    }
+}
@@ -0,0 +1,148 @@
+//! Exchange-side protective stop for the Backpack market maker.
+//!
+//! The quote task's stop-loss only fires while the process is alive and
+//! polling. Once the position reaches `protective_stop_min_position`, a
+//! reduce-only stop-market order rests on the venue `protective_stop_bps`
+//! beyond the average entry, so the position stays protected if we crash or
+//! lose connectivity. Backpack cannot amend a trigger order, so it is
+//! replaced (cancel + create) whenever the position size, entry or side changes.
+
+use crate::backpack_api::client::{BackpackClient, BackpackError};
+use crate::config::{format_price, format_size, round_to_tick};
+use parking_lot::Mutex;
+use tracing::{info, warn};
+
+/// Where the stop should rest for the current position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StopPlan {
+    /// Side of the protected position (the stop sells when long)
+    pub is_long: bool,
+    pub qty: f64,
+    pub trigger: f64,
+}
+
+/// The stop currently resting on the venue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtectiveStop {
+    pub order_id: String,
+    pub plan: StopPlan,
+}
+
+/// `None` when disabled, flat, below the size threshold or without an entry.
+pub fn plan_stop(
+    position: f64,
+    entry: f64,
+    distance_bps: f64,
+    min_position: f64,
+    tick: f64,
+    step: f64,
+) -> Option<StopPlan> {
+    let qty = round_to_tick(position.abs(), step);
+    if distance_bps <= 0.0 || entry <= 0.0 || qty <= 0.0 || qty < min_position {
+        return None;
+    }
+    let is_long = position > 0.0;
+    let offset = entry * distance_bps / 10_000.0;
+    let trigger = if is_long {
+        entry - offset
+    } else {
+        entry + offset
+    };
+    Some(StopPlan {
+        is_long,
+        qty,
+        trigger: round_to_tick(trigger, tick),
+    })
+}
+
+/// Whether the resting stop no longer matches the plan.
+pub fn needs_replace(
+    current: Option<&StopPlan>,
+    plan: Option<&StopPlan>,
+    tick: f64,
+    step: f64,
+) -> bool {
+    match (current, plan) {
+        (None, None) => false,
+        (Some(c), Some(p)) => {
+            c.is_long != p.is_long
+                || (c.qty - p.qty).abs() >= step / 2.0
+                || (c.trigger - p.trigger).abs() >= tick / 2.0
+        }
+        _ => true,
+    }
+}
+
+/// Bring the venue's stop in line with `plan`. Failures are logged and
+/// retried on the next cycle.
+pub async fn sync_protective_stop(
+    client: &BackpackClient,
+    symbol: &str,
+    state: &Mutex<Option<ProtectiveStop>>,
+    plan: Option<StopPlan>,
+    tick: f64,
+    step: f64,
+) {
+    let current = state.lock().clone();
+    if !needs_replace(current.as_ref().map(|s| &s.plan), plan.as_ref(), tick, step) {
+        return;
+    }
+    if let Some(stop) = current {
+        match client.cancel_order(symbol, &stop.order_id).await {
+            Ok(_) => {}
+            Err(e) => match e.downcast_ref::<BackpackError>() {
+                // Triggered or cancelled by the venue
+                Some(BackpackError::OrderNotFound { .. }) => {
+                    info!("🛡️ [BP] Protective stop {} already gone", stop.order_id);
+                }
+                _ => {
+                    warn!("⚠️ [BP] Protective stop cancel failed: {:?}", e);
+                    return;
+                }
+            },
+        }
+        *state.lock() = None;
+    }
+    let Some(plan) = plan else {
+        return;
+    };
+    let side = if plan.is_long { "Ask" } else { "Bid" };
+    let qty = format_size(plan.qty, step);
+    let trigger = format_price(plan.trigger, tick);
+    match client.place_stop_loss(symbol, side, &qty, &trigger).await {
+        Ok(resp) => {
+            info!(
+                "🛡️ [BP] Protective stop {} {} @ {} ({})",
+                side, qty, trigger, resp.id
+            );
+            *state.lock() = Some(ProtectiveStop {
+                order_id: resp.id,
+                plan,
+            });
+        }
+        Err(e) => warn!("⚠️ [BP] Protective stop placement failed: {:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_follows_position_and_replaces_on_change() {
+        assert_eq!(plan_stop(0.5, 2000.0, 0.0, 0.1, 0.01, 0.01), None);
+        assert_eq!(plan_stop(0.05, 2000.0, 100.0, 0.1, 0.01, 0.01), None);
+        let long = plan_stop(0.5, 2000.0, 100.0, 0.1, 0.01, 0.01).unwrap();
+        assert!(long.is_long);
+        assert!((long.trigger - 1980.0).abs() < 1e-9);
+        let short = plan_stop(-0.5, 2000.0, 100.0, 0.1, 0.01, 0.01).unwrap();
+        assert!(!short.is_long && (short.trigger - 2020.0).abs() < 1e-9);
+
+        assert!(!needs_replace(Some(&long), Some(&long), 0.01, 0.01));
+        assert!(needs_replace(Some(&long), Some(&short), 0.01, 0.01));
+        let grown = plan_stop(0.6, 2000.0, 100.0, 0.1, 0.01, 0.01).unwrap();
+        assert!(needs_replace(Some(&long), Some(&grown), 0.01, 0.01));
+        assert!(needs_replace(Some(&long), None, 0.01, 0.01));
+        assert!(!needs_replace(None, None, 0.01, 0.01));
+    }
+}
@@ -0,0 +1,211 @@
+//! Self-trade prevention between strategies sharing an account.
+//!
+//! Each strategy publishes its resting bid/ask per (exchange, symbol) on a
+//! process-wide `QuoteBoard`. Before an aggressive order (stop-loss close,
+//! hedge, arbitrage leg) goes out, the sender asks the board which of our own
+//! quotes it would trade against and resolves the conflict according to
+//! `self_trade_policy`: skip the order, pull its price back one tick inside our
+//! quote, or cancel the conflicting quotes first.
+
+pub use crate::config::SelfTradePolicy;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A resting quote published by one strategy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnQuote {
+    pub owner: &'static str,
+    pub order_id: String,
+    pub price: f64,
+}
+
+/// Outcome of the self-trade check for one order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StpDecision {
+    /// No conflict left: send at this price
+    Submit(f64),
+    Skip,
+    /// Cancel these order ids first, then send at the original price
+    CancelFirst(Vec<String>),
+}
+
+#[derive(Debug, Default)]
+struct Book {
+    bids: Vec<OwnQuote>,
+    asks: Vec<OwnQuote>,
+}
+
+#[derive(Debug, Default)]
+pub struct QuoteBoard {
+    books: RwLock<HashMap<(u8, String), Book>>,
+}
+
+impl QuoteBoard {
+    /// Board shared by every strategy in the process.
+    pub fn global() -> &'static QuoteBoard {
+        static BOARD: OnceLock<QuoteBoard> = OnceLock::new();
+        BOARD.get_or_init(QuoteBoard::default)
+    }
+
+    /// Publish `owner`'s quote on one side, replacing its previous one.
+    pub fn set_quote(
+        &self,
+        exchange_id: u8,
+        symbol: &str,
+        owner: &'static str,
+        is_buy: bool,
+        order_id: String,
+        price: f64,
+    ) {
+        let mut books = self.books.write();
+        let book = books.entry((exchange_id, symbol.to_string())).or_default();
+        let side = if is_buy {
+            &mut book.bids
+        } else {
+            &mut book.asks
+        };
+        side.retain(|q| q.owner != owner);
+        side.push(OwnQuote {
+            owner,
+            order_id,
+            price,
+        });
+    }
+
+    /// Withdraw all of `owner`'s quotes on the symbol (after a cancel).
+    pub fn clear(&self, exchange_id: u8, symbol: &str, owner: &'static str) {
+        if let Some(book) = self
+            .books
+            .write()
+            .get_mut(&(exchange_id, symbol.to_string()))
+        {
+            book.bids.retain(|q| q.owner != owner);
+            book.asks.retain(|q| q.owner != owner);
+        }
+    }
+
+    /// Our quotes (any owner) that a buy / sell at `price` would trade against.
+    pub fn crossing(
+        &self,
+        exchange_id: u8,
+        symbol: &str,
+        is_buy: bool,
+        price: f64,
+    ) -> Vec<OwnQuote> {
+        let books = self.books.read();
+        let Some(book) = books.get(&(exchange_id, symbol.to_string())) else {
+            return Vec::new();
+        };
+        if is_buy {
+            book.asks
+                .iter()
+                .filter(|q| price >= q.price)
+                .cloned()
+                .collect()
+        } else {
+            book.bids
+                .iter()
+                .filter(|q| price <= q.price)
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Self-trade check for an aggressive order, resolved per `policy`.
+    pub fn check(
+        &self,
+        policy: SelfTradePolicy,
+        exchange_id: u8,
+        symbol: &str,
+        is_buy: bool,
+        price: f64,
+        tick: f64,
+    ) -> StpDecision {
+        let conflicts = self.crossing(exchange_id, symbol, is_buy, price);
+        if conflicts.is_empty() {
+            return StpDecision::Submit(price);
+        }
+        match policy {
+            SelfTradePolicy::Skip => StpDecision::Skip,
+            SelfTradePolicy::Adjust => {
+                let adjusted = if is_buy {
+                    conflicts
+                        .iter()
+                        .map(|q| q.price)
+                        .fold(f64::INFINITY, f64::min)
+                        - tick
+                } else {
+                    conflicts.iter().map(|q| q.price).fold(0.0, f64::max) + tick
+                };
+                if adjusted > 0.0 {
+                    StpDecision::Submit(adjusted)
+                } else {
+                    StpDecision::Skip
+                }
+            }
+            SelfTradePolicy::Cancel => {
+                StpDecision::CancelFirst(conflicts.into_iter().map(|q| q.order_id).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_strategies_on_one_symbol() {
+        let board = QuoteBoard::default();
+        // MM quotes 1999/2001; the hedger rests a bid at 1998
+        board.set_quote(4, "ETH_USDC_PERP", "mm", true, "b1".into(), 1999.0);
+        board.set_quote(4, "ETH_USDC_PERP", "mm", false, "a1".into(), 2001.0);
+        board.set_quote(4, "ETH_USDC_PERP", "hedger", true, "h1".into(), 1998.0);
+
+        // Other venue / symbol never conflicts
+        let buy = |policy| board.check(policy, 4, "ETH_USDC_PERP", true, 2002.0, 0.01);
+        let elsewhere = |ex, sym| board.check(SelfTradePolicy::Skip, ex, sym, true, 2002.0, 0.01);
+        assert_eq!(elsewhere(5, "ETH_USDC_PERP"), StpDecision::Submit(2002.0));
+        assert_eq!(elsewhere(4, "BTC_USDC_PERP"), StpDecision::Submit(2002.0));
+
+        // Hedger lifting through the MM's ask
+        assert_eq!(buy(SelfTradePolicy::Skip), StpDecision::Skip);
+        let adjusted = buy(SelfTradePolicy::Adjust);
+        assert!(matches!(adjusted, StpDecision::Submit(p) if (p - 2000.99).abs() < 1e-9));
+        assert_eq!(
+            buy(SelfTradePolicy::Cancel),
+            StpDecision::CancelFirst(vec!["a1".into()])
+        );
+
+        // A sell at 1997 would hit both bids; adjusting stays above the best one
+        let sell = board.check(
+            SelfTradePolicy::Cancel,
+            4,
+            "ETH_USDC_PERP",
+            false,
+            1997.0,
+            0.01,
+        );
+        assert!(matches!(sell, StpDecision::CancelFirst(ids) if ids.len() == 2));
+        let sell = board.check(
+            SelfTradePolicy::Adjust,
+            4,
+            "ETH_USDC_PERP",
+            false,
+            1997.0,
+            0.01,
+        );
+        assert!(matches!(sell, StpDecision::Submit(p) if (p - 1999.01).abs() < 1e-9));
+
+        // Requoting replaces the side; clearing withdraws the owner
+        board.set_quote(4, "ETH_USDC_PERP", "mm", false, "a2".into(), 2003.0);
+        assert_eq!(buy(SelfTradePolicy::Skip), StpDecision::Submit(2002.0));
+        board.clear(4, "ETH_USDC_PERP", "mm");
+        let sell = board.crossing(4, "ETH_USDC_PERP", false, 1997.0);
+        assert_eq!(
+            sell.iter().map(|q| q.owner).collect::<Vec<_>>(),
+            vec!["hedger"]
+        );
+    }
+}
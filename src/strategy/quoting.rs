@@ -0,0 +1,497 @@
+//! Shared quote math for the Backpack / EdgeX market makers.
+//!
+//! Pure functions only: the strategies gather inputs (mid, vol, momentum,
+//! position, book imbalance) on the hot path and hand them here, which keeps
+//! the pricing unit-testable without exchange clients.
+
+use crate::config::{ExchangeConfig, QuotingModel};
+use crate::shm_depth_reader::ShmDepthSnapshot;
+
+/// Market/inventory state used to price one quote cycle.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuoteInputs {
+    pub mid: f64,
+    pub vol_bps: f64,
+    pub momentum_bps: f64,
+    pub position: f64,
+    pub max_position: f64,
+    /// Book imbalance in [-1, 1]; positive = bids heavier (see `imbalance`)
+    pub imbalance: f64,
+    /// Predicted funding rate per period (positive → longs pay)
+    pub funding_rate: f64,
+    /// Seconds until the next funding settlement (None = unknown, no funding adjustment)
+    pub secs_to_funding: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub bid_price: f64,
+    pub ask_price: f64,
+    pub bid_spread_bps: f64,
+    pub ask_spread_bps: f64,
+}
+
+/// One order of a multi-level quote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteLevel {
+    pub is_buy: bool,
+    /// 0 = innermost
+    pub level: usize,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// (bid - ask) / (bid + ask), in [-1, 1]. 0 when both sides are empty.
+#[inline]
+pub fn imbalance(bid_depth: f64, ask_depth: f64) -> f64 {
+    let total = bid_depth + ask_depth;
+    if total <= 0.0 {
+        return 0.0;
+    }
+    (bid_depth - ask_depth) / total
+}
+
+/// Imbalance of resting size within `within_bps` of the snapshot's mid.
+pub fn depth_imbalance(depth: &ShmDepthSnapshot, within_bps: f64) -> f64 {
+    let best_bid = depth.bids[0].price;
+    let best_ask = depth.asks[0].price;
+    if best_bid <= 0.0 || best_ask <= 0.0 {
+        return 0.0;
+    }
+    let mid = (best_bid + best_ask) / 2.0;
+    let band = mid * within_bps / 10_000.0;
+
+    let bid_depth: f64 = depth
+        .bids
+        .iter()
+        .filter(|l| l.price > 0.0 && l.price >= mid - band)
+        .map(|l| l.size)
+        .sum();
+    let ask_depth: f64 = depth
+        .asks
+        .iter()
+        .filter(|l| l.price > 0.0 && l.price <= mid + band)
+        .map(|l| l.size)
+        .sum();
+    imbalance(bid_depth, ask_depth)
+}
+
+/// Avellaneda-Stoikov parameters (all in price units / seconds).
+#[derive(Debug, Clone, Copy)]
+pub struct AvellanedaParams {
+    /// Risk aversion γ
+    pub gamma: f64,
+    /// Order arrival intensity k
+    pub kappa: f64,
+    /// Remaining horizon (T - t), seconds
+    pub time_horizon: f64,
+}
+
+/// Reservation price r = s − q·γ·σ²·(T−t)
+#[inline]
+pub fn reservation_price(mid: f64, inventory: f64, sigma: f64, p: &AvellanedaParams) -> f64 {
+    mid - inventory * p.gamma * sigma * sigma * p.time_horizon
+}
+
+/// Optimal total spread δ = γ·σ²·(T−t) + (2/γ)·ln(1 + γ/k)
+#[inline]
+pub fn optimal_spread(sigma: f64, p: &AvellanedaParams) -> f64 {
+    p.gamma * sigma * sigma * p.time_horizon + (2.0 / p.gamma) * (1.0 + p.gamma / p.kappa).ln()
+}
+
+/// (bid, ask) = r ∓ δ/2. `sigma` is the price volatility (price units per √s).
+pub fn avellaneda_stoikov(
+    mid: f64,
+    inventory: f64,
+    sigma: f64,
+    p: &AvellanedaParams,
+) -> (f64, f64) {
+    let r = reservation_price(mid, inventory, sigma, p);
+    let half = optimal_spread(sigma, p) / 2.0;
+    (r - half, r + half)
+}
+
+/// Price one quote cycle with the model selected by `cfg.quoting_model`.
+pub fn compute_quotes(cfg: &ExchangeConfig, inp: &QuoteInputs) -> Quote {
+    let quote = match cfg.quoting_model {
+        QuotingModel::Basic => basic_quotes(cfg, inp),
+        QuotingModel::Avellaneda => avellaneda_quotes(cfg, inp),
+    };
+    funding_adjust(cfg, inp, quote)
+}
+
+/// Funding skew and pre-settlement widening, applied on top of either model.
+///
+/// Only inventory on the paying side (long with positive funding, short with
+/// negative) is skewed: both prices shift by −weight × rate, scaled by how far
+/// into the funding period we are, so a long paying funding quotes lower.
+fn funding_adjust(cfg: &ExchangeConfig, inp: &QuoteInputs, q: Quote) -> Quote {
+    let Some(secs) = inp.secs_to_funding else {
+        return q;
+    };
+
+    let mut shift_bps = 0.0;
+    if cfg.funding_skew_weight > 0.0 && inp.position * inp.funding_rate > 0.0 {
+        let interval = cfg.funding_interval_secs.max(1) as f64;
+        let proximity = (1.0 - secs / interval).clamp(0.0, 1.0);
+        shift_bps = -cfg.funding_skew_weight * inp.funding_rate * 10_000.0 * proximity;
+    }
+    let widen_bps = if secs <= (cfg.funding_window_mins * 60) as f64 {
+        cfg.funding_window_widen_bps.max(0.0)
+    } else {
+        0.0
+    };
+    if shift_bps == 0.0 && widen_bps == 0.0 {
+        return q;
+    }
+
+    Quote {
+        bid_price: q.bid_price + inp.mid * (shift_bps - widen_bps) / 10_000.0,
+        ask_price: q.ask_price + inp.mid * (shift_bps + widen_bps) / 10_000.0,
+        bid_spread_bps: q.bid_spread_bps - shift_bps + widen_bps,
+        ask_spread_bps: q.ask_spread_bps + shift_bps + widen_bps,
+    }
+}
+
+/// A-S quotes with σ from realized vol, half-spread floored at `min_spread_bps`.
+fn avellaneda_quotes(cfg: &ExchangeConfig, inp: &QuoteInputs) -> Quote {
+    let params = AvellanedaParams {
+        gamma: cfg.gamma,
+        kappa: cfg.kappa,
+        time_horizon: cfg.time_horizon_sec,
+    };
+    let sigma = inp.vol_bps / 10_000.0 * inp.mid;
+    let r = reservation_price(inp.mid, inp.position, sigma, &params);
+    let half = (optimal_spread(sigma, &params) / 2.0).max(r * cfg.min_spread_bps / 10_000.0);
+    let (bid_price, ask_price) = (r - half, r + half);
+
+    Quote {
+        bid_price,
+        ask_price,
+        bid_spread_bps: (inp.mid - bid_price) / inp.mid * 10_000.0,
+        ask_spread_bps: (ask_price - inp.mid) / inp.mid * 10_000.0,
+    }
+}
+
+/// Vol-scaled spread with momentum widening, inventory skew and book-imbalance shading.
+///
+/// Imbalance: the mid is shaded toward the heavy side and the side that the heavy
+/// flow would run over (ask when bids are heavy, bid when asks are heavy) is widened,
+/// both scaled by `cfg.imbalance_weight` (0 disables).
+fn basic_quotes(cfg: &ExchangeConfig, inp: &QuoteInputs) -> Quote {
+    let base_spread = f64::max(cfg.min_spread_bps, inp.vol_bps * cfg.vol_multiplier);
+    let mut bid_spread = base_spread;
+    let mut ask_spread = base_spread;
+
+    if inp.momentum_bps > cfg.momentum_threshold_bps {
+        bid_spread *= cfg.momentum_spread_mult;
+    } else if inp.momentum_bps < -cfg.momentum_threshold_bps {
+        ask_spread *= cfg.momentum_spread_mult;
+    }
+
+    // Book imbalance: widen the vulnerable side
+    let imb = inp.imbalance.clamp(-1.0, 1.0) * cfg.imbalance_weight;
+    if imb > 0.0 {
+        ask_spread *= 1.0 + imb;
+    } else if imb < 0.0 {
+        bid_spread *= 1.0 - imb;
+    }
+
+    // Inventory skew (long → shift down) plus imbalance shading (bids heavy → shift up)
+    let skew_factor = if inp.max_position > 0.0 {
+        inp.position / inp.max_position
+    } else {
+        0.0
+    };
+    let skew_shift = (skew_factor - imb) * base_spread * 0.5;
+    let skewed_mid = inp.mid * (1.0 - skew_shift / 10_000.0);
+
+    Quote {
+        bid_price: skewed_mid * (1.0 - bid_spread / 10_000.0),
+        ask_price: skewed_mid * (1.0 + ask_spread / 10_000.0),
+        bid_spread_bps: bid_spread,
+        ask_spread_bps: ask_spread,
+    }
+}
+
+/// Expand a top-of-book quote into `cfg.num_quote_levels` orders per side.
+///
+/// Level i sits at `spread_0 × level_spacing_multiplier^i` from `mid` with
+/// size `size_0 × level_size_decay^i`; level 0 is `quote` itself. Sides with
+/// zero size are skipped entirely. Ordered bid 0, ask 0, bid 1, ask 1, ...
+///
+/// Each side's total stays within what a full fill could take `position` to
+/// before reaching ±`max_position`, trimming from the outermost level in, so
+/// the side adding to inventory loses its deep levels first.
+pub fn quote_levels(
+    cfg: &ExchangeConfig,
+    mid: f64,
+    quote: &Quote,
+    bid_size: f64,
+    ask_size: f64,
+    position: f64,
+    max_position: f64,
+) -> Vec<QuoteLevel> {
+    let levels = cfg.num_quote_levels.max(1);
+    let mut bid_room = (max_position - position).max(0.0);
+    let mut ask_room = (max_position + position).max(0.0);
+    let mut out = Vec::with_capacity(levels * 2);
+    for i in 0..levels {
+        let spread_mult = cfg.level_spacing_multiplier.powi(i as i32);
+        let size_mult = cfg.level_size_decay.powi(i as i32);
+        let size = (bid_size * size_mult).min(bid_room);
+        if size > 0.0 {
+            bid_room -= size;
+            out.push(QuoteLevel {
+                is_buy: true,
+                level: i,
+                price: mid - (mid - quote.bid_price) * spread_mult,
+                size,
+            });
+        }
+        let size = (ask_size * size_mult).min(ask_room);
+        if size > 0.0 {
+            ask_room -= size;
+            out.push(QuoteLevel {
+                is_buy: false,
+                level: i,
+                price: mid + (quote.ask_price - mid) * spread_mult,
+                size,
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::shm_depth_reader::PriceLevel;
+
+    fn cfg(imbalance_weight: f64) -> ExchangeConfig {
+        let mut cfg = AppConfig::default().backpack;
+        cfg.imbalance_weight = imbalance_weight;
+        cfg
+    }
+
+    fn inputs(imbalance: f64) -> QuoteInputs {
+        QuoteInputs {
+            mid: 3000.0,
+            vol_bps: 1.0,
+            momentum_bps: 0.0,
+            position: 0.0,
+            max_position: 1.0,
+            imbalance,
+            ..Default::default()
+        }
+    }
+
+    fn book(bid_sizes: [f64; 5], ask_sizes: [f64; 5]) -> ShmDepthSnapshot {
+        let mut depth = ShmDepthSnapshot::default();
+        for i in 0..5 {
+            depth.bids[i] = PriceLevel {
+                price: 2999.5 - i as f64 * 0.5,
+                size: bid_sizes[i],
+            };
+            depth.asks[i] = PriceLevel {
+                price: 3000.5 + i as f64 * 0.5,
+                size: ask_sizes[i],
+            };
+        }
+        depth
+    }
+
+    #[test]
+    fn test_depth_imbalance_only_counts_levels_within_band() {
+        // mid = 3000, 5 bps band = ±1.5 → levels 0..=2 on each side
+        let depth = book([3.0, 3.0, 3.0, 50.0, 50.0], [1.0, 1.0, 1.0, 0.0, 0.0]);
+        assert!((depth_imbalance(&depth, 5.0) - 0.5).abs() < 1e-12);
+        assert_eq!(depth_imbalance(&ShmDepthSnapshot::default(), 5.0), 0.0);
+        assert_eq!(imbalance(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_heavy_bids_shade_up_and_widen_ask() {
+        let neutral = compute_quotes(&cfg(0.5), &inputs(0.0));
+        let depth = book([5.0; 5], [1.0; 5]);
+        let heavy_bid = compute_quotes(&cfg(0.5), &inputs(depth_imbalance(&depth, 10.0)));
+
+        assert!(heavy_bid.bid_price > neutral.bid_price);
+        assert!(heavy_bid.ask_spread_bps > neutral.ask_spread_bps);
+        assert_eq!(heavy_bid.bid_spread_bps, neutral.bid_spread_bps);
+    }
+
+    #[test]
+    fn test_heavy_asks_shade_down_and_widen_bid() {
+        let neutral = compute_quotes(&cfg(0.5), &inputs(0.0));
+        let depth = book([1.0; 5], [5.0; 5]);
+        let heavy_ask = compute_quotes(&cfg(0.5), &inputs(depth_imbalance(&depth, 10.0)));
+
+        assert!(heavy_ask.ask_price < neutral.ask_price);
+        assert!(heavy_ask.bid_spread_bps > neutral.bid_spread_bps);
+        assert_eq!(heavy_ask.ask_spread_bps, neutral.ask_spread_bps);
+    }
+
+    #[test]
+    fn test_avellaneda_stoikov_hand_computed() {
+        let p = AvellanedaParams {
+            gamma: 0.1,
+            kappa: 1.5,
+            time_horizon: 1.0,
+        };
+        // γσ²T = 0.1 × 0.25 × 1 = 0.025 → r = 100 − 2 × 0.025 = 99.95
+        assert!((reservation_price(100.0, 2.0, 0.5, &p) - 99.95).abs() < 1e-12);
+        // δ = 0.025 + 20 × ln(16/15) = 0.025 + 1.290770422751 = 1.315770422751
+        assert!((optimal_spread(0.5, &p) - 1.315_770_422_751).abs() < 1e-9);
+
+        let (bid, ask) = avellaneda_stoikov(100.0, 2.0, 0.5, &p);
+        assert!((bid - 99.292_114_788_624).abs() < 1e-9);
+        assert!((ask - 100.607_885_211_376).abs() < 1e-9);
+
+        // Short inventory shifts the reservation price up symmetrically
+        let (bid_s, ask_s) = avellaneda_stoikov(100.0, -2.0, 0.5, &p);
+        assert!((bid_s - (bid + 0.1)).abs() < 1e-9);
+        assert!((ask_s - (ask + 0.1)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compute_quotes_selects_avellaneda_model() {
+        let mut cfg = cfg(0.0);
+        cfg.quoting_model = QuotingModel::Avellaneda;
+        cfg.gamma = 0.1;
+        cfg.kappa = 1.5;
+        cfg.time_horizon_sec = 1.0;
+        cfg.min_spread_bps = 0.0;
+
+        // vol 50 bps on mid 100 → σ = 0.5
+        let inp = QuoteInputs {
+            mid: 100.0,
+            vol_bps: 50.0,
+            position: 2.0,
+            max_position: 10.0,
+            ..Default::default()
+        };
+        let q = compute_quotes(&cfg, &inp);
+        assert!((q.bid_price - 99.292_114_788_624).abs() < 1e-9);
+        assert!((q.ask_price - 100.607_885_211_376).abs() < 1e-9);
+
+        // The min-spread floor widens a too-tight A-S spread around r
+        cfg.min_spread_bps = 100.0;
+        let floored = compute_quotes(&cfg, &inp);
+        assert!((floored.ask_price - floored.bid_price - 2.0 * 0.9995).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_quote_levels_geometric_spacing_and_size_decay() {
+        let mut cfg = cfg(0.0);
+        let quote = Quote {
+            bid_price: 99.9,
+            ask_price: 100.2,
+            bid_spread_bps: 10.0,
+            ask_spread_bps: 20.0,
+        };
+
+        // Default single level is the quote itself
+        let single = quote_levels(&cfg, 100.0, &quote, 1.0, 1.0, 0.0, 10.0);
+        assert_eq!(single.len(), 2);
+        assert_eq!((single[0].price, single[1].price), (99.9, 100.2));
+
+        cfg.num_quote_levels = 3;
+        cfg.level_spacing_multiplier = 2.0;
+        cfg.level_size_decay = 0.5;
+        let levels = quote_levels(&cfg, 100.0, &quote, 1.0, 0.0, 0.0, 10.0);
+        let prices: Vec<f64> = levels.iter().map(|l| l.price).collect();
+        let sizes: Vec<f64> = levels.iter().map(|l| l.size).collect();
+        assert!(levels.iter().all(|l| l.is_buy));
+        assert!((prices[1] - 99.8).abs() < 1e-9 && (prices[2] - 99.6).abs() < 1e-9);
+        assert_eq!(sizes, vec![1.0, 0.5, 0.25]);
+
+        // Long 0.8 of 1.0: 0.2 of bid room left, the whole ask ladder fits in 1.8
+        let levels = quote_levels(&cfg, 100.0, &quote, 1.0, 1.0, 0.8, 1.0);
+        let side = |is_buy: bool| -> Vec<f64> {
+            levels
+                .iter()
+                .filter(|l| l.is_buy == is_buy)
+                .map(|l| l.size)
+                .collect()
+        };
+        assert_eq!(side(false), vec![1.0, 0.5, 0.25]);
+        assert_eq!(side(true).len(), 1);
+        assert!((side(true)[0] - 0.2).abs() < 1e-9);
+
+        // Flat with max 1.7: the third level is trimmed to the 0.2 left over
+        let levels = quote_levels(&cfg, 100.0, &quote, 1.0, 0.0, 0.0, 1.7);
+        let sizes: Vec<f64> = levels.iter().map(|l| l.size).collect();
+        assert_eq!(sizes.len(), 3);
+        assert!((sizes[2] - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_funding_skew_sign() {
+        let mut cfg = cfg(0.0);
+        cfg.funding_skew_weight = 2.0;
+        cfg.funding_interval_secs = 3600;
+        let at = |position: f64, funding_rate: f64| QuoteInputs {
+            position,
+            funding_rate,
+            secs_to_funding: Some(0.0),
+            ..inputs(0.0)
+        };
+        let base = |position: f64| {
+            compute_quotes(
+                &cfg,
+                &QuoteInputs {
+                    position,
+                    ..inputs(0.0)
+                },
+            )
+        };
+
+        // Long paying positive funding (1 bp): both prices shift down 2 bps
+        let long_pos = compute_quotes(&cfg, &at(0.5, 0.0001));
+        assert!((base(0.5).bid_price - long_pos.bid_price - 0.6).abs() < 1e-9);
+        assert!(long_pos.ask_price < base(0.5).ask_price);
+
+        // Short paying negative funding: shift up
+        let short_neg = compute_quotes(&cfg, &at(-0.5, -0.0001));
+        assert!((short_neg.bid_price - base(-0.5).bid_price - 0.6).abs() < 1e-9);
+
+        // Receiving side (long, negative funding) and flat inventory are not skewed
+        assert_eq!(compute_quotes(&cfg, &at(0.5, -0.0001)), base(0.5));
+        assert_eq!(compute_quotes(&cfg, &at(0.0, 0.0001)), base(0.0));
+
+        // Skew fades with distance to settlement: none at the start of the period
+        let early = QuoteInputs {
+            secs_to_funding: Some(3600.0),
+            ..at(0.5, 0.0001)
+        };
+        assert_eq!(compute_quotes(&cfg, &early), base(0.5));
+    }
+
+    #[test]
+    fn test_funding_window_widens_both_sides() {
+        let mut cfg = cfg(0.0);
+        cfg.funding_window_widen_bps = 3.0;
+        cfg.funding_window_mins = 5;
+        let with_secs = |secs: f64| QuoteInputs {
+            secs_to_funding: Some(secs),
+            ..inputs(0.0)
+        };
+        let base = compute_quotes(&cfg, &inputs(0.0));
+
+        let inside = compute_quotes(&cfg, &with_secs(120.0));
+        assert!((base.bid_price - inside.bid_price - 0.9).abs() < 1e-9);
+        assert!((inside.ask_price - base.ask_price - 0.9).abs() < 1e-9);
+        assert_eq!(inside.bid_spread_bps, base.bid_spread_bps + 3.0);
+        assert_eq!(compute_quotes(&cfg, &with_secs(600.0)), base);
+    }
+
+    #[test]
+    fn test_zero_weight_ignores_imbalance() {
+        assert_eq!(
+            compute_quotes(&cfg(0.0), &inputs(0.8)),
+            compute_quotes(&cfg(0.0), &inputs(0.0))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Post-only reject recovery for the Backpack / EdgeX market makers.
+//!
+//! A post-only quote is rejected when the market moves through it between
+//! mid capture and submission, leaving that side empty until the next
+//! requote. The quote task rereads the BBO from the shm matrix, reprices one
+//! tick inside the opposite best and resubmits at most once.
+
+use crate::config::round_to_tick;
+use crate::shm_reader::ShmReader;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often the reprice count is logged
+pub const REPRICE_LOG_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Reads the current BBO of one (symbol, exchange) slot at retry time.
+pub struct LiveBbo {
+    reader: ShmReader,
+    symbol_id: u16,
+    exchange_id: u8,
+}
+
+impl LiveBbo {
+    /// `None` when the matrix is not mapped (feeder down, tests).
+    pub fn open(symbol_id: u16, exchange_id: u8) -> Option<Arc<Self>> {
+        let reader = ShmReader::open("/dev/shm/aleph-matrix", 2048).ok()?;
+        Some(Arc::new(Self {
+            reader,
+            symbol_id,
+            exchange_id,
+        }))
+    }
+
+    /// Repriced post-only level from the current book (see [`reprice_post_only`]).
+    pub fn reprice(&self, is_buy: bool, rejected: f64, tick: f64) -> Option<f64> {
+        let bbo = self
+            .reader
+            .read_bbo_strict(self.symbol_id, self.exchange_id)?;
+        reprice_post_only(is_buy, rejected, bbo.bid_price, bbo.ask_price, tick)
+    }
+}
+
+/// One tick inside the opposite best (bid: `best_ask - tick`, ask:
+/// `best_bid + tick`), never more aggressive than the rejected price.
+/// `None` on an empty or crossed book.
+pub fn reprice_post_only(
+    is_buy: bool,
+    rejected: f64,
+    best_bid: f64,
+    best_ask: f64,
+    tick: f64,
+) -> Option<f64> {
+    if best_bid <= 0.0 || best_ask <= 0.0 || best_bid >= best_ask {
+        return None;
+    }
+    let price = if is_buy {
+        rejected.min(best_ask - tick)
+    } else {
+        rejected.max(best_bid + tick)
+    };
+    Some(round_to_tick(price, tick)).filter(|p| *p > 0.0)
+}
+
+/// Reprices since the last report, shared between the strategy and its quote tasks.
+#[derive(Debug)]
+pub struct RepriceCounter {
+    count: AtomicU64,
+    since: Mutex<Instant>,
+}
+
+impl Default for RepriceCounter {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            since: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl RepriceCounter {
+    pub fn record(&self) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count for the window once `every` has elapsed (then restarts), else `None`.
+    pub fn take_if_elapsed(&self, every: Duration) -> Option<u64> {
+        let mut since = self.since.lock();
+        if since.elapsed() < every {
+            return None;
+        }
+        *since = Instant::now();
+        Some(self.count.swap(0, Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reprice_steps_back_behind_opposite_best() {
+        // Bid at 2001 rejected after the ask dropped to 2000.50
+        assert_eq!(
+            reprice_post_only(true, 2001.0, 2000.0, 2000.5, 0.01),
+            Some(2000.49)
+        );
+        assert_eq!(
+            reprice_post_only(false, 1999.0, 1999.5, 2000.0, 0.01),
+            Some(1999.51)
+        );
+        // Book moved back: keep the original, less aggressive level
+        assert_eq!(
+            reprice_post_only(true, 1998.0, 2000.0, 2000.5, 0.01),
+            Some(1998.0)
+        );
+        assert_eq!(reprice_post_only(true, 2001.0, 0.0, 2000.5, 0.01), None);
+        assert_eq!(reprice_post_only(true, 2001.0, 2000.5, 2000.5, 0.01), None);
+
+        let counter = RepriceCounter::default();
+        counter.record();
+        counter.record();
+        assert_eq!(counter.take_if_elapsed(Duration::from_secs(3600)), None);
+        assert_eq!(counter.take_if_elapsed(Duration::ZERO), Some(2));
+        assert_eq!(counter.take_if_elapsed(Duration::ZERO), Some(0));
+    }
+}
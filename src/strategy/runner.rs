@@ -0,0 +1,151 @@
+//! One OS thread per strategy, fed by its own [`BboMailbox`].
+//!
+//! Each thread enters the Tokio runtime (strategies spawn their REST work onto
+//! it), optionally pins itself to a core, and runs its own idle timer, so a slow
+//! `on_idle` only delays that strategy. Setting the shared stop flag makes every
+//! thread leave its loop and run `on_shutdown` to completion before exiting.
+//! Warm-up state is restored from `STATE_DIR` on start and written back on stop.
+
+use crate::data_plane::BboMailbox;
+use crate::strategy::Strategy;
+use crate::strategy::state::{self, MAX_STATE_AGE, STATE_DIR};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+
+pub fn spawn_strategy_thread(
+    mut strategy: Box<dyn Strategy + Send>,
+    mailbox: Arc<BboMailbox>,
+    cpu_core: Option<usize>,
+    idle_interval: Duration,
+    max_spread_bps: f64,
+    stop: Arc<AtomicBool>,
+    runtime: Handle,
+) -> std::io::Result<JoinHandle<()>> {
+    let name = format!("strategy-{}", strategy.name());
+    thread::Builder::new().name(name).spawn(move || {
+        let _rt = runtime.enter();
+        if let Some(core) = cpu_core {
+            if core_affinity::set_for_current(core_affinity::CoreId { id: core }) {
+                info!("📌 {} pinned to CPU core {}", strategy.name(), core);
+            } else {
+                error!("⚠️ Failed to pin {} to CPU core {}", strategy.name(), core);
+            }
+        }
+
+        match state::load(&mut *strategy, Path::new(STATE_DIR), MAX_STATE_AGE) {
+            Ok(true) => info!("♻️ {} restored warm state", strategy.name()),
+            Ok(false) => {}
+            Err(e) => warn!("⚠️ {} state not restored: {:#}", strategy.name(), e),
+        }
+
+        let mut batch = Vec::new();
+        let mut last_idle = Instant::now();
+        while !stop.load(Ordering::Acquire) {
+            let woke = mailbox.wait(idle_interval);
+            mailbox.drain(&mut batch);
+            for update in batch.drain(..) {
+                if update.bbo.is_sane(max_spread_bps) {
+                    strategy.on_global_sequence(update.global_seq);
+                    strategy.on_bbo_update(update.symbol_id, update.exchange_id, &update.bbo);
+                }
+            }
+            // Quiet interval, or a steady stream that would otherwise starve the timer
+            if !woke || last_idle.elapsed() >= idle_interval {
+                strategy.on_idle();
+                last_idle = Instant::now();
+            }
+        }
+
+        if let Err(e) = state::save(&*strategy, Path::new(STATE_DIR)) {
+            warn!("⚠️ {} state not saved: {:#}", strategy.name(), e);
+        }
+        info!("♻️ {} running shutdown hook...", strategy.name());
+        runtime.block_on(strategy.on_shutdown());
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_plane::{BboFanout, BboUpdate};
+    use crate::shm_reader::ShmBboMessage;
+    use parking_lot::Mutex;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    #[derive(Default)]
+    struct Calls {
+        bids: Vec<f64>,
+        idles: usize,
+        shutdown: bool,
+    }
+
+    struct Recorder(Arc<Mutex<Calls>>);
+
+    impl Strategy for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+        fn on_bbo_update(&mut self, _symbol_id: u16, _exchange_id: u8, bbo: &ShmBboMessage) {
+            self.0.lock().bids.push(bbo.bid_price);
+        }
+        fn on_idle(&mut self) {
+            self.0.lock().idles += 1;
+        }
+        fn on_shutdown(&mut self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            let calls = self.0.clone();
+            Box::pin(async move {
+                // Runs on the runtime, so timers work during cleanup
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                calls.lock().shutdown = true;
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_thread_delivers_updates_idles_and_shuts_down() {
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        let (fanout, mut mailboxes) = BboFanout::new(1);
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = spawn_strategy_thread(
+            Box::new(Recorder(calls.clone())),
+            mailboxes.remove(0),
+            None,
+            Duration::from_millis(1),
+            500.0,
+            stop.clone(),
+            Handle::current(),
+        )
+        .unwrap();
+
+        for bid in [100.0, 101.0] {
+            fanout.publish(BboUpdate {
+                symbol_id: 1002,
+                exchange_id: 3,
+                bbo: ShmBboMessage {
+                    bid_price: bid,
+                    ask_price: bid + 1.0,
+                    ..Default::default()
+                },
+                global_seq: 0,
+            });
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        stop.store(true, Ordering::Release);
+        tokio::task::spawn_blocking(move || handle.join())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let calls = calls.lock();
+        assert_eq!(calls.bids, vec![100.0, 101.0]);
+        assert!(calls.idles > 0);
+        assert!(calls.shutdown);
+    }
+}
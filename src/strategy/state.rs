@@ -0,0 +1,90 @@
+//! Strategy state snapshots for hot restart.
+//!
+//! On graceful shutdown the runner writes each strategy's `save_state` bytes
+//! to `/dev/shm/aleph-state-{name}.bin`; on startup a snapshot younger than
+//! `MAX_STATE_AGE` is handed back through `load_state`, so the price history,
+//! volatility estimate and balance-derived limits survive a restart instead of
+//! being rebuilt during a warm-up in which the MM would quote blind.
+
+use crate::strategy::Strategy;
+use crate::strategy::volatility::VolSnapshot;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const STATE_DIR: &str = "/dev/shm";
+
+/// Older snapshots describe a market that has moved on
+pub const MAX_STATE_AGE: Duration = Duration::from_secs(300);
+
+/// Warm-up state of the Backpack / EdgeX market makers. `last_update` is not
+/// kept: the book was swept on shutdown, so the first idle tick requotes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MmState {
+    pub symbol_id: u16,
+    pub last_mid: f64,
+    pub last_quoted_mid: f64,
+    pub mid_history: Vec<f64>,
+    pub vol: VolSnapshot,
+    pub max_position: f64,
+    pub base_size: f64,
+    pub stop_loss_usd: f64,
+    pub account_equity: f64,
+}
+
+impl MmState {
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).context("encode MM state")
+    }
+
+    /// Decode a snapshot, refusing one taken for another symbol.
+    pub fn decode(bytes: &[u8], symbol_id: u16) -> Result<Self> {
+        let state: Self = bincode::deserialize(bytes).context("decode MM state")?;
+        anyhow::ensure!(
+            state.symbol_id == symbol_id,
+            "state is for symbol {}, not {}",
+            state.symbol_id,
+            symbol_id
+        );
+        Ok(state)
+    }
+}
+
+pub fn state_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("aleph-state-{}.bin", name))
+}
+
+/// Write the strategy's snapshot (nothing for stateless strategies). Goes
+/// through a temp file so a crash mid-write never leaves a torn snapshot.
+pub fn save(strategy: &dyn Strategy, dir: &Path) -> Result<Option<PathBuf>> {
+    let bytes = strategy.save_state()?;
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let path = state_path(dir, strategy.name());
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, &bytes).with_context(|| format!("write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("rename to {}", path.display()))?;
+    Ok(Some(path))
+}
+
+/// Restore the strategy from its snapshot if one exists and is younger than
+/// `max_age`. Returns whether state was loaded.
+pub fn load(strategy: &mut dyn Strategy, dir: &Path, max_age: Duration) -> Result<bool> {
+    let path = state_path(dir, strategy.name());
+    let Ok(meta) = std::fs::metadata(&path) else {
+        return Ok(false);
+    };
+    let age = meta
+        .modified()
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .unwrap_or(Duration::ZERO);
+    if age > max_age {
+        return Ok(false);
+    }
+    let bytes = std::fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+    strategy.load_state(&bytes)?;
+    Ok(true)
+}
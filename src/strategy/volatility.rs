@@ -0,0 +1,211 @@
+//! Realized volatility estimator shared by the Backpack / EdgeX market makers.
+//!
+//! Two modes (`cfg.vol_model`):
+//! - `window`: population std-dev of per-tick returns over the last `vol_window`
+//!   mids (the original behavior; bps per tick).
+//! - `ewma`: time-weighted EWMA of the variance rate r²/Δt with a half-life in
+//!   seconds, so bursts of ticks do not dominate and quiet periods decay on the
+//!   clock rather than on tick count. Reported as bps over one second.
+
+use crate::config::{ExchangeConfig, VolModel};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Samples required before either mode reports a value.
+const MIN_SAMPLES: usize = 10;
+
+/// Estimator state without its configuration, for hot restart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VolSnapshot {
+    pub mids: Vec<f64>,
+    pub last: Option<(f64, f64)>,
+    pub var_rate: f64,
+    pub samples: usize,
+}
+
+pub struct Volatility {
+    model: VolModel,
+    window: usize,
+    half_life_secs: f64,
+    mids: VecDeque<f64>,
+    /// (mid, timestamp in seconds) of the last accepted EWMA tick
+    last: Option<(f64, f64)>,
+    /// EWMA of squared returns per second (bps² / s)
+    var_rate: f64,
+    samples: usize,
+}
+
+impl Volatility {
+    pub fn new(model: VolModel, window: usize, half_life_secs: f64) -> Self {
+        Self {
+            model,
+            window: window.max(2),
+            half_life_secs: half_life_secs.max(f64::EPSILON),
+            mids: VecDeque::with_capacity(window + 1),
+            last: None,
+            var_rate: 0.0,
+            samples: 0,
+        }
+    }
+
+    pub fn from_config(cfg: &ExchangeConfig) -> Self {
+        Self::new(cfg.vol_model, cfg.vol_window, cfg.vol_half_life_secs)
+    }
+
+    /// Feed a mid observed at `timestamp_ns` (feeder tick time).
+    pub fn update(&mut self, mid: f64, timestamp_ns: u64) {
+        if mid <= 0.0 {
+            return;
+        }
+        match self.model {
+            VolModel::Window => {
+                self.mids.push_back(mid);
+                if self.mids.len() > self.window {
+                    self.mids.pop_front();
+                }
+                self.samples = self.mids.len();
+            }
+            VolModel::Ewma => {
+                let ts = timestamp_ns as f64 / 1e9;
+                let Some((prev_mid, prev_ts)) = self.last else {
+                    self.last = Some((mid, ts));
+                    self.samples = 1;
+                    return;
+                };
+                let dt = ts - prev_ts;
+                // Same-timestamp or out-of-order tick: fold into the next interval
+                if dt <= 0.0 {
+                    return;
+                }
+                let ret_bps = (mid - prev_mid) / prev_mid * 10_000.0;
+                let alpha = 1.0 - 0.5_f64.powf(dt / self.half_life_secs);
+                let rate = ret_bps * ret_bps / dt;
+                self.var_rate = if self.samples == 1 {
+                    rate
+                } else {
+                    self.var_rate + alpha * (rate - self.var_rate)
+                };
+                self.last = Some((mid, ts));
+                self.samples += 1;
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> VolSnapshot {
+        VolSnapshot {
+            mids: self.mids.iter().copied().collect(),
+            last: self.last,
+            var_rate: self.var_rate,
+            samples: self.samples,
+        }
+    }
+
+    /// Resume from a snapshot taken under either model; whatever the current
+    /// model has no data for stays cold.
+    pub fn restore(&mut self, snap: VolSnapshot) {
+        let skip = snap.mids.len().saturating_sub(self.window);
+        self.mids = snap.mids.into_iter().skip(skip).collect();
+        self.last = snap.last;
+        self.var_rate = snap.var_rate;
+        self.samples = match self.model {
+            VolModel::Window => self.mids.len(),
+            VolModel::Ewma if self.last.is_some() => snap.samples,
+            VolModel::Ewma => 0,
+        };
+    }
+
+    /// Current volatility in bps, or `None` while warming up.
+    pub fn vol_bps(&self) -> Option<f64> {
+        if self.samples < MIN_SAMPLES {
+            return None;
+        }
+        match self.model {
+            VolModel::Window => {
+                let returns: Vec<f64> = self
+                    .mids
+                    .iter()
+                    .zip(self.mids.iter().skip(1))
+                    .map(|(prev, cur)| ((cur - prev) / prev) * 10_000.0)
+                    .collect();
+                let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+                let variance =
+                    returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+                Some(variance.sqrt())
+            }
+            VolModel::Ewma => Some(self.var_rate.sqrt()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Irregular tick spacing (seconds), cycled through the scripted path.
+    const SPACING: [f64; 6] = [0.05, 1.7, 0.3, 0.01, 2.5, 0.8];
+
+    /// Feed `n` ticks whose returns have variance rate exactly `sigma²` per second
+    /// (|r| = σ·√Δt, alternating sign). Returns the final (mid, t).
+    fn feed(vol: &mut Volatility, mut mid: f64, mut t: f64, sigma: f64, n: usize) -> (f64, f64) {
+        for i in 0..n {
+            let dt = SPACING[i % SPACING.len()];
+            t += dt;
+            let r_bps = sigma * dt.sqrt() * if i % 2 == 0 { 1.0 } else { -1.0 };
+            mid *= 1.0 + r_bps / 10_000.0;
+            vol.update(mid, (t * 1e9) as u64);
+        }
+        (mid, t)
+    }
+
+    #[test]
+    fn test_ewma_converges_with_irregular_spacing() {
+        let mut vol = Volatility::new(VolModel::Ewma, 120, 10.0);
+        vol.update(3000.0, 1_000_000_000);
+        assert_eq!(vol.vol_bps(), None);
+
+        feed(&mut vol, 3000.0, 1.0, 2.0, 300);
+        // Bursty 10ms ticks and 2.5s gaps alike carry σ² per second
+        assert!((vol.vol_bps().unwrap() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ewma_regime_change_decays_by_half_life() {
+        let half_life = 10.0;
+        let mut vol = Volatility::new(VolModel::Ewma, 120, half_life);
+        vol.update(3000.0, 1_000_000_000);
+        let (mut mid, mut t) = feed(&mut vol, 3000.0, 1.0, 1.0, 200);
+        assert!((vol.vol_bps().unwrap() - 1.0).abs() < 1e-6);
+
+        // Switch to σ = 3 for exactly one half-life of clock time (5 ticks × 2s)
+        for i in 0..5 {
+            t += 2.0;
+            let r_bps = 3.0 * 2.0_f64.sqrt() * if i % 2 == 0 { 1.0 } else { -1.0 };
+            mid *= 1.0 + r_bps / 10_000.0;
+            vol.update(mid, (t * 1e9) as u64);
+        }
+        // Variance rate is halfway between 1 and 9
+        assert!((vol.vol_bps().unwrap().powi(2) - 5.0).abs() < 1e-6);
+
+        // Duplicate timestamp is ignored rather than producing an infinite rate
+        vol.update(mid * 1.01, (t * 1e9) as u64);
+        assert!((vol.vol_bps().unwrap().powi(2) - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_window_mode_matches_per_tick_std() {
+        let mut vol = Volatility::new(VolModel::Window, 10, 30.0);
+        // Timestamps are ignored; the two outliers fall out of the 10-mid window
+        let mut mids = vec![50.0, 60.0];
+        mids.extend([100.0, 101.0].repeat(5));
+        for (i, mid) in mids.into_iter().enumerate() {
+            vol.update(mid, i as u64);
+        }
+        // 9 returns: five +1/100, four -1/101
+        let r: Vec<f64> = (0..9)
+            .map(|i| if i % 2 == 0 { 1.0 / 100.0 } else { -1.0 / 101.0 } * 10_000.0)
+            .collect();
+        let mean = r.iter().sum::<f64>() / 9.0;
+        let expected = (r.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 9.0).sqrt();
+        assert!((vol.vol_bps().unwrap() - expected).abs() < 1e-9);
+    }
+}
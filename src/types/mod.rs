@@ -1,8 +1,10 @@
 //! Type definitions for AlephTX
 
 pub mod events;
+pub mod symbol_mapper;
 
 pub use events::{EventType, ShmPrivateEvent, ShmPrivateEventV2};
+pub use symbol_mapper::SymbolMapper;
 
 // Re-export common types from the old types.rs
 use rust_decimal::Decimal;
@@ -11,6 +13,24 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Symbol(pub String);
 
+/// Quote assets recognised when a pair has no separator (`BTCUSDT`).
+/// Longest first so `USDT` wins over `USD`.
+const KNOWN_QUOTES: &[&str] = &["FDUSD", "USDT", "USDC", "USDE", "USD", "EUR", "BTC", "ETH"];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SymbolError {
+    #[error("empty symbol")]
+    Empty,
+    #[error("cannot split {0:?} into base/quote")]
+    Unrecognized(String),
+    #[error("unknown exchange {0:?}")]
+    UnknownExchange(String),
+    #[error("{exchange} does not list {market:?} markets")]
+    UnsupportedMarket { exchange: String, market: Market },
+    #[error("{symbol} is not mapped on {exchange}")]
+    Unmapped { symbol: String, exchange: String },
+}
+
 impl Symbol {
     pub fn new(s: impl Into<String>) -> Self {
         Self(s.into().to_uppercase())
@@ -18,6 +38,71 @@ impl Symbol {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Canonical `BASE/QUOTE` from its two assets.
+    pub fn pair(base: &str, quote: &str) -> Self {
+        Self::new(format!("{}/{}", base, quote))
+    }
+
+    /// Normalize any exchange spelling (`BTCUSDT`, `btc-usdt`, `BTC_USDT`,
+    /// `BTC/USDT`, `ETH_USDC_PERP`) to the canonical `BASE/QUOTE`.
+    pub fn parse_pair(s: &str) -> Result<Self, SymbolError> {
+        let (base, quote) = split_pair(s)?;
+        Ok(Self(format!("{}/{}", base, quote)))
+    }
+
+    /// Spell a pair the way `exchange` expects it (`BTCUSDT` on Binance,
+    /// `BTC_USDT` on Backpack, ...). Unparseable input is returned uppercased.
+    pub fn exchange_format(s: &str, exchange: &str) -> String {
+        let Ok((base, quote)) = split_pair(s) else {
+            return s.trim().to_uppercase();
+        };
+        match exchange.to_ascii_lowercase().as_str() {
+            "binance" | "bybit" | "bitget" | "mexc" => format!("{}{}", base, quote),
+            "backpack" | "gate" => format!("{}_{}", base, quote),
+            "okx" | "coinbase" | "kucoin" => format!("{}-{}", base, quote),
+            "hyperliquid" | "lighter" => base,
+            _ => format!("{}/{}", base, quote),
+        }
+    }
+
+    /// Base asset of a canonical `BASE/QUOTE` symbol.
+    pub fn base(&self) -> &str {
+        self.0.split('/').next().unwrap_or(&self.0)
+    }
+
+    /// Quote asset of a canonical `BASE/QUOTE` symbol (empty otherwise).
+    pub fn quote(&self) -> &str {
+        self.0.split_once('/').map_or("", |(_, q)| q)
+    }
+}
+
+fn split_pair(s: &str) -> Result<(String, String), SymbolError> {
+    let upper = s.trim().to_uppercase();
+    if upper.is_empty() {
+        return Err(SymbolError::Empty);
+    }
+    let mut parts: Vec<&str> = upper
+        .split(['-', '_', '/'])
+        .filter(|p| !p.is_empty())
+        .collect();
+    // Perp markers carry no asset information
+    if parts.len() > 2 && matches!(parts.last(), Some(&("PERP" | "SWAP"))) {
+        parts.pop();
+    }
+    match parts.as_slice() {
+        [base, quote] => Ok((base.to_string(), quote.to_string())),
+        [joined] => KNOWN_QUOTES
+            .iter()
+            .find_map(|q| {
+                joined
+                    .strip_suffix(q)
+                    .filter(|base| !base.is_empty())
+                    .map(|base| (base.to_string(), q.to_string()))
+            })
+            .ok_or_else(|| SymbolError::Unrecognized(s.to_string())),
+        _ => Err(SymbolError::Unrecognized(s.to_string())),
+    }
 }
 
 impl std::fmt::Display for Symbol {
@@ -86,11 +171,57 @@ pub struct Order {
     pub updated_at: u64,
 }
 
+/// Direction and size of a perp position. The size lives in the variant,
+/// so a short can never carry a positive quantity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionSide {
+    Long(Decimal),
+    Short(Decimal),
+    #[default]
+    Flat,
+}
+
+impl PositionSide {
+    /// From a signed quantity as venues report it (negative = short).
+    pub fn from_signed(quantity: Decimal) -> Self {
+        if quantity.is_zero() {
+            Self::Flat
+        } else if quantity.is_sign_negative() {
+            Self::Short(-quantity)
+        } else {
+            Self::Long(quantity)
+        }
+    }
+
+    /// Long positive, short negative, flat zero.
+    pub fn signed_quantity(&self) -> Decimal {
+        match *self {
+            Self::Long(q) => q,
+            Self::Short(q) => -q,
+            Self::Flat => Decimal::ZERO,
+        }
+    }
+
+    /// Absolute size.
+    pub fn quantity(&self) -> Decimal {
+        self.signed_quantity().abs()
+    }
+
+    /// Order side that reduces the position (`None` when flat).
+    pub fn closing_side(&self) -> Option<Side> {
+        match self {
+            Self::Long(_) => Some(Side::Sell),
+            Self::Short(_) => Some(Side::Buy),
+            Self::Flat => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub symbol: Symbol,
-    pub side: Side,
-    pub quantity: Decimal,
+    pub side: PositionSide,
     pub entry_price: Decimal,
     pub unrealized_pnl: Decimal,
     pub opened_at: u64,
@@ -109,6 +240,46 @@ impl Balance {
     }
 }
 
+/// Perp funding rate (per settlement period) and the next settlement time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FundingRate {
+    /// e.g. 0.0001 = 1 bp; positive → longs pay shorts
+    pub rate: f64,
+    pub next_funding_ms: u64,
+}
+
+/// Margin account totals (USD) as reported by the exchange.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarginSummary {
+    /// Collateral value plus unrealized PnL
+    pub net_equity: f64,
+    /// Margin held by open positions and resting orders
+    pub used_margin: f64,
+    /// Margin free for new orders
+    pub available_margin: f64,
+}
+
+impl MarginSummary {
+    /// Largest order size at `price` the free margin supports at `leverage`.
+    pub fn max_order_size(&self, price: f64, leverage: f64) -> f64 {
+        if price <= 0.0 {
+            return 0.0;
+        }
+        (self.available_margin * leverage.max(1.0) / price).max(0.0)
+    }
+}
+
+/// Trading rules of one contract, from the venue's market metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractSpec {
+    pub tick_size: f64,
+    pub lot_size: f64,
+    /// Minimum order value in the settlement asset (0 = none published)
+    pub min_notional: f64,
+    pub max_leverage: u32,
+    pub settlement_asset: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceLevel {
     pub price: Decimal,
@@ -170,3 +341,53 @@ pub struct OrderResponse {
     pub filled_price: Option<Decimal>,
     pub created_at: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pair_normalizes_exchange_spellings() {
+        let canonical = Symbol::new("BTC/USDT");
+        for s in ["BTCUSDT", "btc-usdt", "BTC_USDT", "BTC/USDT", " btc/usdt "] {
+            assert_eq!(Symbol::parse_pair(s).unwrap(), canonical, "{s}");
+        }
+        let perp = Symbol::parse_pair("ETH_USDC_PERP").unwrap();
+        assert_eq!((perp.base(), perp.quote()), ("ETH", "USDC"));
+        assert_eq!(Symbol::parse_pair("ETHBTC").unwrap().as_str(), "ETH/BTC");
+
+        assert_eq!(Symbol::parse_pair(""), Err(SymbolError::Empty));
+        assert!(matches!(
+            Symbol::parse_pair("USDT"),
+            Err(SymbolError::Unrecognized(_))
+        ));
+        assert!(matches!(
+            Symbol::parse_pair("A-B-C"),
+            Err(SymbolError::Unrecognized(_))
+        ));
+    }
+
+    #[test]
+    fn test_position_side_from_signed_quantity() {
+        let (long, short) = (Decimal::new(15, 1), Decimal::new(-15, 1));
+        assert_eq!(PositionSide::from_signed(long), PositionSide::Long(long));
+        assert_eq!(PositionSide::from_signed(short), PositionSide::Short(long));
+        assert_eq!(PositionSide::from_signed(Decimal::ZERO), PositionSide::Flat);
+
+        assert_eq!(PositionSide::Short(long).signed_quantity(), short);
+        assert_eq!(PositionSide::Short(long).quantity(), long);
+        assert_eq!(PositionSide::Long(long).closing_side(), Some(Side::Sell));
+        assert_eq!(PositionSide::Short(long).closing_side(), Some(Side::Buy));
+        assert_eq!(PositionSide::Flat.closing_side(), None);
+    }
+
+    #[test]
+    fn test_exchange_format_round_trips() {
+        assert_eq!(Symbol::exchange_format("BTC/USDT", "binance"), "BTCUSDT");
+        assert_eq!(Symbol::exchange_format("btc-usdc", "Backpack"), "BTC_USDC");
+        assert_eq!(Symbol::exchange_format("BTCUSDT", "okx"), "BTC-USDT");
+        assert_eq!(Symbol::exchange_format("ETH_USDC", "hyperliquid"), "ETH");
+        assert_eq!(Symbol::exchange_format("BTC_USDT", "unknown"), "BTC/USDT");
+        assert_eq!(Symbol::exchange_format("weird", "binance"), "WEIRD");
+    }
+}
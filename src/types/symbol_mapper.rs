@@ -0,0 +1,221 @@
+//! Canonical `BASE/QUOTE` symbols ↔ venue wire names, in both directions.
+//!
+//! Unlike the lenient `Symbol::exchange_format`, the mapper is strict: it
+//! knows which markets each venue lists and fails with a `SymbolError`
+//! instead of guessing, so an adapter never sends an order for a spelling
+//! the venue does not have.
+
+use super::{Market, Symbol, SymbolError, split_pair};
+
+/// EdgeX perp contracts (contract name `BTCUSD`, `ETHUSD`, ...)
+const EDGEX_CONTRACTS: &[(&str, u64)] = &[("BTC/USD", 10000001), ("ETH/USD", 10000002)];
+
+#[derive(Debug, Clone)]
+pub struct SymbolMapper {
+    edgex_contracts: Vec<(Symbol, u64)>,
+}
+
+impl Default for SymbolMapper {
+    fn default() -> Self {
+        Self {
+            edgex_contracts: EDGEX_CONTRACTS
+                .iter()
+                .map(|&(s, id)| (Symbol::new(s), id))
+                .collect(),
+        }
+    }
+}
+
+impl SymbolMapper {
+    /// Register (or override) the EdgeX contract id of a canonical symbol.
+    pub fn with_edgex_contract(mut self, symbol: Symbol, contract_id: u64) -> Self {
+        self.edgex_contracts.retain(|(s, _)| *s != symbol);
+        self.edgex_contracts.push((symbol, contract_id));
+        self
+    }
+
+    /// Wire name of `symbol` on `exchange`:
+    ///
+    /// | venue | spot | perp |
+    /// |-------|------|------|
+    /// | binance | `BTCUSDT` | `BTCUSDT` (USDⓈ-M API) |
+    /// | okx | `BTC-USDT` | `BTC-USDT-SWAP` |
+    /// | backpack | `ETH_USDC` | `ETH_USDC_PERP` |
+    /// | edgex | — | contract id, e.g. `10000002` |
+    pub fn to_exchange(
+        &self,
+        symbol: &Symbol,
+        exchange: &str,
+        market: Market,
+    ) -> Result<String, SymbolError> {
+        let (base, quote) = (symbol.base(), symbol.quote());
+        if base.is_empty() || quote.is_empty() {
+            return Err(SymbolError::Unrecognized(symbol.to_string()));
+        }
+        let exchange = exchange.to_ascii_lowercase();
+        match (exchange.as_str(), market) {
+            ("binance", Market::Spot | Market::Perp) => Ok(format!("{}{}", base, quote)),
+            ("okx", Market::Spot) => Ok(format!("{}-{}", base, quote)),
+            ("okx", Market::Perp) => Ok(format!("{}-{}-SWAP", base, quote)),
+            ("backpack", Market::Spot) => Ok(format!("{}_{}", base, quote)),
+            ("backpack", Market::Perp) => Ok(format!("{}_{}_PERP", base, quote)),
+            ("edgex", Market::Perp) => self
+                .edgex_contracts
+                .iter()
+                .find(|(s, _)| s == symbol)
+                .map(|(_, id)| id.to_string())
+                .ok_or_else(|| SymbolError::Unmapped {
+                    symbol: symbol.to_string(),
+                    exchange,
+                }),
+            ("binance" | "okx" | "backpack" | "edgex", _) => {
+                Err(SymbolError::UnsupportedMarket { exchange, market })
+            }
+            _ => Err(SymbolError::UnknownExchange(exchange)),
+        }
+    }
+
+    /// Canonical symbol and market of a venue wire name. Binance spells spot
+    /// and USDⓈ-M perps the same, so its names parse as `Market::Spot`.
+    pub fn from_exchange(
+        &self,
+        wire: &str,
+        exchange: &str,
+    ) -> Result<(Symbol, Market), SymbolError> {
+        let exchange = exchange.to_ascii_lowercase();
+        let upper = wire.trim().to_uppercase();
+        let unrecognized = || SymbolError::Unrecognized(wire.to_string());
+        let (pair, market) = match exchange.as_str() {
+            "binance" => {
+                if upper.contains(['-', '_', '/']) {
+                    return Err(unrecognized());
+                }
+                (upper.as_str(), Market::Spot)
+            }
+            "okx" => match upper.strip_suffix("-SWAP") {
+                Some(pair) => (pair, Market::Perp),
+                None => (upper.as_str(), Market::Spot),
+            },
+            "backpack" => match upper.strip_suffix("_PERP") {
+                Some(pair) => (pair, Market::Perp),
+                None => (upper.as_str(), Market::Spot),
+            },
+            "edgex" => {
+                let id: u64 = upper.parse().map_err(|_| unrecognized())?;
+                return self
+                    .edgex_contracts
+                    .iter()
+                    .find(|&&(_, c)| c == id)
+                    .map(|(s, _)| (s.clone(), Market::Perp))
+                    .ok_or(SymbolError::Unmapped {
+                        symbol: upper,
+                        exchange,
+                    });
+            }
+            _ => return Err(SymbolError::UnknownExchange(exchange)),
+        };
+        // The remaining separator must be the venue's own
+        let separator = match exchange.as_str() {
+            "okx" => Some('-'),
+            "backpack" => Some('_'),
+            _ => None,
+        };
+        if pair
+            .chars()
+            .any(|c| matches!(c, '-' | '_' | '/') && Some(c) != separator)
+        {
+            return Err(unrecognized());
+        }
+        let (base, quote) = split_pair(pair).map_err(|_| unrecognized())?;
+        Ok((Symbol::pair(&base, &quote), market))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips_per_venue() {
+        let mapper = SymbolMapper::default();
+        let cases = [
+            ("BTC/USDT", "binance", Market::Spot, "BTCUSDT"),
+            ("BTC/USDT", "okx", Market::Spot, "BTC-USDT"),
+            ("BTC/USDT", "okx", Market::Perp, "BTC-USDT-SWAP"),
+            ("SOL/USDC", "backpack", Market::Spot, "SOL_USDC"),
+            ("ETH/USDC", "backpack", Market::Perp, "ETH_USDC_PERP"),
+            ("ETH/USD", "edgex", Market::Perp, "10000002"),
+            ("BTC/USD", "EdgeX", Market::Perp, "10000001"),
+        ];
+        for (canonical, exchange, market, wire) in cases {
+            let symbol = Symbol::new(canonical);
+            assert_eq!(mapper.to_exchange(&symbol, exchange, market).unwrap(), wire);
+            assert_eq!(
+                mapper.from_exchange(wire, exchange).unwrap(),
+                (symbol, market)
+            );
+        }
+        // Same spelling on the futures API
+        let btc = Symbol::new("BTC/USDT");
+        assert_eq!(
+            mapper.to_exchange(&btc, "binance", Market::Perp).unwrap(),
+            "BTCUSDT"
+        );
+
+        let sol = Symbol::new("SOL/USD");
+        let mapper = mapper.with_edgex_contract(sol.clone(), 10000003);
+        assert_eq!(
+            mapper.from_exchange("10000003", "edgex").unwrap(),
+            (sol, Market::Perp)
+        );
+    }
+
+    #[test]
+    fn test_unmapped_symbols_are_errors() {
+        let mapper = SymbolMapper::default();
+        let eth = Symbol::new("ETH/USDC");
+        assert!(matches!(
+            mapper.to_exchange(&eth, "edgex", Market::Perp),
+            Err(SymbolError::Unmapped { .. })
+        ));
+        assert!(matches!(
+            mapper.to_exchange(&eth, "edgex", Market::Spot),
+            Err(SymbolError::UnsupportedMarket {
+                market: Market::Spot,
+                ..
+            })
+        ));
+        assert!(matches!(
+            mapper.to_exchange(&eth, "okx", Market::Futures),
+            Err(SymbolError::UnsupportedMarket { .. })
+        ));
+        assert_eq!(
+            mapper.to_exchange(&eth, "kraken", Market::Spot),
+            Err(SymbolError::UnknownExchange("kraken".to_string()))
+        );
+        assert!(
+            mapper
+                .to_exchange(&Symbol::new("ETH"), "okx", Market::Spot)
+                .is_err()
+        );
+
+        assert!(matches!(
+            mapper.from_exchange("10000099", "edgex"),
+            Err(SymbolError::Unmapped { .. })
+        ));
+        let malformed = [
+            ("ETHUSD", "edgex"),
+            ("BTC-USDT", "binance"),
+            ("ETH-USDC", "backpack"),
+        ];
+        for (wire, exchange) in malformed {
+            assert!(
+                matches!(
+                    mapper.from_exchange(wire, exchange),
+                    Err(SymbolError::Unrecognized(_))
+                ),
+                "{wire} on {exchange}"
+            );
+        }
+    }
+}